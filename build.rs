@@ -27,7 +27,7 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     #[cfg(feature = "generate")]
     bindgen(&crate_root);
 
-    #[cfg(not(feature = "no_sys"))]
+    #[cfg(all(feature = "std", not(feature = "no_sys")))]
     {
         println!("cargo:rustc-link-search={}", lib_path.display());
         // https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-link-lib
@@ -2,6 +2,7 @@
 //!
 //! It is intended to be memory-safe using the power of Rust.
 //!
+//! - prelude: Re-exports of the commonly used address resolution types (`use commonlibsse_ng::prelude::*;`)
 //! - rel: Module related to Relocation (calculate address from ID according to version, read module information, parse version information, etc.)
 //! - rex: Module related to Win32 API
 //! - skse: Module related to SKSE.exe (version information of SkyrimSE.exe, etc.)
@@ -9,13 +10,19 @@
 //! - sys: C++ binding (when no_sys feature is disabled): This is enabled by default.
 //!        However, currently the types generated by rust-bindgen are difficult to use, and since
 //!        the inline function does not exist in the .lib, only the inline function can be called.
+//!
+//! Without the `std` feature(enabled by default), the crate is `no_std` and only the OS independent value types are compiled.
+//! (`rel::version`, `rel::module::Segment`, `rex::ularge_integer`, `rex::time`)
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
 
-
+#[cfg(feature = "std")]
+pub mod prelude;
 pub mod rel;
 pub mod rex;
+#[cfg(feature = "std")]
 pub mod skse;
 
 /// FFI
 #[doc(hidden)]
-#[cfg(not(feature = "no_sys"))]
+#[cfg(all(feature = "std", not(feature = "no_sys")))]
 pub mod sys;
@@ -0,0 +1,18 @@
+//! Re-exports of the commonly used address resolution types.
+//!
+//! # Example
+//! ```no_run
+//! use commonlibsse_ng::prelude::*;
+//!
+//! let id = RelocationID::new(11045, 11163, 0);
+//! let address = id.address();
+//! ```
+
+pub use crate::rel::id::{RelocationID, VariantID, ID};
+pub use crate::rel::offset::{Offset, VariantOffset};
+pub use crate::rel::relocation::Relocation;
+pub use crate::rel::version::{ReadableVersion, Version};
+pub use crate::rel::ResolvableAddress;
+
+#[cfg(feature = "win_api")]
+pub use crate::rel::module::{ModuleState, Runtime};
@@ -0,0 +1,110 @@
+//! Memoization of resolved addresses.
+
+use crate::rel::id::DataBaseError;
+use crate::rel::module::ModuleState;
+use crate::rel::ResolvableAddress;
+use std::sync::Mutex;
+
+/// Wrapper that remembers the address of `A` after the first successful resolution.
+///
+/// Resolving an `ID`/`RelocationID` acquires the database lock and searches it on every call.
+/// `Cached` skips them after the first success, until [`ModuleState::reset`], [`ModuleState::rebase`]
+/// or [`IdDatabase::reload`](crate::rel::id::IdDatabase::reload) is called.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::RelocationID;
+/// use commonlibsse_ng::rel::{Cached, ResolvableAddress as _};
+///
+/// static FOO: Cached<RelocationID> = Cached::new(RelocationID::new(12345, 67890, 12345));
+///
+/// let address = FOO.address().unwrap(); // Resolved
+/// let address = FOO.address().unwrap(); // Cached
+/// ```
+///
+/// # Note
+/// If [`ModuleState::reset`] races with a resolution, that call may return the address from before the reset,
+/// just as the uncached `A` would. It is cached with the old generation, so the next call resolves again.
+#[derive(Debug)]
+pub struct Cached<A> {
+    inner: A,
+    /// `(ModuleState::generation + 1, address)` at the time the address was resolved. `0` means unresolved.
+    ///
+    /// Both are stored as one unit, so that an address is never paired with a generation it was not resolved in.
+    /// (The lock is only held to copy the pair, never while resolving.)
+    resolved: Mutex<(usize, usize)>,
+}
+
+impl<A> Cached<A> {
+    /// Creates an unresolved cache of `inner`.
+    #[inline]
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            resolved: Mutex::new((0, 0)),
+        }
+    }
+
+    /// Returns the wrapped address type.
+    #[inline]
+    pub const fn get(&self) -> &A {
+        &self.inner
+    }
+}
+
+impl<A> ResolvableAddress for Cached<A>
+where
+    A: ResolvableAddress,
+{
+    /// Returns the offset of the wrapped address type. (Not cached)
+    #[inline]
+    fn offset(&self) -> Result<usize, DataBaseError> {
+        self.inner.offset()
+    }
+
+    /// Returns the cached address, resolving it if it is unresolved or the module has been reset since.
+    fn address(&self) -> Result<usize, DataBaseError> {
+        // Read before resolving: if the module changes during the resolution, the pair is stored with the old generation
+        // and is resolved again by the next call.
+        let generation = ModuleState::generation().wrapping_add(1);
+        let (cached_generation, cached_address) =
+            *self.resolved.lock().unwrap_or_else(|err| err.into_inner());
+        if cached_generation == generation {
+            return Ok(cached_address);
+        }
+
+        let address = self.inner.address()?;
+        *self.resolved.lock().unwrap_or_else(|err| err.into_inner()) = (generation, address);
+        Ok(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rel::offset::Offset;
+
+    #[test]
+    fn test_cached_address() {
+        let offset = Cached::new(Offset::new(0x10));
+
+        let expected = ModuleState::base_address().unwrap() + 0x10;
+        assert_eq!(offset.address().unwrap(), expected);
+        assert_eq!(offset.address().unwrap(), expected);
+
+        // Resolved again after the invalidation. (Only bumps the generation, without resetting the global module.)
+        ModuleState::bump_generation();
+        assert_eq!(offset.address().unwrap(), expected);
+        assert_ne!(offset.resolved.lock().unwrap().0, 0);
+    }
+
+    #[test]
+    fn test_cached_stale_generation() {
+        let offset = Cached::new(Offset::new(0x10));
+        let expected = ModuleState::base_address().unwrap() + 0x10;
+
+        // An address stored with an older generation is never returned.
+        *offset.resolved.lock().unwrap() = (ModuleState::generation(), 0xdead);
+        assert_eq!(offset.address().unwrap(), expected);
+    }
+}
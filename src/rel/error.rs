@@ -0,0 +1,85 @@
+//! Top-level error of the address resolution pipeline.
+
+/// Any error that the address resolution pipeline(version parsing, module detection, address library loading) may return.
+///
+/// Each variant is transparent, so `?` converts the specific error types into this,
+/// and the display is the same as the original one. Match on the variants for fine-grained handling.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::ID;
+/// use commonlibsse_ng::rel::module::ModuleState;
+/// use commonlibsse_ng::rel::ResolvableAddress as _;
+///
+/// fn load() -> Result<(), commonlibsse_ng::rel::Error> {
+///     let version = ModuleState::version()?;
+///     let min: commonlibsse_ng::rel::version::Version = "1.5.97".parse()?;
+///     if version >= min {
+///         println!("{:#x}", ID::new(11045).address()?);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, snafu::Snafu)]
+pub enum Error {
+    /// Failed to parse a version string.
+    #[snafu(transparent)]
+    Version {
+        source: crate::rel::version::VersionParseError,
+    },
+
+    /// Failed to read the file version of a module.
+    #[snafu(transparent)]
+    FileVersion {
+        source: crate::rel::version::FileVersionError,
+    },
+
+    /// Failed to initialize the module.
+    #[snafu(transparent)]
+    ModuleInit {
+        source: crate::rel::module::ModuleInitError,
+    },
+
+    /// Failed to get the module state.
+    #[snafu(transparent)]
+    ModuleState {
+        source: crate::rel::module::ModuleStateError,
+    },
+
+    /// Failed to load or query the address library.
+    #[snafu(transparent)]
+    DataBase {
+        source: crate::rel::id::DataBaseError,
+    },
+
+    /// Failed to operate the shared memory of the address library.
+    #[snafu(transparent)]
+    MemoryMap {
+        source: crate::rel::id::shared_rwlock::MemoryMapError,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rel::version::{Version, VersionParseError};
+
+    #[test]
+    fn test_from_specific_errors() {
+        fn parse(s: &str) -> Result<Version, Error> {
+            Ok(s.parse()?)
+        }
+
+        let err = parse("1..2").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Version {
+                source: VersionParseError::MissingNumber { part: 1 }
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            VersionParseError::MissingNumber { part: 1 }.to_string()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Comparison of two address libraries.
+//!
+//! Useful when regenerating `version-*.bin` per game build, to see which IDs changed their offset.
+
+use super::id_database::IdDatabase;
+use super::Mapping;
+
+/// An ID whose offset differs between two address libraries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChangedOffset {
+    /// The ID present in both libraries.
+    pub id: u64,
+    /// Offset in the old library.
+    pub old_offset: u64,
+    /// Offset in the new library.
+    pub new_offset: u64,
+}
+
+/// Result of [`diff`]. All lists are sorted by ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AddressLibraryDiff {
+    /// Mappings only in the new library.
+    pub added: Vec<Mapping>,
+    /// Mappings only in the old library.
+    pub removed: Vec<Mapping>,
+    /// IDs in both libraries whose offsets differ.
+    pub changed: Vec<ChangedOffset>,
+}
+
+impl AddressLibraryDiff {
+    /// Compares two tables by merge-joining them. O(n + m)
+    ///
+    /// Both `old` and `new` must be sorted by `id`. (e.g. the address library table)
+    /// Otherwise, the result is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::{AddressLibraryDiff, ChangedOffset, Mapping};
+    ///
+    /// let old = [Mapping { id: 1, offset: 0x10 }, Mapping { id: 2, offset: 0x20 }];
+    /// let new = [Mapping { id: 2, offset: 0x28 }, Mapping { id: 3, offset: 0x30 }];
+    ///
+    /// let diff = AddressLibraryDiff::from_mappings(&old, &new);
+    /// assert_eq!(diff.added, [Mapping { id: 3, offset: 0x30 }]);
+    /// assert_eq!(diff.removed, [Mapping { id: 1, offset: 0x10 }]);
+    /// assert_eq!(diff.changed, [ChangedOffset { id: 2, old_offset: 0x20, new_offset: 0x28 }]);
+    /// ```
+    pub fn from_mappings(old: &[Mapping], new: &[Mapping]) -> Self {
+        use core::cmp::Ordering;
+
+        let mut diff = Self::default();
+        let mut old_iter = old.iter().peekable();
+        let mut new_iter = new.iter().peekable();
+
+        loop {
+            match (old_iter.peek(), new_iter.peek()) {
+                (Some(&old), Some(&new)) => match old.id.cmp(&new.id) {
+                    Ordering::Less => {
+                        diff.removed.push(old.clone());
+                        old_iter.next();
+                    }
+                    Ordering::Greater => {
+                        diff.added.push(new.clone());
+                        new_iter.next();
+                    }
+                    Ordering::Equal => {
+                        if old.offset != new.offset {
+                            diff.changed.push(ChangedOffset {
+                                id: old.id,
+                                old_offset: old.offset,
+                                new_offset: new.offset,
+                            });
+                        }
+                        old_iter.next();
+                        new_iter.next();
+                    }
+                },
+                (Some(_), None) => {
+                    diff.removed.extend(old_iter.cloned());
+                    break;
+                }
+                (None, Some(_)) => {
+                    diff.added.extend(new_iter.cloned());
+                    break;
+                }
+                (None, None) => break,
+            }
+        }
+
+        diff
+    }
+
+    /// Returns `true` if the two libraries have the same mappings.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two loaded address libraries, `old` and `new`.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::{diff, IdDatabase};
+/// use commonlibsse_ng::rel::module::Runtime;
+/// use commonlibsse_ng::rel::version::Version;
+///
+/// let old = IdDatabase::load("versionlib-1.6.1130.0.bin", Version::new(1, 6, 1130, 0), Runtime::Ae).unwrap();
+/// let new = IdDatabase::load("versionlib-1.6.1170.0.bin", Version::new(1, 6, 1170, 0), Runtime::Ae).unwrap();
+///
+/// for changed in diff(&old, &new).changed {
+///     println!("{}: {:#x} -> {:#x}", changed.id, changed.old_offset, changed.new_offset);
+/// }
+/// ```
+pub fn diff(old: &IdDatabase, new: &IdDatabase) -> AddressLibraryDiff {
+    let old = old.read_poison_free();
+    let new = new.read_poison_free();
+    AddressLibraryDiff::from_mappings(&old, &new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn mapping(id: u64, offset: u64) -> Mapping {
+        Mapping { id, offset }
+    }
+
+    #[test]
+    fn test_diff_mappings() {
+        let old = [
+            mapping(1, 0x10),
+            mapping(3, 0x30),
+            mapping(4, 0x40),
+            mapping(6, 0x60),
+        ];
+        let new = [
+            mapping(2, 0x20),
+            mapping(3, 0x30),
+            mapping(4, 0x48),
+            mapping(7, 0x70),
+            mapping(8, 0x80),
+        ];
+
+        let diff = AddressLibraryDiff::from_mappings(&old, &new);
+        assert_eq!(
+            diff.added,
+            [mapping(2, 0x20), mapping(7, 0x70), mapping(8, 0x80)]
+        );
+        assert_eq!(diff.removed, [mapping(1, 0x10), mapping(6, 0x60)]);
+        assert_eq!(
+            diff.changed,
+            [ChangedOffset {
+                id: 4,
+                old_offset: 0x40,
+                new_offset: 0x48
+            }]
+        );
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_diff_same_or_empty() {
+        let table = [mapping(1, 0x10), mapping(2, 0x20)];
+        assert!(AddressLibraryDiff::from_mappings(&table, &table).is_empty());
+        assert!(AddressLibraryDiff::from_mappings(&[], &[]).is_empty());
+
+        let diff = AddressLibraryDiff::from_mappings(&[], &table);
+        assert_eq!(diff.added, table);
+        let diff = AddressLibraryDiff::from_mappings(&table, &[]);
+        assert_eq!(diff.removed, table);
+    }
+}
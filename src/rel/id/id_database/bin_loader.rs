@@ -1,56 +1,375 @@
+use crate::rel::id::id_database::decompress::LibraryReader;
 use crate::rel::id::id_database::header::Header;
 use crate::rel::id::id_database::unpack::unpack_file;
-use crate::rel::id::id_database::{DataBaseError, FailedUnpackFileSnafu};
-use crate::rel::id::shared_rwlock::SharedRwLock;
+use crate::rel::id::id_database::MappingTable;
+use crate::rel::id::id_database::{
+    DataBaseError, FailedDecompressFileSnafu, FailedUnpackFileSnafu,
+};
 use crate::rel::id::Mapping;
 use crate::rel::version::Version;
 use snafu::ResultExt as _;
+use std::path::Path;
 
 /// Reads, parses, and writes binary database files into memory.
 /// Then returns the written memory.
 ///
 /// - `expected_fmt_ver`: Expected AddressLibrary format version. SE/VR: 1, AE: 2
+/// - `_module_name`: File name(or path) of the running executable, to warn if the library is made for another one.
+///   `None` for libraries loaded regardless of the running module.(e.g. offline tools)
+/// - `progress`: Called with `(done, total)` while unpacking. `total` is the address count of the header.
+///   If another plugin has already unpacked the same version, it is called once with `(total, total)`.
+///
+/// gzip/zstd compressed files are decompressed transparently. (Requires `gzip`/`zstd` feature)
 ///
 /// # Errors
 /// - If the specified path does not exist.
+/// - If the file is compressed but cannot be decompressed.
 /// - If the version without bin file mismatches with the runtime
 /// - If parsing of the data in the bin file fails.
 /// - Failure to allocate memory for bin file storage.
+/// - If the shared memory is poisoned while unpacking. (Not with `single_process` feature)
 pub(super) fn load_bin_file(
     path: &str,
     version: Version,
     expected_fmt_ver: u8,
-) -> Result<SharedRwLock<Mapping>, DataBaseError> {
+    _module_name: Option<&str>,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<MappingTable, DataBaseError> {
     use std::fs::File;
-    use std::io;
 
     let mut reader = {
         let file = File::open(path).map_err(|_| DataBaseError::AddressLibraryNotFound {
             path: path.to_string(),
         })?;
-        io::BufReader::new(file)
+        LibraryReader::new(Path::new(path), file).context(FailedDecompressFileSnafu { path })?
     };
 
-    let header = Header::from_reader(&mut reader, expected_fmt_ver)?;
+    let header = read_header(&mut reader, version, expected_fmt_ver)?;
+
+    #[cfg(feature = "tracing")]
+    if let Some(module_name) = _module_name {
+        warn_name_mismatch(path, &header, module_name);
+    }
+
+    alloc_table(&header, &mut reader, progress)
+}
+
+/// Parses an address library from any `reader` into the heap, without touching the shared memory.
+///
+/// Unlike [`load_bin_file`], the reader is used as it is. (i.e. no decompression)
+///
+/// # Errors
+/// - If the version of the header mismatches with `version`.
+/// - If parsing of the header or mappings fails.
+pub(crate) fn read_mappings<R>(
+    reader: &mut R,
+    version: Version,
+    expected_fmt_ver: u8,
+) -> Result<Vec<Mapping>, DataBaseError>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    let header = read_header(reader, version, expected_fmt_ver)?;
+    let mut mappings = vec![Mapping { id: 0, offset: 0 }; header.address_count()];
+    unpack_file(&mut mappings, reader, header.pointer_size(), &mut |_, _| {})
+        .context(FailedUnpackFileSnafu)?;
+    Ok(mappings)
+}
+
+/// Returns `path`(`*.bin`) if it exists, otherwise its compressed variant that exists.
+///
+/// The compressed variants(`*.bin.gz`/`*.bin.zst`) are only probed with the `gzip`/`zstd` features.
+/// If none exists, `path` is returned as it is, so that the error reports the plain name.
+pub(super) fn find_library_file(path: String) -> String {
+    const COMPRESSED_EXTENSIONS: &[&str] = &[
+        #[cfg(feature = "gzip")]
+        "gz",
+        #[cfg(feature = "zstd")]
+        "zst",
+    ];
+
+    if Path::new(&path).exists() {
+        return path;
+    }
+    let found = COMPRESSED_EXTENSIONS
+        .iter()
+        .map(|ext| format!("{path}.{ext}"))
+        .find(|compressed| Path::new(compressed).exists());
+    found.unwrap_or(path)
+}
+
+/// Parses the header and checks that it is made for `version`.
+fn read_header<R>(
+    reader: &mut R,
+    version: Version,
+    expected_fmt_ver: u8,
+) -> Result<Header, DataBaseError>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    let header = Header::from_reader(reader, expected_fmt_ver)?;
     if header.version != version {
         return Err(DataBaseError::VersionMismatch {
             expected: version,
             actual: header.version,
         });
     }
+    Ok(header)
+}
 
-    let (mem_map, is_created) = {
-        let shared_id =
-            windows::core::HSTRING::from(format!("CommonLibSSEOffsets-rs-v2-{version}"));
-        SharedRwLock::new(&shared_id, header.address_count())
+/// Warns if the module name embedded in the library differs from the running executable.
+///
+/// This is only a warning, because a renamed executable(e.g. a downgrade patcher) is still compatible as long as the version matches.
+#[cfg(feature = "tracing")]
+fn warn_name_mismatch(path: &str, header: &Header, module_name: &str) {
+    if !is_same_module(header.name(), module_name) {
+        tracing::warn!(
+            "The address library {path} is made for `{}`, but the running executable is `{module_name}`.",
+            header.name()
+        );
     }
-    .map_err(|err| DataBaseError::MemoryMapError { source: err })?;
+}
+
+/// Compares the module name embedded in the library with `module_name`, which may be a full path.
+/// (Only the file name is compared, case-insensitively as Windows does.)
+#[cfg(any(test, feature = "tracing"))]
+fn is_same_module(header_name: &str, module_name: &str) -> bool {
+    Path::new(module_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.eq_ignore_ascii_case(header_name))
+}
+
+/// Default prefix of the shared memory name. Plugins using the same prefix share the address library.
+#[cfg(not(feature = "single_process"))]
+pub const DEFAULT_MAPPING_PREFIX: &str = "CommonLibSSEOffsets-rs-v2";
+
+/// Prefix set by [`set_shared_mapping_prefix`]. `None` means [`DEFAULT_MAPPING_PREFIX`].
+#[cfg(not(feature = "single_process"))]
+static MAPPING_PREFIX: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
 
-    if is_created {
-        let mut mem_map = mem_map.write().map_err(|_| DataBaseError::Poisoned)?;
-        unpack_file(&mut mem_map, &mut reader, header.pointer_size())
-            .context(FailedUnpackFileSnafu)?;
+/// Changes the prefix of the shared memory name(`{prefix}-{version}`) holding the address library, for this process.
+///
+/// Tools and test harnesses can use a distinct prefix to get an isolated table, instead of attaching to
+/// the one shared by the plugins of a running game.
+/// The default is [`DEFAULT_MAPPING_PREFIX`], which must be kept to share the table with other plugins.
+///
+/// This only affects the address libraries loaded afterwards. Call it before the first address resolution.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::id::{set_shared_mapping_prefix, shared_mapping_prefix};
+///
+/// set_shared_mapping_prefix("MyTool-Offsets");
+/// assert_eq!(shared_mapping_prefix(), "MyTool-Offsets");
+/// ```
+#[cfg(not(feature = "single_process"))]
+pub fn set_shared_mapping_prefix(prefix: &str) {
+    *MAPPING_PREFIX.lock().unwrap_or_else(|err| err.into_inner()) = Some(prefix.to_string());
+}
+
+/// Returns the current prefix of the shared memory name. (See [`set_shared_mapping_prefix`])
+#[cfg(not(feature = "single_process"))]
+pub fn shared_mapping_prefix() -> String {
+    MAPPING_PREFIX
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+        .unwrap_or_else(|| DEFAULT_MAPPING_PREFIX.to_string())
+}
+
+/// Returns the name of the shared memory holding the address library of `version`.
+#[cfg(not(feature = "single_process"))]
+pub(crate) fn mapping_name(version: &Version) -> windows::core::HSTRING {
+    mapping_name_with(&shared_mapping_prefix(), version)
+}
+
+#[cfg(not(feature = "single_process"))]
+fn mapping_name_with(prefix: &str, version: &Version) -> windows::core::HSTRING {
+    windows::core::HSTRING::from(format!("{prefix}-{version}"))
+}
+
+/// Places the mappings into memory shared across plugins.
+///
+/// If another plugin has already unpacked the same version, its table is reused as is.
+#[cfg(not(feature = "single_process"))]
+fn alloc_table(
+    header: &Header,
+    reader: &mut LibraryReader,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<MappingTable, DataBaseError> {
+    use crate::rel::id::shared_rwlock::{SharedMemInit, SharedRwLock};
+
+    let mem_map = SharedRwLock::new(&mapping_name(&header.version), header.address_count())
+        .map_err(|err| DataBaseError::MemoryMapError { source: err })?;
+
+    match mem_map {
+        SharedMemInit::Created(mem_map) => {
+            {
+                let mut mem_map = mem_map.write().unwrap_or_else(|err| err.into_inner());
+                unpack_file(&mut mem_map, reader, header.pointer_size(), progress)
+                    .context(FailedUnpackFileSnafu)?;
+            }
+            Ok(mem_map)
+        }
+        SharedMemInit::Opened(mem_map) => {
+            progress(header.address_count(), header.address_count());
+            Ok(mem_map)
+        }
+    }
+}
+
+/// Places the mappings into the heap owned by this plugin.
+#[cfg(feature = "single_process")]
+fn alloc_table(
+    header: &Header,
+    reader: &mut LibraryReader,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<MappingTable, DataBaseError> {
+    let mut mappings =
+        vec![Mapping { id: 0, offset: 0 }; header.address_count()].into_boxed_slice();
+    unpack_file(&mut mappings, reader, header.pointer_size(), progress)
+        .context(FailedUnpackFileSnafu)?;
+    Ok(parking_lot::RwLock::new(mappings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rel::id::id_database::{fixtures, IdDatabase};
+    use crate::rel::module::Runtime;
+
+    #[test]
+    fn test_load_synthetic_address_library() {
+        let file = fixtures::TempAddressLibrary::new(Version::new(1, 2, 3, 4));
+        let db = file.load().unwrap_or_else(|err| panic!("{err}"));
+
+        for mapping in fixtures::EXPECTED {
+            assert_eq!(
+                db.id_to_offset(mapping.id).unwrap(),
+                mapping.offset as usize
+            );
+        }
+
+        let err = db.id_to_offset(1).unwrap_err();
+        assert!(matches!(err, DataBaseError::NotFoundId { id: 1, .. }));
+        let message = err.to_string();
+        assert!(message.contains("1.2.3.4"), "{message}");
+        assert!(message.contains("runtime: SE"), "{message}");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_load_gzip_address_library() {
+        use std::io::Write as _;
+
+        let version = Version::new(1, 2, 3, 7);
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder
+            .write_all(&fixtures::address_library(1, &version))
+            .unwrap();
+        let file = fixtures::TempAddressLibrary::with_contents(
+            version,
+            "bin.gz",
+            &encoder.finish().unwrap(),
+        );
+        let db = file.load().unwrap_or_else(|err| panic!("{err}"));
+
+        for mapping in fixtures::EXPECTED {
+            assert_eq!(
+                db.id_to_offset(mapping.id).unwrap(),
+                mapping.offset as usize
+            );
+        }
     }
 
-    Ok(mem_map)
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_find_compressed_library_file() {
+        let file =
+            fixtures::TempAddressLibrary::with_contents(Version::new(1, 2, 3, 11), "bin.gz", &[]);
+        let plain = file.path().strip_suffix(".gz").unwrap().to_string();
+        assert_eq!(find_library_file(plain), file.path());
+
+        let missing = "NotExist/version-1-2-3-11.bin".to_string();
+        assert_eq!(find_library_file(missing.clone()), missing);
+    }
+
+    #[test]
+    fn test_is_same_module() {
+        assert!(is_same_module("SkyrimSE.exe", "SkyrimSE.exe"));
+        assert!(is_same_module("SkyrimSE.exe", "skyrimse.EXE"));
+        assert!(is_same_module(
+            "SkyrimSE.exe",
+            r"C:\Games\Skyrim Special Edition\SkyrimSE.exe"
+        ));
+        assert!(!is_same_module("SkyrimSE.exe", r"C:\Games\SkyrimVR.exe"));
+        assert!(!is_same_module("SkyrimSE.exe", ""));
+    }
+
+    #[test]
+    fn test_load_multiple_versions_at_once() {
+        use crate::rel::id::OffsetToID;
+
+        let old_file = fixtures::TempAddressLibrary::new(Version::new(1, 2, 3, 8));
+        let new_file = fixtures::TempAddressLibrary::new(Version::new(1, 2, 3, 9));
+        let old = old_file.load().unwrap_or_else(|err| panic!("{err}"));
+        let new = new_file.load().unwrap_or_else(|err| panic!("{err}"));
+
+        assert_eq!(old.version(), &Version::new(1, 2, 3, 8));
+        assert_eq!(new.version(), &Version::new(1, 2, 3, 9));
+        for db in [&old, &new] {
+            let table = OffsetToID::from_database(db);
+            for mapping in fixtures::EXPECTED {
+                assert_eq!(
+                    db.id_to_offset(mapping.id).unwrap(),
+                    mapping.offset as usize
+                );
+                assert_eq!(table.get_id(mapping.offset), Some(mapping.id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_offset_to_id_from_reader() {
+        use crate::rel::id::OffsetToID;
+
+        let version = Version::new(1, 2, 3, 10);
+        let mut reader = std::io::Cursor::new(fixtures::address_library(1, &version));
+        let table = OffsetToID::from_reader(&mut reader, version.clone(), Runtime::Se)
+            .unwrap_or_else(|err| panic!("{err}"));
+        for mapping in fixtures::EXPECTED {
+            assert_eq!(table.get_id(mapping.offset), Some(mapping.id));
+        }
+
+        // The format version follows the runtime.
+        let mut reader = std::io::Cursor::new(fixtures::address_library(1, &version));
+        let result = OffsetToID::from_reader(&mut reader, version, Runtime::Ae);
+        assert!(matches!(
+            result,
+            Err(DataBaseError::HeaderParseError { .. })
+        ));
+    }
+
+    #[cfg(not(feature = "single_process"))]
+    #[test]
+    fn test_mapping_name_prefix() {
+        let version = Version::new(1, 6, 1170, 0);
+        assert_eq!(
+            mapping_name_with(DEFAULT_MAPPING_PREFIX, &version).to_string(),
+            "CommonLibSSEOffsets-rs-v2-1.6.1170.0"
+        );
+        assert_eq!(
+            mapping_name_with("Isolated", &version).to_string(),
+            "Isolated-1.6.1170.0"
+        );
+    }
+
+    #[test]
+    fn test_load_version_mismatch() {
+        let file = fixtures::TempAddressLibrary::new(Version::new(1, 2, 3, 5));
+
+        let result = IdDatabase::load(file.path(), Version::new(1, 2, 3, 6), Runtime::Se);
+        assert!(matches!(result, Err(DataBaseError::VersionMismatch { .. })));
+    }
 }
@@ -0,0 +1,365 @@
+//! Transparent decompression of compressed address library files.
+//!
+//! Some distributors ship `version-*.bin` as `.gz`/`.zst` to reduce the size.
+//! The compression is detected by the magic header (or the file extension as a fallback), and
+//! the decoders themselves are only compiled in with the `gzip`/`zstd` features.
+//!
+//! `Header::from_reader` needs `Seek`, which the decoders cannot provide.
+//! Therefore, a compressed file is fully decompressed into memory first.
+//! (The buffer is dropped as soon as the mappings have been unpacked into the shared memory.)
+
+use crate::rel::id::id_database::io_error::clone_io_error;
+use snafu::ResultExt as _;
+use std::fs::File;
+use std::io::{self, BufRead as _, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Compression format of an address library file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain `version-*.bin`
+    None,
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+    const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+    /// Detects the compression from the first bytes of the file, falling back to the extension.
+    ///
+    /// The plain format begins with the format version(`1` or `2` as `i32`), so it never collides
+    /// with the magic headers.
+    pub fn detect(path: &Path, head: &[u8]) -> Self {
+        if head.starts_with(&Self::GZIP_MAGIC) {
+            return Self::Gzip;
+        }
+        if head.starts_with(&Self::ZSTD_MAGIC) {
+            return Self::Zstd;
+        }
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => Self::Gzip,
+            Some(ext) if ext.eq_ignore_ascii_case("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+impl core::fmt::Display for Compression {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::None => "uncompressed",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        })
+    }
+}
+
+/// Upper bound of the decompressed size.
+///
+/// Real address libraries are a few MiB, so anything larger is a broken file or a decompression bomb.
+pub const MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// `Read + Seek` over an address library file, whether compressed or not.
+#[derive(Debug)]
+pub enum LibraryReader {
+    /// Uncompressed file read directly.
+    Plain(BufReader<File>),
+    /// Fully decompressed file contents.
+    Decompressed(Cursor<Vec<u8>>),
+}
+
+impl LibraryReader {
+    /// Wraps an opened address library file, decompressing it if necessary.
+    ///
+    /// # Errors
+    /// - If reading the file fails.
+    /// - If the file is compressed but the feature for the format is disabled.
+    /// - If the compressed stream is broken.
+    /// - If the decompressed size exceeds [`MAX_DECOMPRESSED_SIZE`].
+    pub fn new(path: &Path, file: File) -> Result<Self, DecompressError> {
+        let mut reader = BufReader::new(file);
+        let compression = Compression::detect(path, reader.fill_buf().context(ReadSnafu)?);
+
+        match compression {
+            Compression::None => Ok(Self::Plain(reader)),
+            compression => decompress(compression, reader, MAX_DECOMPRESSED_SIZE)
+                .map(|bytes| Self::Decompressed(Cursor::new(bytes))),
+        }
+    }
+}
+
+impl Read for LibraryReader {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            Self::Decompressed(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for LibraryReader {
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::Plain(reader) => reader.seek(pos),
+            Self::Decompressed(reader) => reader.seek(pos),
+        }
+    }
+}
+
+/// Decompresses `reader` into memory, failing if the result exceeds `limit` bytes.
+fn decompress<R>(
+    compression: Compression,
+    reader: R,
+    limit: u64,
+) -> Result<Vec<u8>, DecompressError>
+where
+    R: Read,
+{
+    let mut bytes = vec![];
+
+    match compression {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            read_limited(
+                compression,
+                flate2::read::GzDecoder::new(reader),
+                limit,
+                &mut bytes,
+            )?;
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let decoder =
+                zstd::stream::read::Decoder::new(reader).context(DecodeSnafu { compression })?;
+            read_limited(compression, decoder, limit, &mut bytes)?;
+        }
+        #[cfg(not(feature = "gzip"))]
+        Compression::Gzip => {
+            return Err(DecompressError::FeatureDisabled {
+                compression,
+                feature: "gzip",
+            });
+        }
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => {
+            return Err(DecompressError::FeatureDisabled {
+                compression,
+                feature: "zstd",
+            });
+        }
+        Compression::None => {
+            reader
+                .take(limit)
+                .read_to_end(&mut bytes)
+                .context(ReadSnafu)?;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Reads the whole `decoder` into `bytes`, but at most `limit` bytes.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn read_limited<R>(
+    compression: Compression,
+    decoder: R,
+    limit: u64,
+    bytes: &mut Vec<u8>,
+) -> Result<(), DecompressError>
+where
+    R: Read,
+{
+    // One extra byte to tell "exactly `limit`" from "more than `limit`".
+    let len = decoder
+        .take(limit.saturating_add(1))
+        .read_to_end(bytes)
+        .context(DecodeSnafu { compression })?;
+    if len as u64 > limit {
+        return Err(DecompressError::TooLarge { compression, limit });
+    }
+    Ok(())
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub enum DecompressError {
+    /// Failed to read the address library file: {source}
+    Read { source: io::Error },
+
+    /// Failed to decode the {compression} compressed address library: {source}
+    Decode {
+        compression: Compression,
+        source: io::Error,
+    },
+
+    /// The address library is {compression} compressed, but the `{feature}` feature is disabled.
+    FeatureDisabled {
+        compression: Compression,
+        feature: &'static str,
+    },
+
+    /// The {compression} compressed address library decompresses to more than {limit} bytes.
+    TooLarge {
+        compression: Compression,
+        limit: u64,
+    },
+}
+
+// io::Error doesn't have `Clone`. Therefore, implement manually.
+impl Clone for DecompressError {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Read { source } => Self::Read {
+                source: clone_io_error(source),
+            },
+            Self::Decode {
+                compression,
+                source,
+            } => Self::Decode {
+                compression: *compression,
+                source: clone_io_error(source),
+            },
+            Self::FeatureDisabled {
+                compression,
+                feature,
+            } => Self::FeatureDisabled {
+                compression: *compression,
+                feature,
+            },
+            Self::TooLarge { compression, limit } => Self::TooLarge {
+                compression: *compression,
+                limit: *limit,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_by_magic() {
+        let path = Path::new("version-1-5-97-0.bin");
+        assert_eq!(
+            Compression::detect(path, &[0x1f, 0x8b, 0x08, 0x00]),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::detect(path, &[0x28, 0xb5, 0x2f, 0xfd]),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::detect(path, &[0x01, 0x00, 0x00, 0x00]),
+            Compression::None
+        );
+    }
+
+    #[test]
+    fn test_detect_by_extension() {
+        let head = [0x01, 0x00, 0x00, 0x00];
+        assert_eq!(
+            Compression::detect(Path::new("version-1-5-97-0.bin.gz"), &head),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::detect(Path::new("version-1-5-97-0.bin.ZST"), &head),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::detect(Path::new("version-1-5-97-0.bin"), &[]),
+            Compression::None
+        );
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_gzip_without_feature() {
+        let err =
+            decompress(Compression::Gzip, &[0x1f, 0x8b][..], MAX_DECOMPRESSED_SIZE).unwrap_err();
+        assert!(matches!(
+            err,
+            DecompressError::FeatureDisabled {
+                feature: "gzip",
+                ..
+            }
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_roundtrip() {
+        use std::io::Write as _;
+
+        let data = crate::rel::id::id_database::fixtures::body();
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&data).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(
+            decompress(
+                Compression::Gzip,
+                compressed.as_slice(),
+                MAX_DECOMPRESSED_SIZE
+            )
+            .unwrap(),
+            data
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_limit() {
+        use std::io::Write as _;
+
+        let data = crate::rel::id::id_database::fixtures::body();
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&data).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let limit = data.len() as u64;
+        assert_eq!(
+            decompress(Compression::Gzip, compressed.as_slice(), limit).unwrap(),
+            data
+        );
+        assert!(matches!(
+            decompress(Compression::Gzip, compressed.as_slice(), limit - 1),
+            Err(DecompressError::TooLarge { limit: actual, .. }) if actual == limit - 1
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_limit() {
+        let data = crate::rel::id::id_database::fixtures::body();
+        let compressed = zstd::stream::encode_all(data.as_slice(), 0).unwrap();
+
+        assert!(matches!(
+            decompress(Compression::Zstd, compressed.as_slice(), 1),
+            Err(DecompressError::TooLarge { .. })
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_roundtrip() {
+        let data = crate::rel::id::id_database::fixtures::body();
+        let compressed = zstd::stream::encode_all(data.as_slice(), 0).unwrap();
+
+        assert_eq!(
+            decompress(
+                Compression::Zstd,
+                compressed.as_slice(),
+                MAX_DECOMPRESSED_SIZE
+            )
+            .unwrap(),
+            data
+        );
+    }
+}
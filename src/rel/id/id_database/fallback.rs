@@ -0,0 +1,119 @@
+//! Signatures scanned for IDs missing in the address library. (See [`IdDatabase::register_fallback_signature`](super::IdDatabase::register_fallback_signature))
+
+use crate::rel::module::{ModuleState, SegmentName};
+use crate::rel::pattern::Pattern;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// ID -> signature registered by the plugin.
+static FALLBACK_SIGNATURES: Mutex<BTreeMap<u64, FallbackSignature>> = Mutex::new(BTreeMap::new());
+
+struct FallbackSignature {
+    pattern: Pattern,
+    segment: SegmentName,
+    /// `(ModuleState::generation, module offset)` found by the last scan, so that the segment is scanned only once
+    /// until the module is reset.
+    found: Option<(usize, usize)>,
+}
+
+/// Registers(or replaces) the signature of `id`.
+pub(super) fn register(id: u64, pattern: Pattern, segment: SegmentName) {
+    let mut signatures = FALLBACK_SIGNATURES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    signatures.insert(
+        id,
+        FallbackSignature {
+            pattern,
+            segment,
+            found: None,
+        },
+    );
+}
+
+/// Returns the module offset of `id` by scanning its registered signature,
+/// or `None` if no signature is registered or it is not found.
+///
+/// The lock is not held while scanning, so that the resolution of other IDs is not blocked.
+pub(super) fn resolve(id: u64) -> Option<usize> {
+    let generation = ModuleState::generation();
+    let (pattern, segment) = {
+        let signatures = FALLBACK_SIGNATURES
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let signature = signatures.get(&id)?;
+        if let Some((found_generation, offset)) = signature.found {
+            if found_generation == generation {
+                return Some(offset);
+            }
+        }
+        (signature.pattern.clone(), signature.segment)
+    };
+
+    let offset = scan(&pattern, segment)?;
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        "ID {id} is missing in the address library, but its fallback signature was found at {offset:#x}."
+    );
+
+    let mut signatures = FALLBACK_SIGNATURES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    // Not memoized if the signature was replaced during the scan.
+    if let Some(signature) = signatures.get_mut(&id) {
+        if signature.pattern == pattern && signature.segment == segment {
+            signature.found = Some((generation, offset));
+        }
+    }
+    Some(offset)
+}
+
+/// Scans `segment` of the running module for `pattern`, and returns the module offset of the first match.
+fn scan(pattern: &Pattern, segment: SegmentName) -> Option<usize> {
+    let segment = ModuleState::map_or_init(|module| module.try_segment(segment))
+        .ok()
+        .flatten()?;
+    if segment.size == 0 {
+        return None;
+    }
+    // SAFETY: The segment is a section of the running module, which stays mapped while the module is loaded.
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            (segment.proxy_base + segment.address as usize) as *const u8,
+            segment.size as usize,
+        )
+    };
+    Some(segment.address as usize + pattern.find(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_fallback() {
+        const ID: u64 = u64::MAX - 1;
+        assert_eq!(resolve(ID), None);
+
+        let text = ModuleState::map_or_init(|module| module.segment(SegmentName::Textx)).unwrap();
+        assert!(text.size >= 16, "{text:?}");
+        let head = unsafe {
+            core::slice::from_raw_parts((text.proxy_base + text.address as usize) as *const u8, 16)
+        };
+        let pattern = Pattern::from(head);
+        let found = pattern.find(head).unwrap(); // The first occurrence(0)
+
+        register(ID, pattern, SegmentName::Textx);
+        assert_eq!(resolve(ID), Some(text.address as usize + found));
+        // Memoized
+        assert_eq!(resolve(ID), Some(text.address as usize + found));
+
+        // Scanned again after the module is reset.
+        let before = ModuleState::generation();
+        ModuleState::bump_generation();
+        assert_eq!(resolve(ID), Some(text.address as usize + found));
+        let signatures = FALLBACK_SIGNATURES.lock().unwrap();
+        let (found_generation, _) = signatures[&ID].found.unwrap();
+        assert!(found_generation > before);
+    }
+}
@@ -0,0 +1,137 @@
+//! Synthetic address library fixtures for tests.
+//!
+//! The real address library is large and cannot be committed, so this builds a tiny but valid one
+//! that covers all the delta-encoding branches of `unpack_file`.
+
+use super::{DataBaseError, IdDatabase};
+use crate::rel::id::Mapping;
+use crate::rel::module::Runtime;
+use crate::rel::version::Version;
+use std::path::PathBuf;
+
+/// Pointer size written in the fixture header.
+pub const PTR_SIZE: u32 = 8;
+
+/// Expected mappings in encoding order. (not sorted by ID)
+#[rustfmt::skip]
+pub const EXPECTED: [Mapping; 10] = [
+    Mapping { id: 100,   offset: 0x1000 },  // low 0: u64 id,    high 0: u64 offset
+    Mapping { id: 101,   offset: 0x1001 },  // low 1: prev + 1,  high 1: prev + 1
+    Mapping { id: 106,   offset: 0x1011 },  // low 2: prev + u8, high 2: prev + u8
+    Mapping { id: 105,   offset: 0x1010 },  // low 3: prev - u8, high 3: prev - u8
+    Mapping { id: 361,   offset: 0x1110 },  // low 4: prev + u16, high 4: prev + u16
+    Mapping { id: 345,   offset: 0x1100 },  // low 5: prev - u16, high 5: prev - u16
+    Mapping { id: 500,   offset: 0x2000 },  // low 6: u16 id,    high 6: u16 offset
+    Mapping { id: 70000, offset: 0x30000 }, // low 7: u32 id,    high 7: u32 offset
+    Mapping { id: 70001, offset: 0x30008 }, // high 8|1: (prev / ptr_size + 1) * ptr_size
+    Mapping { id: 70002, offset: 0x30020 }, // high 8|2: (prev / ptr_size + u8) * ptr_size
+];
+
+/// Returns the delta-encoded body that unpacks into [`EXPECTED`].
+pub fn body() -> Vec<u8> {
+    const fn type_byte(low: u8, high: u8) -> u8 {
+        (high << 4) | low
+    }
+
+    let mut body = vec![];
+
+    body.push(type_byte(0, 0));
+    body.extend_from_slice(&100_u64.to_le_bytes());
+    body.extend_from_slice(&0x1000_u64.to_le_bytes());
+
+    body.push(type_byte(1, 1));
+
+    body.push(type_byte(2, 2));
+    body.push(5);
+    body.push(0x10);
+
+    body.push(type_byte(3, 3));
+    body.push(1);
+    body.push(1);
+
+    body.push(type_byte(4, 4));
+    body.extend_from_slice(&0x100_u16.to_le_bytes());
+    body.extend_from_slice(&0x100_u16.to_le_bytes());
+
+    body.push(type_byte(5, 5));
+    body.extend_from_slice(&0x10_u16.to_le_bytes());
+    body.extend_from_slice(&0x10_u16.to_le_bytes());
+
+    body.push(type_byte(6, 6));
+    body.extend_from_slice(&500_u16.to_le_bytes());
+    body.extend_from_slice(&0x2000_u16.to_le_bytes());
+
+    body.push(type_byte(7, 7));
+    body.extend_from_slice(&70000_u32.to_le_bytes());
+    body.extend_from_slice(&0x30000_u32.to_le_bytes());
+
+    body.push(type_byte(1, 8 | 1));
+
+    body.push(type_byte(1, 8 | 2));
+    body.push(3);
+
+    body
+}
+
+/// Returns the header bytes of the address library.
+pub fn header(format: u32, version: &Version, address_count: u32) -> Vec<u8> {
+    const NAME: &[u8] = b"SkyrimSE.exe";
+
+    let mut header = vec![];
+    header.extend_from_slice(&format.to_le_bytes());
+    for i in 0..4 {
+        header.extend_from_slice(&u32::from(version[i]).to_le_bytes());
+    }
+    header.extend_from_slice(&(NAME.len() as u32).to_le_bytes());
+    header.extend_from_slice(NAME);
+    header.extend_from_slice(&PTR_SIZE.to_le_bytes());
+    header.extend_from_slice(&address_count.to_le_bytes());
+    header
+}
+
+/// Returns a complete address library binary.
+pub fn address_library(format: u32, version: &Version) -> Vec<u8> {
+    let mut bin = header(format, version, EXPECTED.len() as u32);
+    bin.extend(body());
+    bin
+}
+
+/// Address library file in the temporary directory, removed on drop(even if the test panics).
+pub struct TempAddressLibrary {
+    path: PathBuf,
+    version: Version,
+}
+
+impl TempAddressLibrary {
+    /// Writes [`address_library`] of `version` in format 1(SE).
+    ///
+    /// Use a version that never exists to avoid sharing memory with the real address library.
+    pub fn new(version: Version) -> Self {
+        let contents = address_library(1, &version);
+        Self::with_contents(version, "bin", &contents)
+    }
+
+    /// Writes `contents` as `commonlibsse_ng-version-{version}.{extension}`.
+    pub fn with_contents(version: Version, extension: &str, contents: &[u8]) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("commonlibsse_ng-version-{version}.{extension}"));
+        std::fs::write(&path, contents).unwrap();
+        Self { path, version }
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+
+    /// Loads the file as the SE address library of its version.
+    pub fn load(&self) -> Result<IdDatabase, DataBaseError> {
+        IdDatabase::load(self.path(), self.version.clone(), Runtime::Se)
+    }
+}
+
+impl Drop for TempAddressLibrary {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
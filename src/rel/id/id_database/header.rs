@@ -8,6 +8,7 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 //! AddressLibrary header parser
 
+use crate::rel::id::id_database::io_error::clone_io_error;
 use crate::rel::version::Version;
 
 /// AddressLibrary header information
@@ -16,6 +17,9 @@ pub struct Header {
     /// The version information of the address library.
     pub version: Version,
 
+    /// The module name embedded in the address library. (e.g. `"SkyrimSE.exe"`)
+    name: String,
+
     /// The size of pointers in the address library, typically 8 bytes for 64-bit systems.
     pointer_size: u32,
 
@@ -26,14 +30,14 @@ pub struct Header {
 impl Header {
     /// Parses a `Header` from a reader.
     ///
-    /// Reads the format version, the address library version, name length, pointer size, and address count.
+    /// Reads the format version, the address library version, module name, pointer size, and address count.
     ///
     /// # Errors
     ///
     /// Returns a `HeaderError` if any step in the reading process fails, such as:
     /// - Reading format version
     /// - Unsupported address format
-    /// - Reading version, name length, pointer size, or address count
+    /// - Reading version, module name, pointer size, or address count
     pub fn from_reader<R>(reader: &mut R, expected_fmt_ver: u8) -> Result<Self, HeaderError>
     where
         R: std::io::Read + std::io::Seek,
@@ -66,16 +70,30 @@ impl Header {
 
         // Read name length: next 4bytes(20..=23 bytes nth)
         // This value is usually `0x0c` -> 12bytes.
-        {
+        // Then read the module name(e.g. `SkyrimSE.exe`) of that length.
+        let name = {
             let mut name_len = [0_u8; 4];
             reader
                 .read_exact(&mut name_len)
                 .context(ReadNameLengthSnafu)?;
-            let name_len = i32::from_le_bytes(name_len) as i64;
-            reader
-                .seek(std::io::SeekFrom::Current(name_len))
-                .context(SeekAfterNameLengthSnafu)?;
-        }
+            let name_len = i32::from_le_bytes(name_len);
+
+            // Check it before allocating, as with the address count.
+            let remaining_bytes = remaining_len(reader).context(ReadRemainingSizeSnafu)?;
+            let name_len = match u64::try_from(name_len) {
+                Ok(len) if len <= remaining_bytes => len as usize,
+                _ => {
+                    return Err(HeaderError::ImplausibleNameLength {
+                        name_len,
+                        remaining_bytes,
+                    })
+                }
+            };
+
+            let mut name = vec![0_u8; name_len];
+            reader.read_exact(&mut name).context(ReadNameSnafu)?;
+            String::from_utf8_lossy(&name).into_owned()
+        };
 
         // Read pointer size: next 4bytes(usually 0x24..=0x27 bytes nth)
         // This value is almost always 8(bytes) -> 64bit
@@ -96,13 +114,33 @@ impl Header {
             u32::from_le_bytes(address_count)
         };
 
+        // Each entry is encoded in at least 1 byte(type byte), so the count cannot exceed the remaining bytes.
+        // Check it here to avoid allocating a huge shared memory by a corrupt header.
+        {
+            let remaining_bytes = remaining_len(reader).context(ReadRemainingSizeSnafu)?;
+            if u64::from(address_count) > remaining_bytes {
+                return Err(HeaderError::ImplausibleAddressCount {
+                    address_count,
+                    remaining_bytes,
+                });
+            }
+        }
+
         Ok(Self {
             version,
+            name,
             address_count,
             pointer_size,
         })
     }
 
+    /// Returns the module name embedded in the address library. (e.g. `"SkyrimSE.exe"`)
+    ///
+    /// Useful to validate that the library is made for the intended executable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Returns the number of addresses in the address library.
     pub const fn address_count(&self) -> usize {
         self.address_count as usize
@@ -131,9 +169,12 @@ pub enum HeaderError {
     #[snafu(display("Failed to read name length: {}", source))]
     ReadNameLength { source: std::io::Error },
 
-    /// Failed to seek after name length
-    #[snafu(display("Failed to seek after name length: {}", source))]
-    SeekAfterNameLength { source: std::io::Error },
+    /// Failed to read the module name
+    #[snafu(display("Failed to read the module name: {}", source))]
+    ReadName { source: std::io::Error },
+
+    /// The header claims the module name of {name_len} bytes, but only {remaining_bytes} bytes remain. The address library is probably corrupted.
+    ImplausibleNameLength { name_len: i32, remaining_bytes: u64 },
 
     /// Failed to read pointer size
     #[snafu(display("Failed to read pointer size: {}", source))]
@@ -142,6 +183,16 @@ pub enum HeaderError {
     /// Failed to read address count
     #[snafu(display("Failed to read address count: {}", source))]
     ReadAddressCount { source: std::io::Error },
+
+    /// Failed to get the remaining size of the address library
+    #[snafu(display("Failed to get the remaining size of the address library: {}", source))]
+    ReadRemainingSize { source: std::io::Error },
+
+    /// The header claims {address_count} addresses, but only {remaining_bytes} bytes remain. The address library is probably corrupted.
+    ImplausibleAddressCount {
+        address_count: u32,
+        remaining_bytes: u64,
+    },
 }
 
 // io::Error doesn't have `Clone`. Therefore, implement manually.
@@ -149,7 +200,7 @@ impl Clone for HeaderError {
     fn clone(&self) -> Self {
         match self {
             Self::ReadFormatVersion { source } => Self::ReadFormatVersion {
-                source: std::io::Error::new(source.kind(), source.to_string()),
+                source: clone_io_error(source),
             },
             Self::UnexpectedFormat {
                 expected,
@@ -159,24 +210,56 @@ impl Clone for HeaderError {
                 actual_format: *actual_format,
             },
             Self::ReadVersion { source } => Self::ReadVersion {
-                source: std::io::Error::new(source.kind(), source.to_string()),
+                source: clone_io_error(source),
             },
             Self::ReadNameLength { source } => Self::ReadNameLength {
-                source: std::io::Error::new(source.kind(), source.to_string()),
+                source: clone_io_error(source),
             },
-            Self::SeekAfterNameLength { source } => Self::SeekAfterNameLength {
-                source: std::io::Error::new(source.kind(), source.to_string()),
+            Self::ReadName { source } => Self::ReadName {
+                source: clone_io_error(source),
+            },
+            Self::ImplausibleNameLength {
+                name_len,
+                remaining_bytes,
+            } => Self::ImplausibleNameLength {
+                name_len: *name_len,
+                remaining_bytes: *remaining_bytes,
             },
             Self::ReadPointerSize { source } => Self::ReadPointerSize {
-                source: std::io::Error::new(source.kind(), source.to_string()),
+                source: clone_io_error(source),
             },
             Self::ReadAddressCount { source } => Self::ReadAddressCount {
-                source: std::io::Error::new(source.kind(), source.to_string()),
+                source: clone_io_error(source),
+            },
+            Self::ReadRemainingSize { source } => Self::ReadRemainingSize {
+                source: clone_io_error(source),
+            },
+            Self::ImplausibleAddressCount {
+                address_count,
+                remaining_bytes,
+            } => Self::ImplausibleAddressCount {
+                address_count: *address_count,
+                remaining_bytes: *remaining_bytes,
             },
         }
     }
 }
 
+/// Returns the number of bytes from the current position to the end of the stream.
+///
+/// The stream position is restored after the calculation.
+fn remaining_len<R>(reader: &mut R) -> std::io::Result<u64>
+where
+    R: std::io::Seek,
+{
+    use std::io::SeekFrom;
+
+    let current = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(current))?;
+    Ok(end.saturating_sub(current))
+}
+
 // Helper functions for version parsing
 const fn u8_to_le_u32_array(input: [u8; 16]) -> [u32; 4] {
     [
@@ -203,34 +286,75 @@ mod tests {
     /// use commonlibsse_ng::rel::id::Header;
     /// use commonlibsse_ng::rel::version::Version;
 
-    #[test]
-    fn test_parse_header() {
-        #[rustfmt::skip]
-        let binary_data: &[u8] = &[
-            0x01, 0x00, 0x00, 0x00, // 00000000:  u32_le: Format version => 0x00000001
+    #[rustfmt::skip]
+    const HEADER_1_5_97: &[u8] = &[
+        0x01, 0x00, 0x00, 0x00, // 00000000:  u32_le: Format version => 0x00000001
 
-            // Skyrim version(1.5.97.0)
-            0x01, 0x00, 0x00, 0x00, // 00000004:  u32_le: Major -> 1
-            0x05, 0x00, 0x00, 0x00, // 00000008:  u32_le: Minor -> 5
-            0x61, 0x00, 0x00, 0x00, // 0000000C:  u32_le: Patch -> 97
-            0x00, 0x00, 0x00, 0x00, // 00000010:  u32_le: build -> 0
+        // Skyrim version(1.5.97.0)
+        0x01, 0x00, 0x00, 0x00, // 00000004:  u32_le: Major -> 1
+        0x05, 0x00, 0x00, 0x00, // 00000008:  u32_le: Minor -> 5
+        0x61, 0x00, 0x00, 0x00, // 0000000C:  u32_le: Patch -> 97
+        0x00, 0x00, 0x00, 0x00, // 00000010:  u32_le: build -> 0
 
-            0x0C, 0x00, 0x00, 0x00, // 00000014:  u32_le: name length -> 0xc -> 12bytes
+        0x0C, 0x00, 0x00, 0x00, // 00000014:  u32_le: name length -> 0xc -> 12bytes
 
-            // The string "SkyrimSE.exe"(12bytes len) is being read here in ASCII
-            0x53, 0x6B, 0x79, 0x72, // 00000018:  The string (0x53 = 'S', 0x6B = 'k', 0x79 = 'y', 0x72 = 'r')
-            0x69, 0x6D, 0x53, 0x45, // 0000001C:  The string  (0x69 = 'i', 0x6D = 'm', 0x53 = 'S', 0x45 = 'E')
-            0x2E, 0x65, 0x78, 0x65, // 00000020:  The string ".exe" (0x2E = '.', 0x65 = 'e', etc.)
+        // The string "SkyrimSE.exe"(12bytes len) is being read here in ASCII
+        0x53, 0x6B, 0x79, 0x72, // 00000018:  The string (0x53 = 'S', 0x6B = 'k', 0x79 = 'y', 0x72 = 'r')
+        0x69, 0x6D, 0x53, 0x45, // 0000001C:  The string  (0x69 = 'i', 0x6D = 'm', 0x53 = 'S', 0x45 = 'E')
+        0x2E, 0x65, 0x78, 0x65, // 00000020:  The string ".exe" (0x2E = '.', 0x65 = 'e', etc.)
 
-            0x08, 0x00, 0x00, 0x00, // 00000024:  u32_le: The pointer size (this should be the pointer size, which is 8 bytes in this case)
+        0x08, 0x00, 0x00, 0x00, // 00000024:  u32_le: The pointer size (this should be the pointer size, which is 8 bytes in this case)
 
-            0xB2, 0xE1, 0x0B, 0x00, // 00000028:  u32_le: Address count (0xbe1b2 -> 778_674)
-        ];
+        0xB2, 0xE1, 0x0B, 0x00, // 00000028:  u32_le: Address count (0xbe1b2 -> 778_674)
+    ];
+
+    #[test]
+    fn test_parse_header() {
+        // Each entry is at least 1 byte, so pad the body for the address count check.
+        let mut binary_data = HEADER_1_5_97.to_vec();
+        binary_data.resize(binary_data.len() + 778_674, 0);
 
         let mut cursor = Cursor::new(binary_data);
         let header = Header::from_reader(&mut cursor, 1).expect("Failed to read header");
         assert_eq!(header.version, Version::new(1, 5, 97, 0));
+        assert_eq!(header.name(), "SkyrimSE.exe");
         assert_eq!(header.pointer_size(), 8);
         assert_eq!(header.address_count(), 778674);
     }
+
+    #[test]
+    fn test_parse_header_implausible_address_count() {
+        let mut binary_data = HEADER_1_5_97.to_vec();
+        let count_pos = binary_data.len() - 4;
+        binary_data[count_pos..].copy_from_slice(&u32::MAX.to_le_bytes());
+        binary_data.extend_from_slice(&[0; 16]); // Only a small body remains.
+
+        let mut cursor = Cursor::new(binary_data);
+        let err = Header::from_reader(&mut cursor, 1).unwrap_err();
+        assert!(matches!(
+            err,
+            HeaderError::ImplausibleAddressCount {
+                address_count: u32::MAX,
+                remaining_bytes: 16,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_header_implausible_name_length() {
+        for name_len in [-1_i32, i32::MAX] {
+            let mut binary_data = HEADER_1_5_97.to_vec();
+            binary_data[0x14..0x18].copy_from_slice(&name_len.to_le_bytes());
+
+            let mut cursor = Cursor::new(binary_data);
+            let err = Header::from_reader(&mut cursor, 1).unwrap_err();
+            assert!(matches!(
+                err,
+                HeaderError::ImplausibleNameLength {
+                    name_len: actual,
+                    remaining_bytes: 20,
+                } if actual == name_len
+            ));
+        }
+    }
 }
@@ -0,0 +1,34 @@
+//! Cloneable representation of [`std::io::Error`].
+//!
+//! `io::Error` doesn't have `Clone`, but the errors of this crate are cached in globals(e.g. `ModuleState`)
+//! and have to be cloned. So the errors holding it implement `Clone` manually with this.
+
+use std::io;
+
+/// Clones an [`io::Error`] keeping the OS error code if any, otherwise the kind and the message.
+///
+/// The inner custom error(if any) is flattened into its message.
+pub(crate) fn clone_io_error(err: &io::Error) -> io::Error {
+    err.raw_os_error().map_or_else(
+        || io::Error::new(err.kind(), err.to_string()),
+        io::Error::from_raw_os_error,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_io_error() {
+        let os_err = io::Error::from_raw_os_error(2);
+        let cloned = clone_io_error(&os_err);
+        assert_eq!(cloned.raw_os_error(), Some(2));
+        assert_eq!(cloned.kind(), os_err.kind());
+
+        let custom = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated");
+        let cloned = clone_io_error(&custom);
+        assert_eq!(cloned.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(cloned.to_string(), "truncated");
+    }
+}
@@ -14,22 +14,143 @@
 
 mod bin_loader;
 mod byte_reader;
+mod decompress;
+mod fallback;
 mod header;
+mod io_error;
+#[cfg(not(feature = "single_process"))]
+mod stale;
 mod unpack;
 
-use super::{shared_rwlock::SharedRwLock, Mapping};
+#[cfg(test)]
+mod fixtures;
+
+#[cfg(not(feature = "single_process"))]
+pub use self::bin_loader::{
+    set_shared_mapping_prefix, shared_mapping_prefix, DEFAULT_MAPPING_PREFIX,
+};
+#[cfg(not(feature = "single_process"))]
+pub use self::stale::{find_stale_mappings, prune_stale_mappings};
+
+pub(super) use self::bin_loader::read_mappings;
+
+use super::Mapping;
+use crate::rel::module::Runtime;
 use crate::rel::version::Version;
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::OnceLock;
 
-/// Global static instance of `IdDatabase` initialized lazily.
+/// Global static instance of `IdDatabase` initialized lazily, or the error of its load.
 /// This ensures the database is only loaded when needed.
-pub(crate) static ID_DATABASE: LazyLock<IdDatabase> =
-    LazyLock::new(|| IdDatabase::from_bin().unwrap()); // TODO: remove unwrap
+///
+/// A failed load is not retried, so that every resolution does not try to read the missing file again.
+/// (Use [`IdDatabase::reload`] to retry.)
+static ID_DATABASE: OnceLock<Result<&'static IdDatabase, DataBaseError>> = OnceLock::new();
+
+/// Database loaded by [`IdDatabase::reload`], which takes precedence over [`ID_DATABASE`] if not null.
+static RELOADED_ID_DATABASE: AtomicPtr<IdDatabase> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Returns the global database used for address resolution, loading it on first use.
+///
+/// # Errors
+/// Returns the error of the first load if the address library could not be loaded. (e.g. the file is missing)
+pub(crate) fn global() -> Result<&'static IdDatabase, DataBaseError> {
+    let reloaded = RELOADED_ID_DATABASE.load(Ordering::Acquire);
+    // SAFETY: Only leaked boxes are stored, which are never freed.
+    if let Some(db) = unsafe { reloaded.as_ref() } {
+        return Ok(db);
+    }
+
+    ID_DATABASE
+        .get_or_init(|| {
+            let db: &'static IdDatabase = Box::leak(Box::new(IdDatabase::from_bin()?));
+            IS_READY.store(true, Ordering::Release);
+            Ok(db)
+        })
+        .clone()
+}
+
+/// Returns the global database if it has already been loaded, without loading it.
+fn loaded_global() -> Option<&'static IdDatabase> {
+    let reloaded = RELOADED_ID_DATABASE.load(Ordering::Acquire);
+    // SAFETY: Same as `global`.
+    unsafe { reloaded.as_ref() }.or_else(|| ID_DATABASE.get()?.as_ref().ok().copied())
+}
+
+/// Resolves `id` by the global database, falling back to its registered signature if the ID is missing.
+/// (See [`IdDatabase::register_fallback_signature`])
+///
+/// # Errors
+/// - If the global database could not be loaded.
+/// - The error of [`IdDatabase::id_to_offset`] if no signature is registered or found.
+pub(crate) fn resolve_offset(id: u64) -> Result<usize, DataBaseError> {
+    global()?.id_to_offset(id).or_else(|err| {
+        if matches!(err, DataBaseError::NotFoundId { .. }) {
+            fallback::resolve(id).ok_or(err)
+        } else {
+            Err(err)
+        }
+    })
+}
+
+/// Set after the global database has been loaded successfully.
+static IS_READY: AtomicBool = AtomicBool::new(false);
+
+/// Spawns a thread that loads the address library, so that the first ID resolution does not stall the caller.
+///
+/// Loading(and unpacking) the address library can take seconds. Calling this during a non-critical phase
+/// (e.g. `kPostLoad`) moves that cost off the game's main thread.
+/// Resolution before the load finishes still blocks until it is done.
+///
+/// The returned handle yields the error of the load. (e.g. the address library is missing)
+/// The same error is returned by later resolutions, so it can also be ignored here.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::{init_in_background, is_ready};
+///
+/// let handle = init_in_background();
+/// // ...later, on the main thread
+/// if is_ready() {
+///     // ID resolution no longer blocks.
+/// }
+/// if let Err(err) = handle.join().unwrap() {
+///     eprintln!("Failed to load the address library: {err}");
+/// }
+/// ```
+pub fn init_in_background() -> std::thread::JoinHandle<Result<(), DataBaseError>> {
+    std::thread::spawn(|| global().map(|_| ()))
+}
+
+/// Returns `true` if the address library has been loaded successfully, without blocking.
+#[inline]
+pub fn is_ready() -> bool {
+    IS_READY.load(Ordering::Acquire)
+}
+
+/// Storage of the ID-to-offset mappings.
+///
+/// By default, the table is placed in named shared memory so that all plugins loading the same
+/// address library share a single copy.
+/// With the `single_process` feature, it is a plain heap allocation owned by this plugin instead.
+/// (No file mapping, so no name collisions and a faster lock, at the cost of memory deduplication.)
+#[cfg(not(feature = "single_process"))]
+pub(crate) type MappingTable = super::shared_rwlock::SharedRwLock<Mapping>;
+
+/// Storage of the ID-to-offset mappings. (`single_process`: heap allocated, not shared)
+#[cfg(feature = "single_process")]
+pub(crate) type MappingTable = parking_lot::RwLock<Box<[Mapping]>>;
 
 /// Represents a database of ID-to-offset mappings loaded from an address library binary file.
 pub struct IdDatabase {
-    /// Memory-mapped storage of the ID database.
-    pub(super) mem_map: SharedRwLock<Mapping>,
+    /// Storage of the ID database.
+    pub(super) mem_map: MappingTable,
+    /// Version of the loaded address library.
+    version: Version,
+    /// Runtime for which the address library was loaded.
+    runtime: Runtime,
+    /// Path of the loaded address library.
+    path: String,
 }
 
 impl IdDatabase {
@@ -39,49 +160,405 @@ impl IdDatabase {
     /// Returns an error if the module state is invalid, the file cannot be read,
     /// or if the data is not properly formatted.
     fn from_bin() -> Result<Self, DataBaseError> {
-        use self::bin_loader::load_bin_file;
+        let (path, version, runtime, filename) = Self::bin_location()?;
+        Self::load_inner(path, version, runtime, Some(&filename), &mut |_, _| {})
+    }
+
+    /// Returns `(path, version, runtime, module filename)` of the address library for the current module state.
+    fn bin_location() -> Result<(String, Version, Runtime, String), DataBaseError> {
         use crate::rel::module::ModuleState;
 
-        let (version, runtime) = ModuleState::map_or_init(|module| {
+        let (version, runtime, filename) = ModuleState::map_or_init(|module| {
             let version = module.version.clone();
-            (version, module.runtime)
+            (version, module.runtime, module.filename.to_string())
         })?;
 
-        let is_ae = runtime.is_ae();
         let path = {
-            let ver_suffix = if is_ae { "lib" } else { "" };
-            format!("Data/SKSE/Plugins/version{ver_suffix}-{version}.bin")
+            let ver_suffix = if runtime.is_ae() { "lib" } else { "" };
+            bin_loader::find_library_file(format!(
+                "Data/SKSE/Plugins/version{ver_suffix}-{version}.bin"
+            ))
         };
-        let expected_fmt_ver = if is_ae { 2 } else { 1 }; // Expected AddressLibrary format version. SE/VR: 1, AE: 2
+        Ok((path, version, runtime, filename))
+    }
+
+    /// Returns `true` if this database was loaded from `path` for `version` and `runtime`.
+    fn is_loaded_from(&self, path: &str, version: &Version, runtime: Runtime) -> bool {
+        self.path == path && self.version == *version && self.runtime == runtime
+    }
+
+    /// Reloads the global database used for address resolution for the current module state.
+    ///
+    /// The database is loaded once and is not reloaded by [`ModuleState::reset`](crate::rel::module::ModuleState::reset).
+    /// If the module may have changed(e.g. another runtime was detected after the reset), call this after the reset
+    /// so that IDs are not resolved with the address library of the old module.
+    ///
+    /// Addresses memoized by [`Cached`](crate::rel::Cached) are resolved again with the new database.
+    ///
+    /// If the module still needs the same address library(e.g. only its base moved), the current database is kept
+    /// and only the memoized addresses are invalidated.
+    ///
+    /// # Leak
+    /// When another address library is loaded, the previous database is intentionally kept instead of dropped,
+    /// since references to it may still be in use.
+    /// This includes its mapping: its shared memory handle stays open until [`prune_stale_mappings`] releases it.
+    /// (With `single_process`, its heap table is leaked until the process exits. Reloading is expected to be rare.)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::ModuleState;
+    ///
+    /// ModuleState::reset().unwrap();
+    /// IdDatabase::reload().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the module state is invalid, or the address library cannot be loaded.
+    /// In that case, the current database is kept.
+    pub fn reload() -> Result<(), DataBaseError> {
+        let (path, version, runtime, filename) = Self::bin_location()?;
+        if loaded_global().is_some_and(|db| db.is_loaded_from(&path, &version, runtime)) {
+            crate::rel::module::ModuleState::bump_generation();
+            return Ok(());
+        }
+
+        let db = Self::load_inner(path, version, runtime, Some(&filename), &mut |_, _| {})?;
+        Self::replace_global(db);
+        Ok(())
+    }
+
+    /// Makes `db` the global database, keeping the previous one alive. (See [`Self::reload`])
+    fn replace_global(db: Self) {
+        let db = Box::leak(Box::new(db));
+        let _previous = RELOADED_ID_DATABASE.swap(db, Ordering::AcqRel);
+        #[cfg(not(feature = "single_process"))]
+        {
+            // SAFETY: Same as `global`.
+            // Once reloaded, `ID_DATABASE` is never returned as the global database again.
+            let previous =
+                unsafe { _previous.as_ref() }.or_else(|| ID_DATABASE.get()?.as_ref().ok().copied());
+            if let Some(previous) = previous {
+                stale::track_replaced(previous);
+            }
+        }
+        IS_READY.store(true, Ordering::Release);
+        crate::rel::module::ModuleState::bump_generation();
+    }
+
+    /// Loads the ID database of an arbitrary address library file.
+    ///
+    /// Unlike the global database used for address resolution, this does not depend on the running module,
+    /// so it can be used by tools, e.g. to compare two versions with [`diff`](crate::rel::id::diff).
+    /// Any number of databases(of different versions) can be held at once, and each is queried independently
+    /// by [`Self::id_to_offset`] or [`OffsetToID::from_database`](crate::rel::id::OffsetToID::from_database).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let version = Version::new(1, 6, 1170, 0);
+    /// let db = IdDatabase::load("versionlib-1.6.1170.0.bin", version, Runtime::Ae).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, the version in the file mismatches `version`,
+    /// or if the data is not properly formatted.
+    pub fn load(
+        path: impl Into<String>,
+        version: Version,
+        runtime: Runtime,
+    ) -> Result<Self, DataBaseError> {
+        Self::load_with_progress(path, version, runtime, |_, _| {})
+    }
+
+    /// [`Self::load`] that reports the unpacking progress, e.g. to update a loading screen.
+    ///
+    /// `progress(done, total)` is called periodically while unpacking and once on completion, where `total` is the number of addresses in the file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let version = Version::new(1, 6, 1170, 0);
+    /// let db = IdDatabase::load_with_progress("versionlib-1.6.1170.0.bin", version, Runtime::Ae, |done, total| {
+    ///     println!("{done}/{total}");
+    /// })
+    /// .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Self::load`].
+    pub fn load_with_progress<F>(
+        path: impl Into<String>,
+        version: Version,
+        runtime: Runtime,
+        mut progress: F,
+    ) -> Result<Self, DataBaseError>
+    where
+        F: FnMut(usize, usize),
+    {
+        Self::load_inner(path.into(), version, runtime, None, &mut progress)
+    }
+
+    /// Loads the address library at `path`.
+    ///
+    /// `module_name` is the running executable to check the library against, only when loading for address resolution.
+    fn load_inner(
+        path: String,
+        version: Version,
+        runtime: Runtime,
+        module_name: Option<&str>,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<Self, DataBaseError> {
+        use self::bin_loader::load_bin_file;
+
+        let expected_fmt_ver = if runtime.is_ae() { 2 } else { 1 }; // Expected AddressLibrary format version. SE/VR: 1, AE: 2
 
         Ok(Self {
-            mem_map: load_bin_file(&path, version, expected_fmt_ver)?,
+            mem_map: load_bin_file(
+                &path,
+                version.clone(),
+                expected_fmt_ver,
+                module_name,
+                progress,
+            )?,
+            version,
+            runtime,
+            path,
         })
     }
 
+    /// Returns the version of the loaded address library.
+    #[inline]
+    pub const fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Returns the runtime for which the address library was loaded.
+    #[inline]
+    pub const fn runtime(&self) -> Runtime {
+        self.runtime
+    }
+
+    /// Returns the path of the loaded address library.
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
     /// Retrieves the offset corresponding to the given ID.
     ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let se = IdDatabase::load("version-1-5-97-0.bin", Version::new(1, 5, 97, 0), Runtime::Se).unwrap();
+    /// let ae = IdDatabase::load("versionlib-1.6.1170.0.bin", Version::new(1, 6, 1170, 0), Runtime::Ae).unwrap();
+    /// println!("{:#x} -> {:#x}", se.id_to_offset(11045).unwrap(), ae.id_to_offset(11183).unwrap());
+    /// ```
+    ///
     /// # Errors
     /// Returns an error if the ID is not found in the database.
-    pub(crate) fn id_to_offset(&self, id: u64) -> Result<usize, DataBaseError> {
-        let slice = self
-            .mem_map
-            .read()
-            .map_err(|_| DataBaseError::MappingCreationFailed)?;
+    /// The error carries the version, runtime and path of the library so that users can tell a mismatched library.
+    pub fn id_to_offset(&self, id: u64) -> Result<usize, DataBaseError> {
+        let slice = self.read_poison_free();
+
+        Mapping::search_by_id(&slice, id)
+            .map(|mapping| mapping.offset as usize)
+            .ok_or_else(|| {
+                let (below, above) = neighbors_by_id(&slice, id);
+                DataBaseError::NotFoundId {
+                    id,
+                    below: below.map(|mapping| mapping.id),
+                    above: above.map(|mapping| mapping.id),
+                    version: self.version.clone(),
+                    runtime: self.runtime,
+                    path: self.path.clone(),
+                }
+            })
+    }
+
+    /// Returns the mappings whose IDs are immediately below and above `id`. (`id` itself is excluded.)
+    ///
+    /// This is useful for debugging a missing ID: a small gap usually means that the ID was renumbered between versions.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let db = IdDatabase::load("versionlib-1.6.1170.0.bin", Version::new(1, 6, 1170, 0), Runtime::Ae).unwrap();
+    /// let (below, above) = db.lookup_context(5000);
+    /// println!("nearest: {below:?}, {above:?}");
+    /// ```
+    pub fn lookup_context(&self, id: u64) -> (Option<Mapping>, Option<Mapping>) {
+        let slice = self.read_poison_free();
+        let (below, above) = neighbors_by_id(&slice, id);
+        (below.cloned(), above.cloned())
+    }
+
+    /// Registers a signature to scan for when `id` is missing in the address library. (e.g. a brand-new game version)
+    ///
+    /// When [`ID`](crate::rel::id::ID)/[`RelocationID`](crate::rel::id::RelocationID) fail to resolve `id`
+    /// with [`DataBaseError::NotFoundId`], `segment` of the running module is scanned for `pattern` and
+    /// the first match is used instead. The scan result is cached, so each segment is scanned at most once per ID.
+    ///
+    /// `id` is the ID of the current runtime. Registering the same ID again replaces the signature.
+    /// This does not load the address library, and only affects the global database used for address resolution.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::{IdDatabase, ID};
+    /// use commonlibsse_ng::rel::module::SegmentName;
+    /// use commonlibsse_ng::rel::ResolvableAddress as _;
+    ///
+    /// let pattern = "48 89 5C 24 ?? 57 48 83 EC 20".parse().unwrap();
+    /// IdDatabase::register_fallback_signature(52050, pattern, SegmentName::Textx);
+    ///
+    /// // Resolved by the scan if the address library lacks the ID.
+    /// let address = ID::new(52050).address();
+    /// ```
+    pub fn register_fallback_signature(
+        id: u64,
+        pattern: crate::rel::pattern::Pattern,
+        segment: crate::rel::module::SegmentName,
+    ) {
+        fallback::register(id, pattern, segment);
+    }
+
+    /// Returns the `index`-th mapping of the table sorted by ID, or `None` if `index` is out of range.
+    ///
+    /// Unlike indexing the table directly, this never panics, which matters in a plugin where unwinding
+    /// across the game's frames aborts the process.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let db = IdDatabase::load("versionlib-1.6.1170.0.bin", Version::new(1, 6, 1170, 0), Runtime::Ae).unwrap();
+    /// assert!(db.get_mapping(0).is_some());
+    /// assert_eq!(db.get_mapping(usize::MAX), None);
+    /// ```
+    pub fn get_mapping(&self, index: usize) -> Option<Mapping> {
+        self.read_poison_free().get(index).cloned()
+    }
+
+    /// Returns the number of mappings in the table.
+    pub fn len(&self) -> usize {
+        self.read_poison_free().len()
+    }
+
+    /// Returns `true` if the table has no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes all mappings in ID order, one `id => offset` per line in hex. (See [`Mapping`]'s `Display`)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::IdDatabase;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let db = IdDatabase::load("versionlib-1.6.1170.0.bin", Version::new(1, 6, 1170, 0), Runtime::Ae).unwrap();
+    /// let mut file = std::io::BufWriter::new(std::fs::File::create("1.6.1170.txt").unwrap());
+    /// db.dump(&mut file).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// If writing to `writer` fails.
+    pub fn dump(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        for mapping in self.read_poison_free().iter() {
+            writeln!(writer, "{mapping}")?;
+        }
+        Ok(())
+    }
+
+    /// Acquires a read lock on the database, recovering from poisoning.
+    ///
+    /// The table is only written once while it is being unpacked, and after that it is read-only.
+    /// Therefore, a poison caused by an unrelated panic does not mean that the table is broken,
+    /// and it should not permanently break all address resolution.
+    #[cfg(not(feature = "single_process"))]
+    pub(super) fn read_poison_free(&self) -> super::shared_rwlock::RwLockReadGuard<'_, Mapping> {
+        self.mem_map.read().unwrap_or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("The address library lock is poisoned. Recovering to continue reading.");
+            err.into_inner()
+        })
+    }
 
-        slice.binary_search_by(|m| m.id.cmp(&id)).map_or_else(
-            |_| Err(DataBaseError::NotFoundId { id }),
-            |index| Ok(slice[index].offset as usize),
-        )
+    /// Acquires a read lock on the database. (`parking_lot` lock never poisons.)
+    #[cfg(feature = "single_process")]
+    pub(super) fn read_poison_free(&self) -> parking_lot::RwLockReadGuard<'_, Box<[Mapping]>> {
+        self.mem_map.read()
+    }
+}
+
+/// Returns the mappings immediately below and above `id` in `mappings` sorted by ID.
+fn neighbors_by_id(mappings: &[Mapping], id: u64) -> (Option<&Mapping>, Option<&Mapping>) {
+    let (below_end, above_start) = match mappings.binary_search_by(|m| m.id.cmp(&id)) {
+        Ok(index) => (index, index + 1),
+        Err(insert_pos) => (insert_pos, insert_pos),
+    };
+    let below = below_end
+        .checked_sub(1)
+        .and_then(|index| mappings.get(index));
+    (below, mappings.get(above_start))
+}
+
+/// Formats the nearest existing IDs of [`DataBaseError::NotFoundId`].
+fn nearest_ids(below: &Option<u64>, above: &Option<u64>) -> String {
+    match (below, above) {
+        (Some(below), Some(above)) => format!("; nearest are {below} and {above}"),
+        (Some(near), None) | (None, Some(near)) => format!("; nearest is {near}"),
+        (None, None) => String::new(),
     }
 }
 
 /// Errors that can occur during the file loading process.
 #[derive(Debug, Clone, snafu::Snafu)]
 pub enum DataBaseError {
-    /// Failed to find the id within the address library: {id}. This means this script extender plugin is incompatible.,
-    #[snafu(display("Failed to find the id within the address library: {id}\nThis means this script extender plugin is incompatible."))]
-    NotFoundId { id: u64 },
+    /// Failed to find the id {id} in {path}. (version: {version}, runtime: {runtime}) This means this script extender plugin is incompatible.
+    #[snafu(display("Failed to find the id {id}{} in {path} (version: {version}, runtime: {runtime})\nThis means this script extender plugin is incompatible.", nearest_ids(below, above)))]
+    NotFoundId {
+        id: u64,
+        /// The nearest existing ID below `id`.
+        below: Option<u64>,
+        /// The nearest existing ID above `id`.
+        above: Option<u64>,
+        version: Version,
+        runtime: Runtime,
+        path: String,
+    },
+
+    /// The resolved address is null. (The offset for the current runtime is `0`.)
+    NullAddress,
+
+    /// The computed address {address:#x} is out of the module image. (base: {module_base:#x}, size: {image_size:#x})
+    AddressOutOfModule {
+        address: usize,
+        module_base: usize,
+        image_size: u32,
+    },
+
+    /// The offset {seg_offset:#x} is out of the segment {name:?}. (size: {size:#x})
+    SegmentOffsetOutOfRange {
+        name: crate::rel::module::SegmentName,
+        seg_offset: usize,
+        size: u32,
+    },
 
     /// Version mismatch
     #[snafu(display("Version mismatch: expected {}, got {}", expected, actual))]
@@ -93,6 +570,12 @@ pub enum DataBaseError {
     /// Failed to locate an appropriate address library at: {path}
     AddressLibraryNotFound { path: String },
 
+    /// Failed to decompress the address library at {path}: {source}
+    FailedDecompressFile {
+        path: String,
+        source: self::decompress::DecompressError,
+    },
+
     /// Failed to unpack file at: {source}
     FailedUnpackFile { source: self::unpack::UnpackError },
 
@@ -106,12 +589,289 @@ pub enum DataBaseError {
     #[snafu(transparent)]
     HeaderParseError { source: self::header::HeaderError },
 
-    /// A thread that was taking database locks panicked.
-    Poisoned,
-
     /// Inherited memory mapping error.
     #[snafu(transparent)]
     MemoryMapError {
         source: super::shared_rwlock::MemoryMapError,
     },
 }
+
+#[cfg(all(test, not(feature = "single_process")))]
+mod tests {
+    use super::*;
+    use crate::rel::id::shared_rwlock::SharedRwLock;
+    use windows::core::{h, HSTRING};
+
+    /// Creates a database holding `mappings` in the shared memory named `name`.
+    fn test_database(name: &HSTRING, mappings: &[Mapping]) -> IdDatabase {
+        let mem_map = SharedRwLock::new(name, mappings.len())
+            .unwrap()
+            .into_inner();
+        mem_map.write().unwrap().clone_from_slice(mappings);
+        IdDatabase {
+            mem_map,
+            version: Version::new(1, 2, 3, 4),
+            runtime: Runtime::Se,
+            path: "version-1-2-3-4.bin".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_id_to_offset_after_poison() {
+        let db = test_database(
+            h!("IdDatabasePoisonTest"),
+            &[
+                Mapping {
+                    id: 1,
+                    offset: 0x10,
+                },
+                Mapping {
+                    id: 2,
+                    offset: 0x20,
+                },
+            ],
+        );
+
+        // Poison the lock by panicking while holding the write guard.
+        std::thread::scope(|s| {
+            let _ = s
+                .spawn(|| {
+                    let _guard = db.mem_map.write().unwrap();
+                    panic!("poison the lock");
+                })
+                .join();
+        });
+        assert!(db.mem_map.is_poisoned());
+
+        assert_eq!(db.id_to_offset(1).unwrap(), 0x10);
+        assert_eq!(db.id_to_offset(2).unwrap(), 0x20);
+    }
+
+    #[test]
+    fn test_not_found_id_context() {
+        let db = test_database(
+            h!("IdDatabaseNotFoundIdTest"),
+            &[
+                Mapping {
+                    id: 10,
+                    offset: 0x10,
+                },
+                Mapping {
+                    id: 20,
+                    offset: 0x20,
+                },
+            ],
+        );
+
+        let err = db.id_to_offset(15).unwrap_err();
+        assert!(matches!(
+            err,
+            DataBaseError::NotFoundId {
+                id: 15,
+                below: Some(10),
+                above: Some(20),
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Failed to find the id 15; nearest are 10 and 20 in version-1-2-3-4.bin (version: 1.2.3.4, runtime: SE)\nThis means this script extender plugin is incompatible."
+        );
+
+        let err = db.id_to_offset(30).unwrap_err();
+        assert!(matches!(
+            err,
+            DataBaseError::NotFoundId {
+                below: Some(20),
+                above: None,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("id 30; nearest is 20 in"));
+
+        assert_eq!(
+            db.lookup_context(10),
+            (
+                None,
+                Some(Mapping {
+                    id: 20,
+                    offset: 0x20
+                })
+            )
+        );
+        assert_eq!(nearest_ids(&None, &None), "");
+    }
+
+    #[test]
+    fn test_dump() {
+        let db = test_database(
+            h!("IdDatabaseDumpTest"),
+            &[
+                Mapping {
+                    id: 1,
+                    offset: 0x10,
+                },
+                Mapping {
+                    id: 2,
+                    offset: 0x20,
+                },
+            ],
+        );
+
+        let mut dump = Vec::new();
+        db.dump(&mut dump).unwrap();
+        assert_eq!(dump, b"0x1 => 0x10\n0x2 => 0x20\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_mapping_serialize() {
+        let mapping = Mapping {
+            id: 11045,
+            offset: 0x1ede80,
+        };
+        assert_eq!(
+            serde_json::to_string(&mapping).unwrap(),
+            r#"{"id":11045,"offset":2023040}"#
+        );
+    }
+
+    #[test]
+    fn test_get_mapping() {
+        let db = test_database(
+            h!("IdDatabaseGetMappingTest"),
+            &[
+                Mapping {
+                    id: 1,
+                    offset: 0x10,
+                },
+                Mapping {
+                    id: 2,
+                    offset: 0x20,
+                },
+            ],
+        );
+
+        assert_eq!(db.len(), 2);
+        assert!(!db.is_empty());
+        assert_eq!(
+            db.get_mapping(1),
+            Some(Mapping {
+                id: 2,
+                offset: 0x20
+            })
+        );
+        assert_eq!(db.get_mapping(2), None);
+        assert_eq!(db.get_mapping(usize::MAX), None);
+    }
+
+    /// Serializes the tests replacing the global database, and restores the previous one on drop(even on panic).
+    ///
+    /// The other tests resolving through the global database must also hold this, since their results depend on it.
+    struct GlobalDatabaseGuard {
+        _lock: std::sync::MutexGuard<'static, ()>,
+        previous: *mut IdDatabase,
+        was_ready: bool,
+    }
+
+    impl GlobalDatabaseGuard {
+        fn lock() -> Self {
+            static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+            Self {
+                _lock: LOCK.lock().unwrap_or_else(|err| err.into_inner()),
+                previous: RELOADED_ID_DATABASE.load(Ordering::Acquire),
+                was_ready: is_ready(),
+            }
+        }
+    }
+
+    impl Drop for GlobalDatabaseGuard {
+        fn drop(&mut self) {
+            RELOADED_ID_DATABASE.store(self.previous, Ordering::Release);
+            IS_READY.store(self.was_ready, Ordering::Release);
+        }
+    }
+
+    #[test]
+    fn test_cached_after_reload() {
+        use crate::rel::id::ID;
+        use crate::rel::module::ModuleState;
+        use crate::rel::{Cached, ResolvableAddress as _};
+
+        const TEST_ID: u64 = u64::MAX - 2;
+        let address = Cached::new(ID::new(TEST_ID));
+        let base = ModuleState::base_address().unwrap();
+
+        let _guard = GlobalDatabaseGuard::lock();
+        IdDatabase::replace_global(test_database(
+            h!("IdDatabaseReloadTest1"),
+            &[Mapping {
+                id: TEST_ID,
+                offset: 0x10,
+            }],
+        ));
+        assert_eq!(address.address().unwrap(), base + 0x10);
+
+        // Not bound to the offset of the previous database.
+        IdDatabase::replace_global(test_database(
+            h!("IdDatabaseReloadTest2"),
+            &[Mapping {
+                id: TEST_ID,
+                offset: 0x20,
+            }],
+        ));
+        assert_eq!(address.address().unwrap(), base + 0x20);
+    }
+
+    #[test]
+    fn test_prune_replaced_database() {
+        use crate::rel::id::shared_rwlock::shared_mem_exists;
+
+        let _guard = GlobalDatabaseGuard::lock();
+        let mappings = [Mapping {
+            id: 1,
+            offset: 0x10,
+        }];
+        IdDatabase::replace_global(test_database(h!("IdDatabasePruneTest1"), &mappings));
+        IdDatabase::replace_global(test_database(h!("IdDatabasePruneTest2"), &mappings));
+        assert!(shared_mem_exists(h!("IdDatabasePruneTest1")));
+
+        unsafe { prune_stale_mappings(&Version::new(1, 2, 3, 4)) };
+        // The replaced one is released, and the current one is kept.
+        assert!(!shared_mem_exists(h!("IdDatabasePruneTest1")));
+        assert!(shared_mem_exists(h!("IdDatabasePruneTest2")));
+        assert_eq!(global().unwrap().id_to_offset(1).unwrap(), 0x10);
+    }
+
+    #[test]
+    fn test_init_in_background() {
+        let _guard = GlobalDatabaseGuard::lock();
+
+        // The address library of the test module(`msvcrt.dll`) does not exist.
+        let err = init_in_background().join().unwrap().unwrap_err();
+        assert!(!is_ready());
+        // The error is kept and returned by the resolution, instead of panicking.
+        assert_eq!(resolve_offset(1).unwrap_err().to_string(), err.to_string());
+    }
+
+    #[test]
+    fn test_is_loaded_from() {
+        let db = test_database(
+            h!("IdDatabaseLoadedFromTest"),
+            &[Mapping {
+                id: 1,
+                offset: 0x10,
+            }],
+        );
+        let version = Version::new(1, 2, 3, 4);
+
+        assert!(db.is_loaded_from("version-1-2-3-4.bin", &version, Runtime::Se));
+        assert!(!db.is_loaded_from("version-1-2-3-4.bin.gz", &version, Runtime::Se));
+        assert!(!db.is_loaded_from(
+            "version-1-2-3-4.bin",
+            &Version::new(1, 2, 3, 5),
+            Runtime::Se
+        ));
+        assert!(!db.is_loaded_from("version-1-2-3-4.bin", &version, Runtime::Vr));
+    }
+}
@@ -0,0 +1,103 @@
+//! Detection and release of address library shared memory left by other game versions.
+
+use super::bin_loader::mapping_name;
+use super::IdDatabase;
+use crate::rel::id::shared_rwlock::shared_mem_exists;
+use crate::rel::version::Version;
+use crate::skse::version::KNOWN_RUNTIMES;
+use std::sync::Mutex;
+
+/// Databases replaced by [`IdDatabase::reload`], kept alive until [`prune_stale_mappings`] releases them.
+static REPLACED_DATABASES: Mutex<Vec<&'static IdDatabase>> = Mutex::new(Vec::new());
+
+/// Keeps `db` replaced by [`IdDatabase::reload`] so that [`prune_stale_mappings`] can release it later.
+///
+/// `db` must be a leaked box that is no longer returned as the global database.
+pub(super) fn track_replaced(db: &'static IdDatabase) {
+    REPLACED_DATABASES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(db);
+}
+
+/// Releases the address library shared memory that this process holds only for old databases, and
+/// returns the versions other than `current` whose shared memory is still alive. (See [`find_stale_mappings`])
+///
+/// The databases replaced by [`IdDatabase::reload`] are dropped, which closes their mapping handles.
+///
+/// # Best-effort
+/// The kernel releases a named shared memory only when its last handle is closed.
+/// If another process(or a database loaded by [`IdDatabase::load`] and still held) also holds the same mapping,
+/// closing the handles of this process does not release it, and its version is returned.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::{prune_stale_mappings, IdDatabase};
+/// use commonlibsse_ng::rel::module::ModuleState;
+/// use commonlibsse_ng::rel::version::Version;
+///
+/// ModuleState::reset().unwrap();
+/// IdDatabase::reload().unwrap();
+/// // SAFETY: Called on the main thread, where all the ID resolution of this plugin happens.
+/// for stale in unsafe { prune_stale_mappings(&Version::new(1, 6, 1170, 0)) } {
+///     println!("The address library shared memory of {stale} is held by another process.");
+/// }
+/// ```
+///
+/// # Safety
+/// The global database is used by reference during an address resolution(`ID`, `RelocationID`, `VariantID`, etc.),
+/// [`symbolicate`](crate::rel::id::symbolicate) and [`OffsetToID::from_global`](crate::rel::id::OffsetToID::from_global).
+/// None of them that started before the last [`IdDatabase::reload`] may still be running on another thread,
+/// since the database they use is released.
+pub unsafe fn prune_stale_mappings(current: &Version) -> Vec<Version> {
+    let replaced = core::mem::take(
+        &mut *REPLACED_DATABASES
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()),
+    );
+    for db in replaced {
+        // SAFETY: Only leaked boxes that are no longer global are tracked, and the caller guarantees that
+        //         no reference to them is in use.
+        drop(unsafe { Box::from_raw(core::ptr::from_ref(db).cast_mut()) });
+    }
+
+    find_stale_mappings(current)
+}
+
+/// Finds the address library shared memory of known versions other than `current` that is still alive.
+///
+/// This only reports them and releases nothing. (Use [`prune_stale_mappings`] to release the ones of this process.)
+///
+/// A named shared memory is released by the kernel when the last handle to it is closed,
+/// and no process can close the handles of another process.
+///
+/// - The handles of this crate are closed when the owning [`IdDatabase`] is dropped,
+///   so drop any database loaded for an old version(e.g. by [`IdDatabase::load`]) before calling this.
+/// - The databases replaced by [`IdDatabase::reload`] are kept until [`prune_stale_mappings`],
+///   so their versions are reported too if the reload switched versions.
+/// - This function itself only opens each mapping to check its existence and closes it immediately,
+///   so it never keeps a stale mapping alive.
+///
+/// The returned versions are the mappings still held by this process(as above) or by someone else
+/// (e.g. another still-running process). It is useful for logging.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::find_stale_mappings;
+/// use commonlibsse_ng::rel::version::Version;
+///
+/// for stale in find_stale_mappings(&Version::new(1, 6, 1170, 0)) {
+///     println!("The address library shared memory of {stale} is still alive.");
+/// }
+/// ```
+pub fn find_stale_mappings(current: &Version) -> Vec<Version> {
+    KNOWN_RUNTIMES
+        .into_iter()
+        .filter(|version| version != current)
+        .filter(|version| shared_mem_exists(&mapping_name(version)))
+        .inspect(|_version| {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("The address library shared memory of {_version} is still alive.");
+        })
+        .collect()
+}
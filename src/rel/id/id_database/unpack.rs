@@ -1,9 +1,16 @@
 use crate::rel::id::id_database::byte_reader::{read_le_u16, read_le_u32, read_le_u64, read_u8};
+use crate::rel::id::id_database::io_error::clone_io_error;
 use crate::rel::id::Mapping;
 use std::io::Read;
 
+/// Number of entries unpacked between progress reports.
+const PROGRESS_INTERVAL: usize = 0x4000;
+
 /// Unpacks the ID database from the binary file and writes it into the memory map(sorted by ID).
 ///
+/// `progress(done, total)` is called every [`PROGRESS_INTERVAL`] entries and once on completion.
+/// `total` is the length of `mem_map`, i.e. the address count of the header.
+///
 /// # Errors
 /// - If the memory allocated as `MemoryMap` is not consistent as the length of the mapping data array.
 /// - Returns an error if the binary data cannot be properly parsed.
@@ -11,6 +18,7 @@ pub(crate) fn unpack_file<R>(
     mem_map: &mut [Mapping],
     reader: &mut R,
     ptr_size: u64,
+    progress: &mut dyn FnMut(usize, usize),
 ) -> Result<(), UnpackError>
 where
     R: Read,
@@ -21,7 +29,12 @@ where
     let mut prev_offset: u64 = 0;
 
     let mappings = mem_map;
-    for mapping in &mut *mappings {
+    let total = mappings.len();
+    for (index, mapping) in mappings.iter_mut().enumerate() {
+        if index % PROGRESS_INTERVAL == 0 && index != 0 {
+            progress(index, total);
+        }
+
         let type_byte = read_u8(reader)?;
 
         let low = type_byte & 0xF;
@@ -47,6 +60,7 @@ where
     }
 
     mappings.sort_by(|a, b| a.id.cmp(&b.id));
+    progress(total, total);
 
     Ok(())
 }
@@ -111,8 +125,96 @@ impl Clone for UnpackError {
             Self::InvalidId { id } => Self::InvalidId { id: *id },
             Self::InvalidOffset { offset } => Self::InvalidOffset { offset: *offset },
             Self::Io { source: err } => Self::Io {
-                source: std::io::Error::new(err.kind(), err.to_string()),
+                source: clone_io_error(err),
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rel::id::id_database::fixtures;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_unpack_all_encodings() {
+        let mut reader = Cursor::new(fixtures::body());
+        let mut mappings = vec![Mapping { id: 0, offset: 0 }; fixtures::EXPECTED.len()];
+        unpack_file(
+            &mut mappings,
+            &mut reader,
+            fixtures::PTR_SIZE as u64,
+            &mut |_, _| {},
+        )
+        .unwrap();
+
+        let mut expected = fixtures::EXPECTED.to_vec();
+        expected.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(mappings, expected);
+    }
+
+    #[test]
+    fn test_unpack_progress() {
+        let mut reader = Cursor::new(fixtures::body());
+        let mut mappings = vec![Mapping { id: 0, offset: 0 }; fixtures::EXPECTED.len()];
+        let mut reports = vec![];
+        unpack_file(
+            &mut mappings,
+            &mut reader,
+            fixtures::PTR_SIZE as u64,
+            &mut |done, total| reports.push((done, total)),
+        )
+        .unwrap();
+
+        // The fixture is smaller than the interval, so only the completion is reported.
+        let total = fixtures::EXPECTED.len();
+        assert_eq!(reports, [(total, total)]);
+    }
+
+    #[test]
+    fn test_unpack_progress_interval() {
+        // `0x11`: ID + 1 and offset + 1. i.e. 1 byte per entry.
+        let total = PROGRESS_INTERVAL * 2 + 1;
+        let mut reader = Cursor::new(vec![0x11; total]);
+        let mut mappings = vec![Mapping { id: 0, offset: 0 }; total];
+        let mut reports = vec![];
+        unpack_file(&mut mappings, &mut reader, 8, &mut |done, total| {
+            reports.push((done, total));
+        })
+        .unwrap();
+
+        assert_eq!(
+            reports,
+            [
+                (PROGRESS_INTERVAL, total),
+                (PROGRESS_INTERVAL * 2, total),
+                (total, total)
+            ]
+        );
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(
+            mappings[total - 1],
+            Mapping {
+                id: total as u64,
+                offset: total as u64
+            }
+        );
+    }
+
+    #[test]
+    fn test_unpack_eof() {
+        let mut body = fixtures::body();
+        body.pop(); // Cut the last u8 delta.
+
+        let mut reader = Cursor::new(body);
+        let mut mappings = vec![Mapping { id: 0, offset: 0 }; fixtures::EXPECTED.len()];
+        let result = unpack_file(
+            &mut mappings,
+            &mut reader,
+            fixtures::PTR_SIZE as u64,
+            &mut |_, _| {},
+        );
+        assert!(matches!(result, Err(UnpackError::Io { .. })));
+    }
+}
@@ -1,15 +1,23 @@
+mod diff;
 mod id_database;
 mod offset_to_id;
 mod relocation_id;
 pub mod shared_rwlock;
+mod symbolicate;
 mod variant_id;
 
-pub use self::id_database::DataBaseError;
+pub use self::diff::{diff, AddressLibraryDiff, ChangedOffset};
+#[cfg(not(feature = "single_process"))]
+pub use self::id_database::{
+    find_stale_mappings, prune_stale_mappings, set_shared_mapping_prefix, shared_mapping_prefix,
+    DEFAULT_MAPPING_PREFIX,
+};
+pub use self::id_database::{init_in_background, is_ready, DataBaseError, IdDatabase};
 pub use self::offset_to_id::OffsetToID;
 pub use self::relocation_id::RelocationID;
+pub use self::symbolicate::{symbolicate, Symbolicated};
 pub use self::variant_id::VariantID;
 
-use self::id_database::ID_DATABASE;
 use super::ResolvableAddress;
 
 /// Represents a memory mapping ID and offset.
@@ -17,6 +25,7 @@ use super::ResolvableAddress;
 /// This struct is used to uniquely identify a mapped memory region.
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Mapping {
     /// The unique ID of the memory-mapped file.
     pub id: u64,
@@ -24,6 +33,73 @@ pub struct Mapping {
     pub offset: u64,
 }
 
+// Same layout as `mapping_t` of `REL::IDDatabase`(include/REL/ID.h): `{ std::uint64_t id; std::uint64_t offset; }`
+// The address library table in the shared memory is an array of it.
+static_assertions::assert_eq_size!(Mapping, [u8; 16]);
+static_assertions::assert_eq_align!(Mapping, u64);
+static_assertions::const_assert_eq!(core::mem::offset_of!(Mapping, id), 0x0);
+static_assertions::const_assert_eq!(core::mem::offset_of!(Mapping, offset), 0x8);
+
+impl Mapping {
+    /// Searches `mappings` for the entry with the given `id`. O(log n)
+    ///
+    /// `mappings` must be sorted by `id`. (e.g. the address library table)
+    /// Otherwise, the result is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::Mapping;
+    ///
+    /// let mappings = [Mapping { id: 1, offset: 0x30 }, Mapping { id: 2, offset: 0x10 }];
+    /// assert_eq!(Mapping::search_by_id(&mappings, 2), Some(&mappings[1]));
+    /// assert_eq!(Mapping::search_by_id(&mappings, 3), None);
+    /// ```
+    #[inline]
+    pub fn search_by_id(mappings: &[Self], id: u64) -> Option<&Self> {
+        mappings
+            .binary_search_by(|m| m.id.cmp(&id))
+            .ok()
+            .map(|index| &mappings[index])
+    }
+
+    /// Searches `mappings` for the entry with the given `offset`. O(log n)
+    ///
+    /// `mappings` must be sorted by `offset`. (e.g. [`OffsetToID`] table)
+    /// Otherwise, the result is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::Mapping;
+    ///
+    /// let mappings = [Mapping { id: 2, offset: 0x10 }, Mapping { id: 1, offset: 0x30 }];
+    /// assert_eq!(Mapping::search_by_offset(&mappings, 0x30), Some(&mappings[1]));
+    /// assert_eq!(Mapping::search_by_offset(&mappings, 0x20), None);
+    /// ```
+    #[inline]
+    pub fn search_by_offset(mappings: &[Self], offset: u64) -> Option<&Self> {
+        mappings
+            .binary_search_by(|m| m.offset.cmp(&offset))
+            .ok()
+            .map(|index| &mappings[index])
+    }
+}
+
+/// Formats as `id => offset` in hex, e.g. `0x2b25 => 0x1ede80`.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::id::Mapping;
+///
+/// let mapping = Mapping { id: 11045, offset: 0x1ede80 };
+/// assert_eq!(mapping.to_string(), "0x2b25 => 0x1ede80");
+/// ```
+impl core::fmt::Display for Mapping {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self { id, offset } = self;
+        write!(f, "{id:#x} => {offset:#x}")
+    }
+}
+
 /// Represents different formats of the address library.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Format {
@@ -60,10 +136,57 @@ impl ID {
 impl ResolvableAddress for ID {
     /// Retrieves the offset corresponding to the ID.
     ///
+    /// If the ID is missing, its fallback signature is scanned. (See [`IdDatabase::register_fallback_signature`])
+    ///
     /// # Errors
     /// Returns an error if the ID is not found in the database.
     #[inline]
     fn offset(&self) -> Result<usize, DataBaseError> {
-        ID_DATABASE.id_to_offset(self.0)
+        id_database::resolve_offset(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_search() {
+        let by_id = [
+            Mapping {
+                id: 1,
+                offset: 0x30,
+            },
+            Mapping {
+                id: 5,
+                offset: 0x10,
+            },
+            Mapping {
+                id: 9,
+                offset: 0x20,
+            },
+        ];
+        assert_eq!(
+            Mapping::search_by_id(&by_id, 1).map(|m| m.offset),
+            Some(0x30)
+        );
+        assert_eq!(
+            Mapping::search_by_id(&by_id, 9).map(|m| m.offset),
+            Some(0x20)
+        );
+        assert_eq!(Mapping::search_by_id(&by_id, 4), None);
+        assert_eq!(Mapping::search_by_id(&[], 1), None);
+
+        let mut by_offset = by_id.clone();
+        by_offset.sort_by(|a, b| a.offset.cmp(&b.offset));
+        assert_eq!(
+            Mapping::search_by_offset(&by_offset, 0x10).map(|m| m.id),
+            Some(5)
+        );
+        assert_eq!(
+            Mapping::search_by_offset(&by_offset, 0x30).map(|m| m.id),
+            Some(1)
+        );
+        assert_eq!(Mapping::search_by_offset(&by_offset, 0x15), None);
     }
 }
@@ -9,9 +9,11 @@
 //! This module allows efficient lookup of IDs corresponding to memory offsets.
 //! The mapping is backed by a sorted vector for quick binary search.
 
-use super::id_database::ID_DATABASE;
+use super::id_database::{global, read_mappings, DataBaseError, IdDatabase};
 use super::shared_rwlock::{PoisonError, RwLockReadGuard};
 use super::Mapping;
+use crate::rel::module::{ModuleState, ModuleStateError, Runtime};
+use crate::rel::version::Version;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// Maps memory offsets to unique IDs using a sorted list for quick lookup.
@@ -25,24 +27,131 @@ impl OffsetToID {
     /// Creates a new `Offset2ID` instance by loading the offset-to-ID mapping(Global instance).
     ///
     /// # Errors
-    /// If poisoned.
+    /// Never returns an error. (See [`Self::from_global`])
+    ///
+    /// # Panics
+    /// If the global address library could not be loaded.
+    #[deprecated = "This never returns an error. Use `OffsetToID::from_global` instead."]
+    pub fn new() -> Result<Self, PoisonError<RwLockReadGuard<'static, Mapping>>> {
+        Ok(Self::from_global().unwrap_or_else(|err| panic!("{err}")))
+    }
+
+    /// Creates a new `OffsetToID` instance by loading the offset-to-ID mapping(Global instance).
+    ///
+    /// A poisoned lock is recovered from, as in ID to offset resolution.
+    ///
+    /// # Errors
+    /// If the global address library could not be loaded.
     ///
     /// # Note
     /// Parse the binary table of bin data in `AddressLibrary` and arrange the offset/id pair structures in order of offset,
     /// noting that a call to [`Clone::clone`] is made to prevent sort from destroying the existing table.
-    pub fn new() -> Result<Self, PoisonError<RwLockReadGuard<'static, Mapping>>> {
-        let mut offset_to_id = ID_DATABASE.mem_map.read()?.to_vec();
+    pub fn from_global() -> Result<Self, DataBaseError> {
+        Ok(Self::from_database(global()?))
+    }
+
+    /// Creates a new `OffsetToID` instance from an address library loaded by [`IdDatabase::load`].
+    ///
+    /// Unlike [`Self::from_global`], this does not touch the global address library, so tables of several versions can be held at once.
+    pub fn from_database(database: &IdDatabase) -> Self {
+        let offset_to_id = database.read_poison_free().to_vec();
+        Self::from_mappings(offset_to_id)
+    }
+
+    /// Creates a new `OffsetToID` instance by parsing an address library from `reader`.
+    ///
+    /// Unlike [`Self::from_global`] and [`IdDatabase::load`], neither the global address library nor the shared memory is touched,
+    /// so this can be used by an offline tool. (e.g. symbolicating crash logs on CI)
+    ///
+    /// - `version`: The game version which the address library must be made for.
+    /// - `runtime`: Selects the expected format of the file. SE/VR: 1(`version-*.bin`), AE: 2(`versionlib-*.bin`)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::OffsetToID;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let mut file = std::io::BufReader::new(std::fs::File::open("versionlib-1.6.1170.0.bin").unwrap());
+    /// let table = OffsetToID::from_reader(&mut file, Version::new(1, 6, 1170, 0), Runtime::Ae).unwrap();
+    /// println!("{:?}", table.get_nearest_id(0x12_3456));
+    /// ```
+    ///
+    /// # Errors
+    /// - If the version of the file mismatches with `version`.
+    /// - If parsing of the header or mappings fails.
+    pub fn from_reader<R>(
+        reader: &mut R,
+        version: Version,
+        runtime: Runtime,
+    ) -> Result<Self, DataBaseError>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let expected_fmt_ver = if runtime.is_ae() { 2 } else { 1 }; // Expected AddressLibrary format version. SE/VR: 1, AE: 2
+        let mappings = read_mappings(reader, version, expected_fmt_ver)?;
+        Ok(Self::from_mappings(mappings))
+    }
+
+    /// Creates a table from arbitrary `mappings`, sorting them by offset.
+    ///
+    /// Unlike [`Self::from_global`], this does not touch the global address library.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::{Mapping, OffsetToID};
+    ///
+    /// let table = OffsetToID::from_mappings(vec![Mapping { id: 2, offset: 0x20 }, Mapping { id: 1, offset: 0x10 }]);
+    /// assert_eq!(table.get_id(0x20), Some(2));
+    /// ```
+    pub fn from_mappings(mut offset_to_id: Vec<Mapping>) -> Self {
         offset_to_id.sort_by(|a, b| a.offset.cmp(&b.offset));
-        Ok(Self { offset_to_id })
+        Self { offset_to_id }
     }
 
     /// Gets the ID corresponding to the given `offset`, if available.
     ///
     /// Performs a binary search on the sorted mapping. O(log n)
     pub fn get_id(&self, offset: u64) -> Option<u64> {
-        let elem = Mapping { id: 0, offset };
-        self.offset_to_id
-            .binary_search_by(|m| m.offset.cmp(&elem.offset))
-            .map_or_else(|_| None, |index| Some(self.offset_to_id[index].id))
+        Mapping::search_by_offset(&self.offset_to_id, offset).map(|mapping| mapping.id)
+    }
+
+    /// Gets the mapping with the greatest offset not exceeding `offset`. O(log n)
+    ///
+    /// i.e. the ID whose code/data most likely contains `offset`.
+    /// Returns `None` if `offset` is below the first mapping.
+    pub fn get_nearest(&self, offset: u64) -> Option<&Mapping> {
+        let index = self
+            .offset_to_id
+            .partition_point(|mapping| mapping.offset <= offset);
+        index.checked_sub(1).map(|index| &self.offset_to_id[index])
+    }
+
+    /// Gets the ID with the greatest offset not exceeding `offset`. See [`Self::get_nearest`].
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::{Mapping, OffsetToID};
+    ///
+    /// let table = OffsetToID::from_mappings(vec![Mapping { id: 1, offset: 0x10 }, Mapping { id: 2, offset: 0x20 }]);
+    /// assert_eq!(table.get_nearest_id(0x1f), Some(1));
+    /// assert_eq!(table.get_nearest_id(0x0f), None);
+    /// ```
+    pub fn get_nearest_id(&self, offset: u64) -> Option<u64> {
+        self.get_nearest(offset).map(|mapping| mapping.id)
+    }
+
+    /// Gets the ID corresponding to the given absolute `address`(e.g. a runtime pointer), if available.
+    ///
+    /// The module base is subtracted before searching, so this follows the module base even if it changes.
+    /// An address below the module base is never found.
+    ///
+    /// # Errors
+    /// Returns an error if the module base cannot be retrieved.
+    pub fn get_id_from_address(&self, address: usize) -> Result<Option<u64>, ModuleStateError> {
+        let base = ModuleState::base_address()?;
+        Ok(address
+            .checked_sub(base)
+            .and_then(|offset| self.get_id(offset as u64)))
     }
 }
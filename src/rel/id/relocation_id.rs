@@ -1,7 +1,9 @@
 use crate::rel::id::id_database::DataBaseError;
+use crate::rel::module::Runtime;
+use crate::rel::ResolvableAddress;
 
 /// Represents an ID that varies based on runtime format.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RelocationID {
     se_id: u64,
     ae_id: u64,
@@ -19,27 +21,109 @@ impl RelocationID {
         }
     }
 
+    /// Creates a new RelocationID from `(Runtime, id)` pairs.
+    ///
+    /// Runtimes not contained in `pairs` are filled with `0`.
+    /// If the same runtime appears more than once, the last one wins.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::RelocationID;
+    /// use commonlibsse_ng::rel::module::Runtime;
+    ///
+    /// let id = RelocationID::from_pairs(&[(Runtime::Se, 10), (Runtime::Ae, 20)]);
+    /// assert_eq!(id, RelocationID::new(10, 20, 0));
+    /// ```
+    pub const fn from_pairs(pairs: &[(Runtime, u64)]) -> Self {
+        let mut this = Self::new(0, 0, 0);
+
+        let mut i = 0;
+        while i < pairs.len() {
+            let (runtime, id) = pairs[i];
+            this = match runtime {
+                Runtime::Ae => this.with_ae(id),
+                Runtime::Se => this.with_se(id),
+                Runtime::Vr => this.with_vr(id),
+            };
+            i += 1;
+        }
+        this
+    }
+
+    /// Sets the ID for Skyrim Special Edition.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::RelocationID;
+    ///
+    /// let id = RelocationID::default().with_se(10).with_ae(20);
+    /// assert_eq!(id, RelocationID::new(10, 20, 0));
+    /// ```
+    #[inline]
+    pub const fn with_se(mut self, se_id: u64) -> Self {
+        self.se_id = se_id;
+        self
+    }
+
+    /// Sets the ID for Skyrim Anniversary Edition.
+    #[inline]
+    pub const fn with_ae(mut self, ae_id: u64) -> Self {
+        self.ae_id = ae_id;
+        self
+    }
+
+    /// Sets the ID for Skyrim VR.
+    #[inline]
+    pub const fn with_vr(mut self, vr_id: u64) -> Self {
+        self.vr_id = vr_id;
+        self
+    }
+
+    /// Returns the ID for Skyrim Special Edition.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::RelocationID;
+    ///
+    /// const ID: RelocationID = RelocationID::new(10, 20, 30);
+    /// const SE: u64 = ID.se();
+    /// assert_eq!((SE, ID.ae(), ID.vr()), (10, 20, 30));
+    /// ```
+    #[inline]
+    pub const fn se(&self) -> u64 {
+        self.se_id
+    }
+
+    /// Returns the ID for Skyrim Anniversary Edition.
+    #[inline]
+    pub const fn ae(&self) -> u64 {
+        self.ae_id
+    }
+
+    /// Returns the ID for Skyrim VR.
+    #[inline]
+    pub const fn vr(&self) -> u64 {
+        self.vr_id
+    }
+
     /// Retrieves the absolute address corresponding to the ID.
     ///
     /// # Errors
     /// Returns an error if the ID cannot be resolved.
     #[inline]
     pub fn address(&self) -> Result<usize, DataBaseError> {
-        let offset = self.offset()?;
-        Ok(if offset == 0 {
-            0
-        } else {
-            Self::base()? + offset
-        })
+        crate::rel::resolve_address(self.offset()?)
     }
 
     /// Retrieves the offset corresponding to the ID.
     ///
+    /// If the ID is missing, its fallback signature is scanned. (See [`IdDatabase::register_fallback_signature`](crate::rel::id::IdDatabase::register_fallback_signature))
+    ///
     /// # Errors
     /// Returns an error if the ID is not found.
     #[inline]
     pub fn offset(&self) -> Result<usize, DataBaseError> {
-        crate::rel::id::id_database::ID_DATABASE.id_to_offset(self.id()?)
+        crate::rel::id::id_database::resolve_offset(self.id()?)
     }
 
     /// Retrieves the appropriate ID based on the runtime format.
@@ -47,9 +131,9 @@ impl RelocationID {
     /// # Errors
     /// Returns an error if the module is in an invalid state.
     pub fn id(&self) -> Result<u64, crate::rel::module::ModuleStateError> {
-        use crate::rel::module::{ModuleState, Runtime};
+        use crate::rel::module::ModuleState;
 
-        let runtime = ModuleState::map_or_init(|module| module.runtime)?; // derived Copy
+        let runtime = ModuleState::runtime()?;
 
         Ok(match runtime {
             Runtime::Ae => self.ae_id,
@@ -57,13 +141,47 @@ impl RelocationID {
             Runtime::Vr => self.vr_id,
         })
     }
+}
 
-    /// Retrieves the base address of the module.
+impl ResolvableAddress for RelocationID {
+    /// Retrieves the offset corresponding to the ID for the current runtime.
     ///
     /// # Errors
-    /// Returns an error if the module is in an invalid state.
+    /// Returns an error if the ID is not found.
     #[inline]
-    fn base() -> Result<usize, crate::rel::module::ModuleStateError> {
-        crate::rel::module::ModuleState::map_or_init(|module| module.base.as_raw())
+    fn offset(&self) -> Result<usize, DataBaseError> {
+        Self::offset(self)
+    }
+}
+
+impl core::fmt::Display for RelocationID {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self {
+            se_id,
+            ae_id,
+            vr_id,
+        } = self;
+        write!(f, "RelocationID(SE: {se_id}, AE: {ae_id}, VR: {vr_id})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relocation_id_from_pairs() {
+        let id = RelocationID::from_pairs(&[(Runtime::Ae, 20), (Runtime::Se, 10)]);
+        assert_eq!(id, RelocationID::new(10, 20, 0));
+        assert_eq!(id, RelocationID::default().with_ae(20).with_se(10));
+
+        let id = RelocationID::from_pairs(&[(Runtime::Vr, 30), (Runtime::Vr, 31)]);
+        assert_eq!(id, RelocationID::new(0, 0, 31));
+    }
+
+    #[test]
+    fn test_relocation_id_display() {
+        let id = RelocationID::new(10, 20, 30);
+        assert_eq!(id.to_string(), "RelocationID(SE: 10, AE: 20, VR: 30)");
     }
 }
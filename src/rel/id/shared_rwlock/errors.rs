@@ -7,6 +7,12 @@ pub enum MemoryMapError {
     /// Failed to create memory mapping: {source}
     CreateMapping { source: windows::core::Error },
 
+    /// The size of the shared memory for {len} elements of {elem_size} bytes overflows `usize`.
+    SizeOverflow { len: usize, elem_size: usize },
+
+    /// Expected a non-zero length for the shared memory, but got 0.
+    ZeroSize,
+
     /// Failed to map view of file.
     MapView,
 
@@ -15,4 +21,15 @@ pub enum MemoryMapError {
 
     /// Failed to close handle: {source}
     CloseHandle { source: windows::core::Error },
+
+    /// The opened shared memory was created for a different element layout. Expected (size: {expected_size}, align: {expected_align}), but got (size: {actual_size}, align: {actual_align})
+    LayoutMismatch {
+        expected_size: usize,
+        expected_align: usize,
+        actual_size: usize,
+        actual_align: usize,
+    },
+
+    /// The mapped view at {address:#x} is not aligned to {align} bytes required by the element type.
+    Misaligned { address: usize, align: usize },
 }
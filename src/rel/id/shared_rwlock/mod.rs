@@ -15,13 +15,17 @@ mod tests;
 
 pub use self::errors::MemoryMapError;
 pub use self::poison::{LockResult, PoisonError, TryLockError, TryLockResult};
+pub(crate) use self::shared_mem::exists as shared_mem_exists;
+pub use self::shared_mem::MappingOptions;
 
 use core::cell::UnsafeCell;
 use core::fmt;
 use core::marker::PhantomData;
-use core::mem::{size_of, ManuallyDrop};
+use core::mem::{align_of, size_of, ManuallyDrop};
 use core::ops::{Deref, DerefMut};
 use core::ptr::NonNull;
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use core::time::Duration;
 use core::{ffi::c_void, num::NonZeroUsize};
 use windows::core::HSTRING;
 
@@ -30,8 +34,12 @@ struct SharedCell<T: ?Sized> {
     // shared memory lock state: 64bytes(To avoid false sharing)
     inner: sys::RwLock, // size 56bytes
     poison: poison::Flag,
-    _pad39: u8,  // 0x39
-    _pad3a: u32, // 0x3a
+    /// `align_of::<T>().trailing_zeros()` stamped by the creator. (See [`SharedRwLock::new_checked`])
+    elem_align_log2: AtomicU8, // 0x39
+    /// `size_of::<T>()` stamped by the creator. (See [`SharedRwLock::new_checked`])
+    ///
+    /// Stored last with `Release`, so a non-zero value also publishes `elem_align_log2`.
+    elem_size: AtomicU32, // 0x3c
     // <------- 64bytes
 
     // Shared memory data array start(Same as `MEMORY_MAPPED_VIEW_ADDRESS` ptr)
@@ -44,9 +52,31 @@ struct SharedCell<T: ?Sized> {
 }
 
 static_assertions::assert_eq_size!(SharedCell<u64>, [u8; 64 + 8]);
+// The false sharing avoidance depends on `data` starting at the next cache line.
+// If the size of `sys::RwLock` changes, these catch it at compile time.
+static_assertions::const_assert_eq!(core::mem::offset_of!(SharedCell<u64>, inner), 0);
+static_assertions::const_assert_eq!(core::mem::offset_of!(SharedCell<u64>, poison), 0x38);
+#[cfg(panic = "unwind")] // `poison::Flag` is ZST with `panic = "abort"`.
+static_assertions::const_assert_eq!(
+    core::mem::offset_of!(SharedCell<u64>, elem_align_log2),
+    0x39
+);
+static_assertions::const_assert_eq!(core::mem::offset_of!(SharedCell<u64>, elem_size), 0x3c);
+static_assertions::const_assert_eq!(
+    core::mem::offset_of!(SharedCell<u64>, data),
+    RWLOCK_LOCK_STATE_SIZE
+);
+// Over-aligned elements would move `data` beyond the lock state.(`SharedRwLock::mapping_size` assumes it does not.)
+static_assertions::const_assert_eq!(
+    core::mem::offset_of!(SharedCell<crate::rel::id::Mapping>, data),
+    RWLOCK_LOCK_STATE_SIZE
+);
 
 const RWLOCK_LOCK_STATE_SIZE: usize = 64;
 
+/// How long an opener waits for the creator to stamp the element layout. (See [`SharedRwLock::new_checked`])
+const LAYOUT_STAMP_TIMEOUT: Duration = Duration::from_secs(1);
+
 unsafe impl<T: ?Sized + Send> Send for SharedCell<T> {}
 unsafe impl<T: ?Sized + Send + Sync> Sync for SharedCell<T> {}
 
@@ -111,6 +141,8 @@ unsafe impl<T: ?Sized + Send + Sync> Sync for SharedCell<T> {}
 pub struct SharedRwLock<T: ?Sized> {
     // Handle ptr(by `open`/`create`)
     handle: NonZeroUsize,
+    // Name of the shared memory passed to `new`
+    shared_id: HSTRING,
     // Length of the shared data
     len: usize,
 
@@ -137,39 +169,355 @@ impl<T> SharedRwLock<T> {
     ///
     /// The lock data itself is allocated on the shared memory according to the C ABI and the lock state is read/write by AtomicT.
     ///
+    /// Returns [`SharedMemInit::Created`] if the memory was newly created (and therefore needs to be initialized),
+    /// or [`SharedMemInit::Opened`] if an existing one was opened.
+    ///
+    /// Even if another process creates the same memory between the failed open and the create,
+    /// this detects `ERROR_ALREADY_EXISTS` and returns [`SharedMemInit::Opened`], so that only the true creator initializes it.
+    ///
     /// # Errors
-    /// If memory cannot be opened, it creates, but if even that fails, it returns an error.
+    /// - [`MemoryMapError::ZeroSize`] if `len` is `0`. (e.g. a corrupted header)
+    /// - If the mapping size overflows. (See [`Self::mapping_size`])
+    /// - If memory cannot be opened, it creates, but if even that fails, it returns an error.
     ///
     /// # Note: Initial value when mem create.
-    /// Created memory is filled with 0, which is the same value as the first initialization.
+    /// The lock state is initialized explicitly, but the data is left as zero-filled by the OS.
     ///
     /// # Panics
     /// Invalid pointer.
+    pub fn new(shared_id: &HSTRING, len: usize) -> Result<SharedMemInit<T>, MemoryMapError> {
+        Self::new_with(
+            shared_id,
+            len,
+            &MappingOptions::default(),
+            shared_mem::create,
+        )
+    }
+
+    /// Same as [`Self::new`], but with the namespace and security of the shared memory specified.
+    ///
+    /// By default, the memory lives in the session-local namespace and can only be opened by the creator's account.
+    /// This allows sharing the database across sessions(`Global\`) or user accounts. (See [`MappingOptions`])
+    ///
+    /// [`Self::shared_id`] returns the name actually used. (e.g. `Global\{shared_id}`)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::shared_rwlock::{MappingOptions, SharedRwLock};
+    /// use windows::core::h;
+    ///
+    /// let options = MappingOptions { global_namespace: true, allow_all_users: true };
+    /// let lock = SharedRwLock::<u64>::new_with_options(h!("MyDatabase"), 4, options).unwrap().into_inner();
+    /// assert_eq!(lock.shared_id(), h!("Global\\MyDatabase"));
+    /// ```
+    ///
+    /// # Errors
+    /// - Same as [`Self::new`].
+    /// - If the security descriptor cannot be initialized.
+    /// - If creating a global mapping without `SeCreateGlobalPrivilege`.
+    pub fn new_with_options(
+        shared_id: &HSTRING,
+        len: usize,
+        options: MappingOptions,
+    ) -> Result<SharedMemInit<T>, MemoryMapError> {
+        let shared_id = options.mapped_name(shared_id);
+        Self::new_with(&shared_id, len, &options, shared_mem::create)
+    }
+
+    /// Same as [`Self::new`], but backs newly created memory with large pages if possible.
+    ///
+    /// This reduces TLB pressure when scanning a large table(e.g. ~800k entries of the address library).
+    /// Large pages require `SeLockMemoryPrivilege` to be held and enabled in the process token;
+    /// otherwise this silently falls back to normal pages.
+    /// (An existing memory is opened as is, whichever pages it was created with.)
+    ///
+    /// # Errors
+    /// Same as [`Self::new`].
+    pub fn new_large_pages(
+        shared_id: &HSTRING,
+        len: usize,
+    ) -> Result<SharedMemInit<T>, MemoryMapError> {
+        Self::new_with(
+            shared_id,
+            len,
+            &MappingOptions::default(),
+            shared_mem::create_large_pages,
+        )
+    }
+
     #[allow(clippy::unwrap_in_result)]
-    pub fn new(shared_id: &HSTRING, len: usize) -> Result<(Self, bool), MemoryMapError> {
-        let size = RWLOCK_LOCK_STATE_SIZE + size_of::<T>() * len;
+    fn new_with(
+        shared_id: &HSTRING,
+        len: usize,
+        options: &MappingOptions,
+        create: shared_mem::CreateFn,
+    ) -> Result<SharedMemInit<T>, MemoryMapError> {
+        // Only the lock state would be mapped, and then the data could never be read.
+        if len == 0 {
+            return Err(MemoryMapError::ZeroSize);
+        }
+        let size = Self::checked_mapping_size(len)?;
         let ((handle, view), is_created) = shared_mem::open(shared_id, size)
             .map(|pair| (pair, false))
-            .or_else(|_| shared_mem::create(shared_id, size).map(|pair| (pair, true)))?;
+            .or_else(|_| {
+                // Another process may have created it between `open` and `create`.
+                create(shared_id, size, options)
+                    .map(|(handle, view, already_existed)| ((handle, view), !already_existed))
+            })?;
+
+        let this = Self::from_view(handle, view, shared_id.clone(), len, is_created)?;
+        Ok(if is_created {
+            SharedMemInit::Created(this)
+        } else {
+            SharedMemInit::Opened(this)
+        })
+    }
 
+    /// Wraps a mapped view into the lock, taking ownership of `handle` and `view`.
+    ///
+    /// Both are released on error. If `is_created`, the lock state is initialized.
+    fn from_view(
+        handle: windows::Win32::Foundation::HANDLE,
+        view: windows::Win32::System::Memory::MEMORY_MAPPED_VIEW_ADDRESS,
+        shared_id: HSTRING,
+        len: usize,
+        is_created: bool,
+    ) -> Result<Self, MemoryMapError> {
         let ptr = view.Value.cast::<SharedCell<T>>();
+        let (Some(handle_raw), Some(shared)) =
+            (NonZeroUsize::new(handle.0 as usize), NonNull::new(ptr))
+        else {
+            // `shared_mem` has already checked both, but never leak them even on this path.
+            let _ = shared_mem::close(handle, view.Value);
+            return Err(MemoryMapError::MapView);
+        };
+
+        // `MapViewOfFile` returns allocation granularity(64KiB) aligned views, so this never fails in practice.
+        // However, every slice created from `data` relies on it, so reject the view instead of causing UB.
+        let align = align_of::<SharedCell<T>>();
+        if ptr.align_offset(align) != 0 {
+            let _ = shared_mem::close(handle, view.Value);
+            return Err(MemoryMapError::Misaligned {
+                address: ptr as usize,
+                align,
+            });
+        }
 
-        // NOTE: Initial value when mem create.
-        // Created memory is filled with 0, which is the same value as the first initialization.
+        // Initialize the lock state explicitly instead of relying on the zero-fill by the OS.
+        // Also stamp the element layout so that openers can validate it by `Self::new_checked`.
         //
-        // if is_created {
-        //     let ptr = unsafe {
-        //         &mut *ptr.write(SharedCell::new(t));
-        //     };
-        // }
-
-        Ok((
-            Self {
-                handle: NonZeroUsize::new(handle.0 as usize).unwrap(),
-                len,
-                shared: NonNull::new(ptr).unwrap(),
-            },
-            is_created,
+        // The data region is left as it is(zero-filled), because its initialization is the caller's responsibility.
+        if is_created {
+            // SAFETY: `ptr` points to the start of the view, which is at least `RWLOCK_LOCK_STATE_SIZE` bytes.
+            //         Only the header fields are written, and no reference to the uninitialized data is created.
+            unsafe {
+                (&raw mut (*ptr).inner).write(sys::RwLock::new());
+                (&raw mut (*ptr).poison).write(poison::Flag::new());
+            }
+            // The mapping is already visible by name, so an opener may read the stamp concurrently.
+            // SAFETY: Same as above. The stamp fields are only accessed atomically.
+            let (elem_align_log2, elem_size) =
+                unsafe { (&(*ptr).elem_align_log2, &(*ptr).elem_size) };
+            elem_align_log2.store(align_of::<T>().trailing_zeros() as u8, Ordering::Relaxed);
+            elem_size.store(size_of::<T>() as u32, Ordering::Release);
+        }
+
+        Ok(Self {
+            handle: handle_raw,
+            shared_id,
+            len,
+            shared,
+        })
+    }
+
+    /// Computes the byte size of the shared memory needed for `len` elements. (lock state + `T` * `len`)
+    ///
+    /// This is pure arithmetic, separated from the mapping so that it can also be used in const context.
+    /// Returns `None` if the size does not fit in `usize`.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::shared_rwlock::SharedRwLock;
+    ///
+    /// const SIZE: usize = match SharedRwLock::<u64>::mapping_size(2) {
+    ///     Some(size) => size,
+    ///     None => panic!("overflow"),
+    /// };
+    /// assert_eq!(SIZE, 64 + 8 * 2);
+    /// assert_eq!(SharedRwLock::<u64>::mapping_size(usize::MAX), None);
+    /// ```
+    pub const fn mapping_size(len: usize) -> Option<usize> {
+        match size_of::<T>().checked_mul(len) {
+            Some(data_size) => data_size.checked_add(RWLOCK_LOCK_STATE_SIZE),
+            None => None,
+        }
+    }
+
+    /// [`Self::mapping_size`] as an error for the constructors.
+    fn checked_mapping_size(len: usize) -> Result<usize, MemoryMapError> {
+        Self::mapping_size(len).ok_or(MemoryMapError::SizeOverflow {
+            len,
+            elem_size: size_of::<T>(),
+        })
+    }
+
+    /// Same as [`Self::new`], but validates the element layout when opening an existing shared memory.
+    ///
+    /// The creator stamps `size_of::<T>()` and `align_of::<T>()` into the otherwise unused padding of the lock state.
+    /// When another process(or plugin) opens the same named memory expecting a different `T`,
+    /// this returns an error instead of silently reinterpreting the data.
+    ///
+    /// # Errors
+    /// - Same as [`Self::new`].
+    /// - [`MemoryMapError::LayoutMismatch`] if the opened memory was created for a different element layout.
+    ///   (Memory created by a version without the stamp is also treated as a mismatch.)
+    ///
+    /// # Note
+    /// The creator stamps the layout right after creating the mapping, so an opener racing with it may see no stamp yet.
+    /// In that case, this waits for the stamp up to 1 second before reporting a mismatch.
+    pub fn new_checked(
+        shared_id: &HSTRING,
+        len: usize,
+    ) -> Result<SharedMemInit<T>, MemoryMapError> {
+        let init = Self::new(shared_id, len)?;
+
+        if let SharedMemInit::Opened(lock) = &init {
+            lock.check_layout()?;
+        }
+
+        Ok(init)
+    }
+
+    /// Maps a view of an already opened file mapping `handle`, instead of opening it by name.
+    ///
+    /// This is for the case where another component holds the mapping and passes its handle to this process.
+    /// The memory must have been created by [`SharedRwLock`] of the same `T`, and is validated as [`Self::new_checked`] does.
+    ///
+    /// The returned lock takes ownership of `handle`: it is closed on drop, and also on error.
+    /// Since the lock is not opened by name, [`Self::shared_id`] is empty.
+    ///
+    /// # Safety
+    /// `handle` must be a file mapping handle that is valid in this process, and must not be closed(or owned) by anyone else.
+    /// A handle of another process must be duplicated into this process by `DuplicateHandle` first,
+    /// with at least `FILE_MAP_READ | FILE_MAP_WRITE` access.
+    ///
+    /// # Errors
+    /// - [`MemoryMapError::ZeroSize`] if `len` is `0`.
+    /// - If the mapping size overflows. (See [`Self::mapping_size`])
+    /// - [`MemoryMapError::MapView`] if the view cannot be mapped. (e.g. `len` exceeds the mapping size, or lacking access rights)
+    /// - [`MemoryMapError::LayoutMismatch`] if the memory was created for a different element layout.
+    pub unsafe fn from_handle(
+        handle: windows::Win32::Foundation::HANDLE,
+        len: usize,
+    ) -> Result<Self, MemoryMapError> {
+        let handle = shared_mem::HandleGuard(handle); // Closes the handle on early return.
+        if len == 0 {
+            return Err(MemoryMapError::ZeroSize);
+        }
+        let size = Self::checked_mapping_size(len)?;
+
+        let handle = handle.release();
+        let view = shared_mem::map_handle(handle, size)?;
+        let this = Self::from_view(handle, view, HSTRING::new(), len, false)?;
+        this.check_layout()?; // `this` is dropped on error, which unmaps the view and closes the handle.
+
+        Ok(this)
+    }
+
+    /// Returns [`MemoryMapError::LayoutMismatch`] if the memory was stamped with a different layout from `T`.
+    fn check_layout(&self) -> Result<(), MemoryMapError> {
+        let (actual_size, actual_align) = self.wait_elem_layout();
+        if actual_size != size_of::<T>() || actual_align != align_of::<T>() {
+            return Err(MemoryMapError::LayoutMismatch {
+                expected_size: size_of::<T>(),
+                expected_align: align_of::<T>(),
+                actual_size,
+                actual_align,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the element `(size, align)` stamped by the creator, waiting while it is not stamped yet.
+    ///
+    /// Returns `(0, 0)` if it is still not stamped after [`LAYOUT_STAMP_TIMEOUT`]. (e.g. created by a version without the stamp)
+    fn wait_elem_layout(&self) -> (usize, usize) {
+        let start = std::time::Instant::now();
+        loop {
+            if let Some(layout) = self.shared().elem_layout() {
+                return layout;
+            }
+            if start.elapsed() >= LAYOUT_STAMP_TIMEOUT {
+                return (0, 0);
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    /// Returns the shared data as a slice without acquiring the lock.
+    ///
+    /// This is an escape hatch for hot read paths of read-mostly data(e.g. the address database after it has been unpacked),
+    /// where the cost of the atomic lock operations is not negligible.
+    ///
+    /// # Safety
+    /// The caller must guarantee the following for the entire lifetime of the returned slice.
+    ///
+    /// - No writer exists. That is, no [`RwLockWriteGuard`]/[`MappedRwLockWriteGuard`] is alive in this process,
+    ///   and no other process that opened the same shared memory writes to it.
+    ///   Even one concurrent write is a data race, and thus undefined behavior.
+    /// - The data has already been initialized. (Freshly created memory is only zero-filled.)
+    ///
+    /// # Note
+    /// Since the lock is skipped, poison is not checked either.
+    /// Even if a writer panicked while holding the lock, the possibly half-written data is returned as it is.
+    #[inline]
+    pub unsafe fn peek_unchecked(&self) -> &[T] {
+        // SAFETY: The pointer is valid for `len` elements as long as `self` is alive,
+        //         and the caller guarantees that no concurrent writes occur.
+        unsafe { core::slice::from_raw_parts(self.shared().data.get(), self.len) }
+    }
+}
+
+/// Result of [`SharedRwLock::new`], telling whether the shared memory was newly created or
+/// an existing one was opened.
+///
+/// Newly created memory is zero-filled, so the caller is responsible for initializing it.
+#[derive(Debug)]
+pub enum SharedMemInit<T> {
+    /// The shared memory was newly created by this call. Its data needs to be initialized.
+    Created(SharedRwLock<T>),
+    /// The shared memory already existed (e.g. created by another plugin) and was opened.
+    Opened(SharedRwLock<T>),
+}
+
+impl<T> SharedMemInit<T> {
+    /// Returns `true` if the shared memory was newly created.
+    #[inline]
+    pub const fn is_created(&self) -> bool {
+        matches!(self, Self::Created(_))
+    }
+
+    /// Returns the lock regardless of whether it was created or opened.
+    #[inline]
+    pub fn into_inner(self) -> SharedRwLock<T> {
+        match self {
+            Self::Created(lock) | Self::Opened(lock) => lock,
+        }
+    }
+}
+
+impl<T: ?Sized> SharedCell<T> {
+    /// Returns the element `(size, align)` stamped by the creator of the shared memory,
+    /// or `None` if it is not stamped(yet).
+    #[inline]
+    fn elem_layout(&self) -> Option<(usize, usize)> {
+        let size = self.elem_size.load(Ordering::Acquire);
+        if size == 0 {
+            return None;
+        }
+        Some((
+            size as usize,
+            1 << self.elem_align_log2.load(Ordering::Relaxed),
         ))
     }
 }
@@ -179,6 +527,23 @@ impl<T: ?Sized> SharedRwLock<T> {
     const fn shared(&self) -> &SharedCell<T> {
         unsafe { self.shared.as_ref() }
     }
+
+    /// Returns the raw value of the file mapping handle for diagnostics.
+    ///
+    /// Handle values are per process, so the same mapping opened by different processes has different values.
+    /// Use [`Self::shared_id`] to correlate them.
+    #[inline]
+    pub const fn raw_handle(&self) -> usize {
+        self.handle.get()
+    }
+
+    /// Returns the name of the shared memory passed to [`SharedRwLock::new`].
+    ///
+    /// This is empty if the lock was created by [`SharedRwLock::from_handle`].
+    #[inline]
+    pub const fn shared_id(&self) -> &HSTRING {
+        &self.shared_id
+    }
 }
 
 /// RAII structure used to release the shared read access of a lock when
@@ -199,6 +564,8 @@ pub struct RwLockReadGuard<'a, T: ?Sized + 'a> {
     data: NonNull<T>,
     inner_lock: &'a sys::RwLock,
     len: usize,
+    /// For [`RwLockReadGuard::try_upgrade`].
+    lock: &'a SharedRwLock<T>,
 }
 
 // impl<T: ?Sized> !Send for RwLockReadGuard<'_, T> {}
@@ -492,6 +859,25 @@ impl<T: ?Sized> SharedRwLock<T> {
         }
     }
 
+    /// Locks this `RwLock` with exclusive write access, and clears the poison if it is poisoned.
+    ///
+    /// This is a shorthand of recovering with [`Self::clear_poison`]:
+    /// `lock.write().unwrap_or_else(|err| { lock.clear_poison(); err.into_inner() })`
+    ///
+    /// Use this only when the data is known to stay consistent even if a writer panicked
+    /// (e.g. the address database, which is fully rewritten by the next writer).
+    ///
+    /// # Panics
+    ///
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn write_recover(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(|err| {
+            self.clear_poison();
+            err.into_inner()
+        })
+    }
+
     /// Determines whether the lock is poisoned.
     ///
     /// If another thread is active, the lock can still become poisoned at any
@@ -559,9 +945,12 @@ impl<T: fmt::Debug> fmt::Debug for SharedRwLock<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut d = f.debug_struct("RwLock");
         d.field("handle", &self.handle);
+        d.field("shared_id", &self.shared_id);
         d.field("shared_address", &(self.shared.as_ptr() as usize));
         d.field("len", &self.len);
 
+        // NOTE: `try_read` is a single CAS on the lock state and never waits on the futex,
+        // so this prints `<locked>` instead of hanging even if the current thread holds the write guard.
         match self.try_read() {
             Ok(guard) => {
                 d.field("data", &&*guard);
@@ -606,13 +995,14 @@ impl<'rwlock, T: ?Sized> RwLockReadGuard<'rwlock, T> {
             data: unsafe { NonNull::new_unchecked(lock.shared().data.get()) },
             inner_lock: &lock.shared().inner,
             len: lock.len,
+            lock,
         })
     }
 }
 
 impl<'rwlock, T: ?Sized> RwLockWriteGuard<'rwlock, T> {
     /// Creates a new instance of `RwLockWriteGuard<T>` from a `RwLock<T>`.
-    // SAFETY: if and only if `lock.inner.write()` (or `lock.inner.try_write()`) has been
+    // SAFETY: if and only if `lock.inner.write()` (or `lock.inner.try_write()`, `lock.inner.try_upgrade()`) has been
     // successfully called from the same thread before instantiating this object.
     unsafe fn new(lock: &'rwlock SharedRwLock<T>) -> LockResult<Self> {
         poison::map_result(lock.shared().poison.guard(), |guard| RwLockWriteGuard {
@@ -785,6 +1175,48 @@ impl<'a, T: ?Sized> RwLockReadGuard<'a, T> {
             None => Err(orig),
         }
     }
+
+    /// Attempts to upgrade a read-locked `RwLockReadGuard` into a write-locked [`RwLockWriteGuard`].
+    ///
+    /// This succeeds only if this guard is the only reader. Otherwise, the original guard is returned as `Err(...)`,
+    /// so the caller can drop it and call [`SharedRwLock::write`] instead.
+    ///
+    /// Since the read lock is never released in between, no writer can get in between reading and writing.
+    ///
+    /// This is an associated function that needs to be used as `RwLockReadGuard::try_upgrade(...)`.
+    ///
+    /// # Errors
+    /// Returns the original guard if other readers hold the lock.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::shared_rwlock::{RwLockReadGuard, SharedRwLock};
+    /// use windows::core::h;
+    ///
+    /// let lock = SharedRwLock::<u32>::new(h!("TryUpgradeExample"), 1).unwrap().into_inner();
+    ///
+    /// let guard = lock.read().unwrap();
+    /// if guard[0] == 0 {
+    ///     // Lazily fill the value.
+    ///     if let Ok(mut guard) = RwLockReadGuard::try_upgrade(guard) {
+    ///         guard[0] = 42;
+    ///     }
+    /// }
+    /// ```
+    #[allow(clippy::mem_forget)]
+    pub fn try_upgrade(s: Self) -> Result<RwLockWriteGuard<'a, T>, Self> {
+        // SAFETY: the conditions of `RwLockReadGuard::new` were satisfied when created, so we hold a read lock.
+        if !unsafe { s.inner_lock.try_upgrade() } {
+            return Err(s);
+        }
+
+        let lock = s.lock;
+        // We don't want to call the destructor since that calls `read_unlock`.
+        core::mem::forget(s);
+
+        // SAFETY: We have just successfully called `try_upgrade`, so the `RwLock` is in write mode.
+        Ok(unsafe { RwLockWriteGuard::new(lock).unwrap_or_else(PoisonError::into_inner) })
+    }
 }
 
 impl<'a, T: ?Sized> MappedRwLockReadGuard<'a, T> {
@@ -964,6 +1396,23 @@ impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
 }
 
 impl<'a, T: ?Sized> MappedRwLockWriteGuard<'a, T> {
+    /// Clears the poisoned state of the lock this guard belongs to.
+    ///
+    /// This is the mapped guard counterpart of [`SharedRwLock::clear_poison`].
+    /// It is useful when the guard was recovered by [`PoisonError::into_inner`] and the data has been
+    /// overwritten by a known-good value through this guard.
+    ///
+    /// Note that if the current thread panics while still holding this guard, the lock is poisoned again on drop.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `MappedRwLockWriteGuard::unpoison(...)`. A method would interfere with
+    /// methods of the same name on the contents of the `MappedRwLockWriteGuard`
+    /// used through `Deref`.
+    #[inline]
+    pub fn unpoison(orig: &Self) {
+        orig.poison_flag.clear();
+    }
+
     /// Makes a [`MappedRwLockWriteGuard`] for a component of the borrowed data,
     /// e.g. an enum variant.
     ///
@@ -25,68 +25,250 @@ pub fn open(
     name: &HSTRING,
     size: usize,
 ) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS), MemoryMapError> {
-    use windows::Win32::Foundation::CloseHandle;
-    use windows::Win32::System::Memory::{
-        MapViewOfFile, OpenFileMappingW, FILE_MAP_READ, FILE_MAP_WRITE,
-    };
+    use windows::Win32::System::Memory::{OpenFileMappingW, FILE_MAP_READ, FILE_MAP_WRITE};
 
     let handle = unsafe { OpenFileMappingW((FILE_MAP_READ | FILE_MAP_WRITE).0, false, name) }
         .map_err(|e| MemoryMapError::OpenMapping { source: e })?;
 
+    Ok((handle, map_handle(handle, size)?))
+}
+
+/// Maps a read/write view of `size` bytes of an already opened mapping `handle`.
+///
+/// This takes ownership of `handle`: if mapping fails, the handle is closed.
+pub fn map_handle(
+    handle: HANDLE,
+    size: usize,
+) -> Result<MEMORY_MAPPED_VIEW_ADDRESS, MemoryMapError> {
+    use windows::Win32::System::Memory::{MapViewOfFile, FILE_MAP_READ, FILE_MAP_WRITE};
+
+    let handle = HandleGuard(handle);
+
     // MapViewOfFile: https://learn.microsoft.com/windows/win32/api/memoryapi/nf-memoryapi-mapviewoffile
-    let view_address = unsafe { MapViewOfFile(handle, FILE_MAP_READ | FILE_MAP_WRITE, 0, 0, size) };
+    let view_address =
+        unsafe { MapViewOfFile(handle.0, FILE_MAP_READ | FILE_MAP_WRITE, 0, 0, size) };
     if view_address.Value.is_null() {
-        let _ = unsafe { CloseHandle(handle) };
-        return Err(MemoryMapError::MapView);
+        return Err(MemoryMapError::MapView); // The handle is closed by the guard.
     }
 
-    Ok((handle, view_address))
+    handle.release();
+    Ok(view_address)
 }
 
+/// Returns `true` if a named shared memory of `name` currently exists. (i.e. some process still holds a handle to it)
+///
+/// The handle opened for the check is closed immediately.
+pub fn exists(name: &HSTRING) -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Memory::{OpenFileMappingW, FILE_MAP_READ};
+
+    match unsafe { OpenFileMappingW(FILE_MAP_READ.0, false, name) } {
+        Ok(handle) => {
+            let _ = unsafe { CloseHandle(handle) };
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Options of the named shared memory. (See [`SharedRwLock::new_with_options`](super::SharedRwLock::new_with_options))
+///
+/// The default is the session-local namespace with the default security descriptor, same as [`SharedRwLock::new`](super::SharedRwLock::new).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MappingOptions {
+    /// Prefixes the name with `Global\` to share the memory across sessions. (e.g. with a service)
+    ///
+    /// Creating a global mapping requires `SeCreateGlobalPrivilege`(held by administrators and services),
+    /// but opening an existing one does not.
+    pub global_namespace: bool,
+
+    /// Creates the memory with a NULL DACL, which grants access to all users.
+    ///
+    /// Without this, only the creator's account(and administrators) can open it.
+    /// NOTE: This does not change the integrity label, so lower integrity processes still cannot write to it.
+    pub allow_all_users: bool,
+}
+
+impl MappingOptions {
+    /// Returns the name actually used for the mapping, i.e. `Global\{name}` if [`Self::global_namespace`] is set.
+    pub fn mapped_name(&self, name: &HSTRING) -> HSTRING {
+        if self.global_namespace {
+            HSTRING::from(format!("Global\\{name}"))
+        } else {
+            name.clone()
+        }
+    }
+}
+
+/// Signature of [`create`] and [`create_large_pages`].
+pub type CreateFn = fn(
+    &HSTRING,
+    usize,
+    &MappingOptions,
+) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS, bool), MemoryMapError>;
+
+/// Creates a named shared memory and maps its view.
+///
+/// Returns `(handle, view, already_existed)`.
+///
+/// `CreateFileMappingW` does not fail even if the mapping of the same name already exists,
+/// but returns its handle(`GetLastError() == ERROR_ALREADY_EXISTS`).
+/// In that case, `already_existed` is `true`, meaning that another process(or thread) won the creation race
+/// and this caller must not initialize the memory.
 pub fn create(
     name: &windows::core::HSTRING,
     size: usize,
-) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS), MemoryMapError> {
-    use windows::Win32::Foundation::CloseHandle;
-    use windows::Win32::Foundation::INVALID_HANDLE_VALUE;
+    options: &MappingOptions,
+) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS, bool), MemoryMapError> {
+    use windows::Win32::System::Memory::{FILE_MAP_READ, FILE_MAP_WRITE, PAGE_READWRITE};
+
+    create_with(
+        name,
+        size,
+        options,
+        PAGE_READWRITE,
+        FILE_MAP_READ | FILE_MAP_WRITE,
+    )
+}
+
+/// Same as [`create`], but backs the memory with large pages to reduce TLB misses on the read path
+/// over a large table.
+///
+/// The size is rounded up to the large-page granularity(`GetLargePageMinimum`).
+///
+/// Large pages require `SeLockMemoryPrivilege` to be held and enabled in the process token.
+/// If large pages are unsupported or the privilege is not held, this falls back to [`create`] with normal pages.
+pub fn create_large_pages(
+    name: &windows::core::HSTRING,
+    size: usize,
+    options: &MappingOptions,
+) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS, bool), MemoryMapError> {
     use windows::Win32::System::Memory::{
-        CreateFileMappingW, MapViewOfFile, FILE_MAP_READ, FILE_MAP_WRITE, PAGE_READWRITE,
+        GetLargePageMinimum, FILE_MAP_LARGE_PAGES, FILE_MAP_READ, FILE_MAP_WRITE, PAGE_READWRITE,
+        SEC_COMMIT, SEC_LARGE_PAGES,
     };
 
+    // GetLargePageMinimum: https://learn.microsoft.com/windows/win32/api/memoryapi/nf-memoryapi-getlargepageminimum
+    let large_page_size = unsafe { GetLargePageMinimum() };
+    if large_page_size == 0 {
+        return create(name, size, options); // Large pages are not supported by the processor.
+    }
+    let Some(rounded_size) = size.div_ceil(large_page_size).checked_mul(large_page_size) else {
+        return create(name, size, options);
+    };
+
+    create_with(
+        name,
+        rounded_size,
+        options,
+        PAGE_READWRITE | SEC_COMMIT | SEC_LARGE_PAGES,
+        FILE_MAP_READ | FILE_MAP_WRITE | FILE_MAP_LARGE_PAGES,
+    )
+    .or_else(|_err| {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Large pages are unavailable, falling back to normal pages: {_err}");
+        create(name, size, options)
+    })
+}
+
+fn create_with(
+    name: &windows::core::HSTRING,
+    size: usize,
+    options: &MappingOptions,
+    protect: windows::Win32::System::Memory::PAGE_PROTECTION_FLAGS,
+    access: windows::Win32::System::Memory::FILE_MAP,
+) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS, bool), MemoryMapError> {
+    use core::mem::size_of;
+    use windows::Win32::Foundation::{GetLastError, ERROR_ALREADY_EXISTS, INVALID_HANDLE_VALUE};
+    use windows::Win32::Security::{
+        InitializeSecurityDescriptor, SetSecurityDescriptorDacl, PSECURITY_DESCRIPTOR,
+        SECURITY_ATTRIBUTES, SECURITY_DESCRIPTOR,
+    };
+    use windows::Win32::System::Memory::{CreateFileMappingW, MapViewOfFile};
+    use windows::Win32::System::SystemServices::SECURITY_DESCRIPTOR_REVISION;
+
     // CreateFileMappingW: https://learn.microsoft.com/windows/win32/api/memoryapi/nf-memoryapi-createfilemappingw
+    // (A page file backed mapping of size `0` fails with an obscure `ERROR_INVALID_PARAMETER`.)
+    if size == 0 {
+        return Err(MemoryMapError::ZeroSize);
+    }
+
+    // Both must outlive `CreateFileMappingW`, which copies the descriptor into the kernel object.
+    let mut descriptor = SECURITY_DESCRIPTOR::default();
+    let mut attributes = SECURITY_ATTRIBUTES::default();
+    let security = if options.allow_all_users {
+        let descriptor_ptr = PSECURITY_DESCRIPTOR((&raw mut descriptor).cast());
+        unsafe {
+            InitializeSecurityDescriptor(descriptor_ptr, SECURITY_DESCRIPTOR_REVISION)
+                .map_err(|e| MemoryMapError::CreateMapping { source: e })?;
+            // NULL DACL: no access control at all.
+            SetSecurityDescriptorDacl(descriptor_ptr, true, None, false)
+                .map_err(|e| MemoryMapError::CreateMapping { source: e })?;
+        }
+        attributes.nLength = size_of::<SECURITY_ATTRIBUTES>() as u32;
+        attributes.lpSecurityDescriptor = descriptor_ptr.0;
+        Some(&raw const attributes)
+    } else {
+        None
+    };
 
     let handle = unsafe {
         let (max, min) = ((size >> 32) as u32, size as u32); // Split to high, low
-        CreateFileMappingW(INVALID_HANDLE_VALUE, None, PAGE_READWRITE, max, min, name)
+        CreateFileMappingW(INVALID_HANDLE_VALUE, security, protect, max, min, name)
     }
     .map_err(|e| MemoryMapError::CreateMapping { source: e })?;
+    // NOTE: This must be checked immediately after `CreateFileMappingW` before another API overwrites it.
+    let already_existed = unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+    let handle = HandleGuard(handle);
 
     let view = {
-        let view_address =
-            unsafe { MapViewOfFile(handle, FILE_MAP_READ | FILE_MAP_WRITE, 0, 0, size) };
+        let view_address = unsafe { MapViewOfFile(handle.0, access, 0, 0, size) };
 
         if view_address.Value.is_null() {
-            // CloseHandle: https://learn.microsoft.com/windows/win32/api/handleapi/nf-handleapi-closehandle
-            let _ = unsafe { CloseHandle(handle) };
-            return Err(MemoryMapError::MapView);
+            return Err(MemoryMapError::MapView); // The handle is closed by the guard.
         }
 
         view_address
     };
 
-    Ok((handle, view))
+    Ok((handle.release(), view, already_existed))
 }
 
+/// Unmaps the view and closes the handle.
+///
+/// The handle is closed even if unmapping fails, so that neither leaks. The first error is returned.
 pub fn close(handle: HANDLE, view: *mut core::ffi::c_void) -> Result<(), MemoryMapError> {
     use windows::Win32::Foundation::CloseHandle;
     use windows::Win32::System::Memory::UnmapViewOfFile;
 
     let view = MEMORY_MAPPED_VIEW_ADDRESS { Value: view };
 
-    // view is NonNull
-    unsafe { UnmapViewOfFile(view) }.map_err(|e| MemoryMapError::UnmapView { source: e })?;
+    let unmapped =
+        unsafe { UnmapViewOfFile(view) }.map_err(|e| MemoryMapError::UnmapView { source: e });
+    let closed =
+        unsafe { CloseHandle(handle) }.map_err(|e| MemoryMapError::CloseHandle { source: e });
 
-    unsafe { CloseHandle(handle) }.map_err(|e| MemoryMapError::CloseHandle { source: e })?;
+    unmapped.and(closed)
+}
+
+/// Scope guard that closes the mapping handle on drop unless released.
+///
+/// This makes every early return after a successful `OpenFileMappingW`/`CreateFileMappingW` close the handle.
+pub struct HandleGuard(pub HANDLE);
+
+impl HandleGuard {
+    /// Disarms the guard and returns the handle.
+    #[allow(clippy::mem_forget)]
+    pub fn release(self) -> HANDLE {
+        let handle = self.0;
+        core::mem::forget(self);
+        handle
+    }
+}
 
-    Ok(())
+impl Drop for HandleGuard {
+    fn drop(&mut self) {
+        // CloseHandle: https://learn.microsoft.com/windows/win32/api/handleapi/nf-handleapi-closehandle
+        let _ = unsafe { windows::Win32::Foundation::CloseHandle(self.0) };
+    }
 }
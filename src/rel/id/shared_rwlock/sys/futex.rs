@@ -137,11 +137,21 @@ pub fn futex_wait<W: Waitable>(futex: &W::Futex, expected: W, timeout: Option<Du
     wait_on_address(futex, expected, timeout) || c::get_last_error() != c::TIMEOUT
 }
 
+/// Wakes up one thread waiting on `futex`.
+///
+/// Returns whether a thread was actually woken, but that is unknowable on Windows
+/// (`WakeByAddressSingle` returns nothing), so this always returns `false`, same as std.
+///
+/// `false` only means "a waiter may not have been woken". Callers must treat it as such:
+/// `RwLock::wake_writer_or_readers` then also wakes the readers, which costs spurious wakeups but never misses one.
 pub fn futex_wake<T: Futexable>(futex: &T) -> bool {
     wake_by_address_single(futex);
     false
 }
 
+/// Wakes up all threads waiting on `futex`.
+///
+/// Unlike Linux `FUTEX_WAKE`, the number of woken threads is unknowable on Windows, so nothing is returned.
 pub fn futex_wake_all<T: Futexable>(futex: &T) {
     wake_by_address_all(futex);
 }
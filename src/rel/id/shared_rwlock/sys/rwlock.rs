@@ -42,6 +42,7 @@ const READ_LOCKED: Primitive = 1;
 const MASK: Primitive = (1 << 30) - 1;
 const WRITE_LOCKED: Primitive = MASK;
 const DOWNGRADE: Primitive = READ_LOCKED.wrapping_sub(WRITE_LOCKED); // READ_LOCKED - WRITE_LOCKED
+const UPGRADE: Primitive = WRITE_LOCKED - READ_LOCKED;
 const MAX_READERS: Primitive = MASK - 1;
 const READERS_WAITING: Primitive = 1 << 30;
 const WRITERS_WAITING: Primitive = 1 << 31;
@@ -255,6 +256,23 @@ impl RwLock {
         }
     }
 
+    /// Atomically turns the single read lock held by the caller into a write lock.
+    ///
+    /// Returns `false` without changing anything if other readers also hold the lock.
+    /// The waiting bits are kept, so waiters are woken up by the later `write_unlock`.
+    ///
+    /// # Safety
+    ///
+    /// The `RwLock` must be read-locked by the caller in order to call this.
+    #[inline]
+    pub unsafe fn try_upgrade(&self) -> bool {
+        self.state
+            .fetch_update(Acquire, Relaxed, |s| {
+                (s & MASK == READ_LOCKED).then(|| s + UPGRADE)
+            })
+            .is_ok()
+    }
+
     #[cold]
     fn write_contended(&self) {
         let mut state = self.spin_write();
@@ -381,8 +399,8 @@ impl RwLock {
     fn wake_writer(&self) -> bool {
         self.writer_notify.fetch_add(1, Release);
         futex_wake(&self.writer_notify)
-        // Note that FreeBSD and DragonFlyBSD don't tell us whether they woke
-        // up any threads or not, and always return `false` here. That still
+        // Note that Windows(like FreeBSD and DragonFlyBSD) doesn't tell us whether it woke
+        // up any threads or not, so this always returns `false`. That still
         // results in correct behavior: it just means readers get woken up as
         // well in case both readers and writers were waiting.
     }
@@ -1,4 +1,7 @@
-use crate::rel::id::shared_rwlock::SharedRwLock;
+use crate::rel::id::shared_rwlock::{
+    shared_mem_exists, MappedRwLockWriteGuard, MappingOptions, MemoryMapError, PoisonError,
+    RwLockReadGuard, RwLockWriteGuard, SharedRwLock,
+};
 use std::sync::OnceLock;
 use std::thread;
 use windows::core::h;
@@ -10,7 +13,7 @@ type Primitive = usize;
 static GLOBAL_SHARED_MEM: OnceLock<SharedRwLock<Primitive>> = OnceLock::new();
 
 fn get_shared_memory() -> &'static SharedRwLock<Primitive> {
-    GLOBAL_SHARED_MEM.get_or_init(|| SharedRwLock::new(h!("GlobalTest"), 1).unwrap().0)
+    GLOBAL_SHARED_MEM.get_or_init(|| SharedRwLock::new(h!("GlobalTest"), 1).unwrap().into_inner())
 }
 
 #[test]
@@ -44,3 +47,416 @@ fn test_shared_memory_rwlock() {
 
     assert_eq!(shared_mem.read().unwrap()[0], THREAD_COUNT);
 }
+
+#[test]
+fn test_new_checked_layout_mismatch() {
+    let created = SharedRwLock::<u64>::new_checked(h!("LayoutCheckTest"), 4).unwrap();
+    assert!(created.is_created());
+
+    let opened = SharedRwLock::<u64>::new_checked(h!("LayoutCheckTest"), 4).unwrap();
+    assert!(!opened.is_created());
+
+    let err = SharedRwLock::<u32>::new_checked(h!("LayoutCheckTest"), 8).unwrap_err();
+    assert_eq!(
+        err,
+        MemoryMapError::LayoutMismatch {
+            expected_size: 4,
+            expected_align: 4,
+            actual_size: 8,
+            actual_align: 8,
+        }
+    );
+}
+
+#[test]
+fn test_new_checked_waits_for_layout_stamp() {
+    use core::sync::atomic::Ordering;
+
+    let created = SharedRwLock::<u64>::new_checked(h!("LayoutStampWaitTest"), 4)
+        .unwrap()
+        .into_inner();
+    // Pretend that the creator has not stamped the layout yet.
+    created.shared().elem_size.store(0, Ordering::Release);
+
+    thread::scope(|s| {
+        let opener = s.spawn(|| SharedRwLock::<u64>::new_checked(h!("LayoutStampWaitTest"), 4));
+        thread::sleep(core::time::Duration::from_millis(50));
+        created.shared().elem_size.store(8, Ordering::Release);
+
+        let opened = opener.join().unwrap().unwrap();
+        assert!(!opened.is_created());
+    });
+}
+
+#[test]
+fn test_mapped_write_guard_drop_does_not_poison() {
+    let lock = SharedRwLock::<u64>::new(h!("MappedWriteGuardDropTest"), 1)
+        .unwrap()
+        .into_inner();
+
+    {
+        let guard = lock.write().unwrap();
+        let mut mapped = RwLockWriteGuard::map(guard, |first| first);
+        mapped[0] = 1;
+        let mut mapped = MappedRwLockWriteGuard::map(mapped, |first| first);
+        mapped[0] += 1;
+    }
+
+    assert!(!lock.is_poisoned());
+    assert_eq!(lock.read().unwrap()[0], 2);
+}
+
+#[test]
+fn test_mapped_write_guard_panic_poisons() {
+    let lock = SharedRwLock::<u64>::new(h!("MappedWriteGuardPanicTest"), 1)
+        .unwrap()
+        .into_inner();
+
+    // Panic while holding the mapped write guard.
+    thread::scope(|s| {
+        let result = s
+            .spawn(|| {
+                let guard = lock.write().unwrap();
+                let mapped = RwLockWriteGuard::map(guard, |first| first);
+                let _mapped = MappedRwLockWriteGuard::map(mapped, |first| first);
+                panic!("poison the lock");
+            })
+            .join();
+        assert!(result.is_err());
+    });
+    assert!(lock.is_poisoned());
+
+    // Recover, overwrite with a known-good value and unpoison.
+    {
+        let guard = lock.write().unwrap_or_else(PoisonError::into_inner);
+        let mut mapped = RwLockWriteGuard::map(guard, |first| first);
+        mapped[0] = 42;
+        MappedRwLockWriteGuard::unpoison(&mapped);
+    }
+    assert!(!lock.is_poisoned());
+    assert_eq!(lock.read().unwrap()[0], 42);
+}
+
+#[test]
+fn test_write_recover() {
+    let lock = SharedRwLock::<u64>::new(h!("WriteRecoverTest"), 1)
+        .unwrap()
+        .into_inner();
+
+    thread::scope(|s| {
+        let _ = s
+            .spawn(|| {
+                let _guard = lock.write().unwrap();
+                panic!("poison the lock");
+            })
+            .join();
+    });
+    assert!(lock.is_poisoned());
+
+    lock.write_recover()[0] = 42;
+    assert!(!lock.is_poisoned());
+    assert_eq!(lock.read().unwrap()[0], 42);
+}
+
+#[test]
+fn test_mapped_read_guard_panic_does_not_poison() {
+    let lock = SharedRwLock::<u64>::new(h!("MappedReadGuardPanicTest"), 1)
+        .unwrap()
+        .into_inner();
+
+    thread::scope(|s| {
+        let result = s
+            .spawn(|| {
+                let guard = lock.read().unwrap();
+                let _mapped = RwLockReadGuard::map(guard, |first| first);
+                panic!("panic while reading");
+            })
+            .join();
+        assert!(result.is_err());
+    });
+    assert!(!lock.is_poisoned());
+}
+
+#[test]
+fn test_mapping_size() {
+    const SIZE: Option<usize> = SharedRwLock::<u64>::mapping_size(3);
+    assert_eq!(SIZE, Some(64 + 24));
+    assert_eq!(SharedRwLock::<u64>::mapping_size(0), Some(64));
+    assert_eq!(SharedRwLock::<u64>::mapping_size(usize::MAX / 8), None);
+    assert!(matches!(
+        SharedRwLock::<u64>::new(h!("MappingSizeOverflowTest"), usize::MAX / 8),
+        Err(MemoryMapError::SizeOverflow { len, elem_size: 8 }) if len == usize::MAX / 8
+    ));
+}
+
+#[test]
+fn test_new_zero_len() {
+    assert_eq!(
+        SharedRwLock::<u64>::new(h!("ZeroLenTest"), 0).map(|_| ()),
+        Err(MemoryMapError::ZeroSize)
+    );
+    assert_eq!(
+        super::shared_mem::create(h!("ZeroSizeTest"), 0, &MappingOptions::default()).map(|_| ()),
+        Err(MemoryMapError::ZeroSize)
+    );
+}
+
+#[test]
+fn test_new_with_options() {
+    let options = MappingOptions {
+        global_namespace: false,
+        allow_all_users: true,
+    };
+    let lock = SharedRwLock::<u64>::new_with_options(h!("AllowAllUsersTest"), 2, options)
+        .unwrap()
+        .into_inner();
+    assert_eq!(lock.shared_id(), h!("AllowAllUsersTest"));
+    lock.write().unwrap()[1] = 42;
+
+    // Opened by the plain name since it is in the local namespace.
+    let opened = SharedRwLock::<u64>::new(h!("AllowAllUsersTest"), 2).unwrap();
+    assert!(!opened.is_created());
+    assert_eq!(opened.into_inner().read().unwrap()[1], 42);
+
+    let global = MappingOptions {
+        global_namespace: true,
+        ..Default::default()
+    };
+    assert_eq!(global.mapped_name(h!("Db")), *h!("Global\\Db"));
+}
+
+#[test]
+fn test_new_large_pages_fallback() {
+    // Without `SeLockMemoryPrivilege`, this falls back to normal pages.
+    let lock = SharedRwLock::<u64>::new_large_pages(h!("LargePagesTest"), 4)
+        .unwrap()
+        .into_inner();
+    lock.write().unwrap()[3] = 42;
+    assert_eq!(lock.read().unwrap()[3], 42);
+}
+
+#[test]
+fn test_created_lock_state_is_initialized() {
+    let init = SharedRwLock::<u32>::new(h!("LockStateInitTest"), 2).unwrap();
+    assert!(init.is_created());
+
+    let lock = init.into_inner();
+    assert!(!lock.is_poisoned());
+    assert!(lock.try_write().is_ok());
+    assert_eq!(*lock.try_read().unwrap(), [0, 0]);
+}
+
+#[test]
+fn test_debug_while_write_locked_on_same_thread() {
+    let lock = SharedRwLock::<u32>::new(h!("DebugReentrancyTest"), 1)
+        .unwrap()
+        .into_inner();
+
+    let mut guard = lock.write().unwrap();
+    guard[0] = 1;
+    let debug = format!("{lock:?}");
+    assert!(debug.contains("data: <locked>"), "{debug}");
+    drop(guard);
+
+    let debug = format!("{lock:?}");
+    assert!(debug.contains("data: [1]"), "{debug}");
+}
+
+#[test]
+fn test_downgrade_under_contention() {
+    static LOCK: OnceLock<SharedRwLock<usize>> = OnceLock::new();
+    let lock = LOCK.get_or_init(|| {
+        SharedRwLock::new(h!("DowngradeTest"), 1)
+            .unwrap()
+            .into_inner()
+    });
+
+    let mut guard = lock.write().unwrap();
+    let readers: Vec<_> = (0..8)
+        .map(|_| thread::spawn(|| LOCK.get().unwrap().read().unwrap()[0]))
+        .collect();
+    guard[0] = 1;
+
+    // Readers waiting on the write lock are woken up by `downgrade` and see the written value.
+    let guard = RwLockWriteGuard::downgrade(guard);
+    assert_eq!(guard[0], 1);
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), 1);
+    }
+
+    // Still read-locked: writers are excluded.
+    assert!(lock.try_write().is_err());
+    drop(guard);
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn test_readers_and_writers_waiting_wake_up() {
+    // `futex_wake` cannot tell whether a writer was woken. Then `write_unlock` with both readers and writers waiting
+    // must fall back to waking the readers too. A missed wakeup on this path hangs this test.
+    const READERS: usize = 8;
+    const WRITERS: usize = 8;
+
+    let lock = SharedRwLock::new(h!("ReadersWritersWaitingTest"), 1)
+        .unwrap()
+        .into_inner();
+
+    for round in 0..50 {
+        let guard = lock.write().unwrap();
+        thread::scope(|s| {
+            for _ in 0..READERS {
+                s.spawn(|| lock.read().unwrap()[0]);
+            }
+            for _ in 0..WRITERS {
+                s.spawn(|| lock.write().unwrap()[0] += 1);
+            }
+            // Let them block on the held write lock.
+            thread::sleep(std::time::Duration::from_millis(1));
+            drop(guard);
+        });
+        assert_eq!(lock.read().unwrap()[0], (round + 1) * WRITERS);
+    }
+}
+
+#[test]
+fn test_try_upgrade() {
+    static LOCK: OnceLock<SharedRwLock<usize>> = OnceLock::new();
+    let lock = LOCK.get_or_init(|| {
+        SharedRwLock::new(h!("TryUpgradeTest"), 1)
+            .unwrap()
+            .into_inner()
+    });
+
+    // Fails while another reader holds the lock.
+    let other = lock.read().unwrap();
+    let Err(guard) = RwLockReadGuard::try_upgrade(lock.read().unwrap()) else {
+        panic!("upgraded while another reader holds the lock");
+    };
+    drop(other);
+
+    // Succeeds as the only reader.
+    let mut guard =
+        RwLockReadGuard::try_upgrade(guard).unwrap_or_else(|_| panic!("upgrade failed"));
+    guard[0] = 1;
+    assert!(lock.try_read().is_err());
+    drop(guard);
+
+    // Concurrent readers race to upgrade and fill the value lazily. It is filled exactly once.
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            thread::spawn(|| {
+                let lock = LOCK.get().unwrap();
+                loop {
+                    let guard = lock.read().unwrap();
+                    if guard[0] != 1 {
+                        return false;
+                    }
+                    if let Ok(mut guard) = RwLockReadGuard::try_upgrade(guard) {
+                        guard[0] = 2;
+                        return true;
+                    }
+                }
+            })
+        })
+        .collect();
+    let filled = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .filter(|&filled| filled)
+        .count();
+    assert_eq!(filled, 1);
+    assert_eq!(lock.read().unwrap()[0], 2);
+}
+
+#[test]
+fn test_shared_mem_exists() {
+    let name = h!("SharedMemExistsTest");
+    assert!(!shared_mem_exists(name));
+
+    let lock = SharedRwLock::<u8>::new(name, 1).unwrap().into_inner();
+    assert!(shared_mem_exists(name));
+
+    // Released when the last handle is closed.
+    drop(lock);
+    assert!(!shared_mem_exists(name));
+}
+
+#[test]
+fn test_from_handle() {
+    use core::ffi::c_void;
+    use windows::Win32::Foundation::{DuplicateHandle, DUPLICATE_SAME_ACCESS, HANDLE};
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let duplicate = |lock: &SharedRwLock<u64>| {
+        let mut handle = HANDLE::default();
+        unsafe {
+            let process = GetCurrentProcess();
+            let source = HANDLE(lock.raw_handle() as *mut c_void);
+            DuplicateHandle(
+                process,
+                source,
+                process,
+                &mut handle,
+                0,
+                false,
+                DUPLICATE_SAME_ACCESS,
+            )
+            .unwrap();
+        }
+        handle
+    };
+
+    let lock = SharedRwLock::<u64>::new(h!("FromHandleTest"), 4)
+        .unwrap()
+        .into_inner();
+    lock.write().unwrap()[3] = 42;
+
+    let from_handle = unsafe { SharedRwLock::<u64>::from_handle(duplicate(&lock), 4) }.unwrap();
+    assert_eq!(from_handle.read().unwrap()[3], 42);
+    assert!(from_handle.shared_id().is_empty());
+
+    // Larger than the mapping.
+    let err = unsafe { SharedRwLock::<u64>::from_handle(duplicate(&lock), 1 << 20) }.unwrap_err();
+    assert_eq!(err, MemoryMapError::MapView);
+    let err = unsafe { SharedRwLock::<u64>::from_handle(duplicate(&lock), 0) }.unwrap_err();
+    assert_eq!(err, MemoryMapError::ZeroSize);
+
+    // The memory outlives the original lock while the owned handle is alive.
+    drop(lock);
+    assert_eq!(from_handle.read().unwrap()[3], 42);
+}
+
+#[test]
+fn test_new_create_race_reports_opened() {
+    use super::shared_mem;
+    use super::SharedMemInit;
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Memory::MEMORY_MAPPED_VIEW_ADDRESS;
+
+    static WINNER: OnceLock<SharedRwLock<u64>> = OnceLock::new();
+
+    /// Simulates another process that creates and initializes the memory between our `open` and `create`.
+    fn racing_create(
+        name: &HSTRING,
+        size: usize,
+        options: &MappingOptions,
+    ) -> Result<(HANDLE, MEMORY_MAPPED_VIEW_ADDRESS, bool), MemoryMapError> {
+        WINNER.get_or_init(|| {
+            let lock = SharedRwLock::new(name, 2).unwrap();
+            assert!(lock.is_created());
+            let lock = lock.into_inner();
+            lock.write().unwrap().copy_from_slice(&[1, 2]);
+            lock
+        });
+        shared_mem::create(name, size, options)
+    }
+
+    let name = h!("CreateRaceTest");
+    let init =
+        SharedRwLock::<u64>::new_with(name, 2, &MappingOptions::default(), racing_create).unwrap();
+    assert!(matches!(init, SharedMemInit::Opened(_)));
+
+    // The winner's data is not re-initialized.
+    assert_eq!(*init.into_inner().read().unwrap(), [1, 2]);
+}
@@ -0,0 +1,107 @@
+//! Symbolication of absolute addresses(e.g. from a crash dump) into address library IDs.
+//!
+//! This is intended for crash-log tooling, so that a bug report can point to `ID + delta`,
+//! which is stable across game builds unlike raw addresses.
+
+use super::id_database::DataBaseError;
+use super::offset_to_id::OffsetToID;
+use crate::rel::module::ModuleState;
+
+/// An absolute address resolved to the address library ID containing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbolicated {
+    /// The absolute address given.
+    pub address: usize,
+    /// The nearest ID at or below the address. `None` if the address is below the module base or the first ID.
+    pub id: Option<u64>,
+    /// `address - base - id_offset`. `0` if `id` is `None`.
+    pub delta: usize,
+}
+
+impl Symbolicated {
+    /// Resolves `address` with the given table and module `base`.
+    pub fn resolve(table: &OffsetToID, base: usize, address: usize) -> Self {
+        let nearest = address
+            .checked_sub(base)
+            .and_then(|offset| table.get_nearest(offset as u64).map(|m| (m, offset)));
+
+        match nearest {
+            Some((mapping, offset)) => Self {
+                address,
+                id: Some(mapping.id),
+                delta: offset - mapping.offset as usize,
+            },
+            None => Self {
+                address,
+                id: None,
+                delta: 0,
+            },
+        }
+    }
+}
+
+/// Resolves each absolute address to the containing address library ID and the delta into it.
+///
+/// The offset-to-id table is built once for all `addresses`.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::symbolicate;
+///
+/// for sym in symbolicate(&[0x7ff6_1234_5678]).unwrap() {
+///     match sym.id {
+///         Some(id) => println!("{:#x}: ID {id} + {:#x}", sym.address, sym.delta),
+///         None => println!("{:#x}: <unknown>", sym.address),
+///     }
+/// }
+/// ```
+///
+/// # Errors
+/// If the module base cannot be retrieved, or the address library could not be loaded.
+pub fn symbolicate(addresses: &[usize]) -> Result<Vec<Symbolicated>, DataBaseError> {
+    let base = ModuleState::base_address()?;
+    let table = OffsetToID::from_global()?;
+
+    Ok(addresses
+        .iter()
+        .map(|&address| Symbolicated::resolve(&table, base, address))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rel::id::Mapping;
+
+    #[test]
+    fn test_symbolicate_resolve() {
+        let table = OffsetToID::from_mappings(vec![
+            Mapping {
+                id: 2,
+                offset: 0x200,
+            },
+            Mapping {
+                id: 1,
+                offset: 0x100,
+            },
+        ]);
+        let base = 0x1000;
+
+        let resolve = |address| Symbolicated::resolve(&table, base, address);
+        assert_eq!(
+            resolve(0x1180),
+            Symbolicated {
+                address: 0x1180,
+                id: Some(1),
+                delta: 0x80,
+            }
+        );
+        assert_eq!(resolve(0x1200).id, Some(2));
+        assert_eq!(resolve(0x1200).delta, 0);
+        assert_eq!(resolve(0x1400).delta, 0x200);
+
+        // Below the first ID, and below the module base.
+        assert_eq!(resolve(0x10ff).id, None);
+        assert_eq!(resolve(0x0fff).id, None);
+    }
+}
@@ -1,17 +1,38 @@
 use crate::rel::id::id_database::DataBaseError;
+use crate::rel::id::ID;
+use crate::rel::module::Runtime;
+use crate::rel::offset::Offset;
+use crate::rel::ResolvableAddress;
 
 /// Represents an ID with a possible VR-specific offset.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// SE/AE are resolved by looking up the address library with [`ID`],
+/// while VR uses the raw [`Offset`] as it is. Types enforce this distinction,
+/// so a VR id cannot be accidentally passed where an offset is expected.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::id::{VariantID, ID};
+/// use commonlibsse_ng::rel::offset::Offset;
+///
+/// let id = VariantID::new(ID::new(10), ID::new(20), Offset::new(0x1000));
+/// let same = VariantID::default()
+///     .with_se(ID::new(10))
+///     .with_ae(ID::new(20))
+///     .with_vr_offset(Offset::new(0x1000));
+/// assert_eq!(id, same);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct VariantID {
-    se_id: u64,
-    ae_id: u64,
-    vr_offset: u64,
+    se_id: ID,
+    ae_id: ID,
+    vr_offset: Offset,
 }
 
 impl VariantID {
     /// Creates a new VariantID instance.
     #[inline]
-    pub const fn new(se_id: u64, ae_id: u64, vr_offset: u64) -> Self {
+    pub const fn new(se_id: ID, ae_id: ID, vr_offset: Offset) -> Self {
         Self {
             se_id,
             ae_id,
@@ -19,44 +40,140 @@ impl VariantID {
         }
     }
 
+    /// Creates a new VariantID from `(Runtime, value)` pairs.
+    ///
+    /// Runtimes not contained in `pairs` are filled with `0`.
+    /// For [`Runtime::Se`]/[`Runtime::Ae`], the value is an [`ID`], and for [`Runtime::Vr`], it is an [`Offset`].
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::{VariantID, ID};
+    /// use commonlibsse_ng::rel::module::Runtime;
+    /// use commonlibsse_ng::rel::offset::Offset;
+    ///
+    /// let id = VariantID::from_pairs(&[(Runtime::Se, 10), (Runtime::Vr, 0x1000)]);
+    /// assert_eq!(id, VariantID::new(ID::new(10), ID::new(0), Offset::new(0x1000)));
+    /// ```
+    pub const fn from_pairs(pairs: &[(Runtime, u64)]) -> Self {
+        let mut this = Self::new(ID::new(0), ID::new(0), Offset::new(0));
+
+        let mut i = 0;
+        while i < pairs.len() {
+            let (runtime, value) = pairs[i];
+            this = match runtime {
+                Runtime::Ae => this.with_ae(ID::new(value)),
+                Runtime::Se => this.with_se(ID::new(value)),
+                Runtime::Vr => this.with_vr_offset(Offset::new(value as usize)),
+            };
+            i += 1;
+        }
+        this
+    }
+
+    /// Sets the ID for Skyrim Special Edition.
+    #[inline]
+    pub const fn with_se(mut self, se_id: ID) -> Self {
+        self.se_id = se_id;
+        self
+    }
+
+    /// Sets the ID for Skyrim Anniversary Edition.
+    #[inline]
+    pub const fn with_ae(mut self, ae_id: ID) -> Self {
+        self.ae_id = ae_id;
+        self
+    }
+
+    /// Sets the offset for Skyrim VR.
+    #[inline]
+    pub const fn with_vr_offset(mut self, vr_offset: Offset) -> Self {
+        self.vr_offset = vr_offset;
+        self
+    }
+
     /// Retrieves the absolute address corresponding to the ID.
     ///
     /// # Errors
     /// Returns an error if the ID cannot be resolved.
     #[inline]
     pub fn address(&self) -> Result<usize, DataBaseError> {
-        let offset = self.offset()?;
-        Ok(if offset == 0 {
-            0
-        } else {
-            Self::base()? + offset
-        })
+        crate::rel::resolve_address(self.offset()?)
     }
 
     /// Retrieves the offset corresponding to the ID.
     ///
+    /// - SE/AE: The offset is looked up from the address library by ID.
+    /// - VR: The VR offset is returned as it is.
+    ///
     /// # Errors
     /// Returns an error if the ID is not found.
     pub fn offset(&self) -> Result<usize, DataBaseError> {
-        use crate::rel::module::{ModuleState, Runtime};
+        use crate::rel::module::ModuleState;
 
-        let runtime = ModuleState::map_or_init(|module| module.runtime)?; // derived Copy
+        let runtime = ModuleState::runtime()?;
 
-        let id = match runtime {
-            Runtime::Ae => self.ae_id,
-            Runtime::Se => self.se_id,
-            Runtime::Vr => self.vr_offset,
-        };
-
-        crate::rel::id::id_database::ID_DATABASE.id_to_offset(id)
+        match runtime {
+            Runtime::Ae => self.ae_id.offset(),
+            Runtime::Se => self.se_id.offset(),
+            Runtime::Vr => self.vr_offset.offset(),
+        }
     }
+}
 
-    /// Retrieves the base address of the module.
+impl ResolvableAddress for VariantID {
+    /// Retrieves the offset corresponding to the ID for the current runtime.
     ///
     /// # Errors
-    /// Returns an error if the module is in an invalid state.
+    /// Returns an error if the ID is not found.
     #[inline]
-    fn base() -> Result<usize, crate::rel::module::ModuleStateError> {
-        crate::rel::module::ModuleState::map_or_init(|module| module.base.as_raw())
+    fn offset(&self) -> Result<usize, DataBaseError> {
+        Self::offset(self)
+    }
+}
+
+impl core::fmt::Display for VariantID {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self {
+            se_id,
+            ae_id,
+            vr_offset,
+        } = self;
+        write!(
+            f,
+            "VariantID(SE: {}, AE: {}, VR offset: {:#x})",
+            se_id.0,
+            ae_id.0,
+            vr_offset.get()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_id_from_pairs() {
+        const VARIANT: VariantID =
+            VariantID::from_pairs(&[(Runtime::Ae, 20), (Runtime::Se, 10), (Runtime::Vr, 0x1000)]);
+        assert_eq!(
+            VARIANT,
+            VariantID::new(ID::new(10), ID::new(20), Offset::new(0x1000))
+        );
+        assert_eq!(VariantID::from_pairs(&[]), VariantID::default());
+        // The last pair wins.
+        assert_eq!(
+            VariantID::from_pairs(&[(Runtime::Se, 1), (Runtime::Se, 2)]),
+            VariantID::default().with_se(ID::new(2))
+        );
+    }
+
+    #[test]
+    fn test_variant_id_display() {
+        let id = VariantID::new(ID::new(10), ID::new(20), Offset::new(0x1000));
+        assert_eq!(
+            id.to_string(),
+            "VariantID(SE: 10, AE: 20, VR offset: 0x1000)"
+        );
     }
 }
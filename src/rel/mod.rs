@@ -1,16 +1,50 @@
 //! REL dir portion of `CommonLibSSE-NG` written by hand.
+//! Without the `std` feature, only the pure value types(`version`, `module::Segment`) are available.
 
+#[cfg(feature = "std")]
+mod cached;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
 pub mod id;
-#[cfg(feature = "win_api")]
+#[cfg(any(feature = "win_api", not(feature = "std")))]
 pub mod module;
+#[cfg(feature = "std")]
 pub mod offset;
+#[cfg(feature = "std")]
 pub mod pattern;
+#[cfg(feature = "std")]
 pub mod relocation;
 pub mod version;
 
+#[cfg(feature = "std")]
+pub use cached::Cached;
+#[cfg(feature = "std")]
+pub use error::Error;
+#[cfg(feature = "std")]
 use id::DataBaseError;
+#[cfg(feature = "std")]
 use module::{ModuleState, ModuleStateError};
 
+/// Computes the absolute address from the module-relative `offset`.
+///
+/// This is the single place where all address types(`Offset`, `VariantOffset`, `ID`, `RelocationID`, `VariantID`)
+/// turn an offset into an address, so that they cannot drift apart.
+///
+/// If the offset is `0`, this returns `0` without touching the module state.
+///
+/// # Errors
+/// Returns an error if the base address is unavailable.
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn resolve_address(offset: usize) -> Result<usize, DataBaseError> {
+    Ok(if offset == 0 {
+        0
+    } else {
+        ModuleState::base_address()? + offset
+    })
+}
+
 /// A trait for resolving an absolute address based on an offset.
 ///
 /// Implementing types must provide an `offset()` method that returns the offset
@@ -22,6 +56,7 @@ use module::{ModuleState, ModuleStateError};
 /// # Errors
 /// - If the offset cannot be resolved, `offset()` returns a `DataBaseError`.
 /// - If the base address cannot be retrieved, `base()` returns a `ModuleStateError`.
+#[cfg(feature = "std")]
 pub trait ResolvableAddress {
     /// Returns the offset associated with this instance.
     ///
@@ -32,18 +67,36 @@ pub trait ResolvableAddress {
     /// Computes the absolute address by adding the offset to the module's base address.
     ///
     /// If the offset is `0`, the function returns `0` as well.
+    /// As in the C++ `REL`, `0` means "not available on this runtime"(e.g. a `VariantOffset` with `0` for VR),
+    /// so that an unavailable address can be passed around and null-checked later.
+    /// Use [`Self::non_null_address`] to reject it up front.
     ///
     /// # Errors
     /// - Returns `DataBaseError` if the offset cannot be determined.
     /// - Returns `ModuleStateError` if the base address is unavailable.
     #[inline]
     fn address(&self) -> Result<usize, DataBaseError> {
-        let offset = self.offset()?;
-        Ok(if offset == 0 {
-            0
-        } else {
-            Self::base()? + offset
-        })
+        resolve_address(self.offset()?)
+    }
+
+    /// Computes the absolute address like [`Self::address`], but rejects the null address.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::id::DataBaseError;
+    /// use commonlibsse_ng::rel::offset::Offset;
+    /// use commonlibsse_ng::rel::ResolvableAddress as _;
+    ///
+    /// // The offset `0` is rejected without touching the module state.
+    /// assert!(matches!(Offset::new(0).non_null_address(), Err(DataBaseError::NullAddress)));
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns `DataBaseError` if the address cannot be resolved.
+    /// - Returns [`DataBaseError::NullAddress`] if the resolved address is null.(i.e. the offset is `0`)
+    #[inline]
+    fn non_null_address(&self) -> Result<core::num::NonZeroUsize, DataBaseError> {
+        core::num::NonZeroUsize::new(self.address()?).ok_or(DataBaseError::NullAddress)
     }
 
     /// Retrieves the base address of the module.
@@ -52,6 +105,41 @@ pub trait ResolvableAddress {
     /// Returns an error if the module is in an invalid state.
     #[inline]
     fn base() -> Result<usize, ModuleStateError> {
-        ModuleState::map_or_init(|module| module.base.as_raw())
+        ModuleState::base_address()
+    }
+
+    /// Resolves the address and reinterprets it as a function pointer `F`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::ID;
+    /// use commonlibsse_ng::rel::ResolvableAddress as _;
+    ///
+    /// type DebugNotification = unsafe extern "C" fn(*const core::ffi::c_char, *const core::ffi::c_char, bool);
+    ///
+    /// let func = unsafe { ID::new(52050).as_fn::<DebugNotification>() }.unwrap();
+    /// unsafe { func(c"Hello".as_ptr(), core::ptr::null(), true) };
+    /// ```
+    ///
+    /// # Errors
+    /// - Returns `DataBaseError` if the address cannot be resolved.
+    /// - Returns [`DataBaseError::NullAddress`] if the resolved address is null.(i.e. the offset is `0`)
+    ///
+    /// # Safety
+    /// - `F` must be a function pointer type(e.g. `unsafe extern "C" fn(i32) -> i32`).
+    ///   Pointer-sized non-function types are not rejected at compile time, so this is the caller's responsibility.
+    /// - The signature and calling convention of `F` must match the function at the resolved address.
+    ///   Calling it with a wrong signature is undefined behavior.
+    #[inline]
+    unsafe fn as_fn<F>(&self) -> Result<F, DataBaseError>
+    where
+        F: Copy,
+    {
+        const { assert!(core::mem::size_of::<F>() == core::mem::size_of::<usize>()) };
+
+        let address = self.non_null_address()?.get();
+
+        // SAFETY: The size is checked above, and the caller guarantees that `F` is a valid function pointer type.
+        Ok(unsafe { core::mem::transmute_copy::<usize, F>(&address) })
     }
 }
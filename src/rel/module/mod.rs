@@ -8,24 +8,46 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 //! Module handling library for Skyrim SE/AE/VR .
+//! Without the `std` feature, only the pure value types([`Segment`], [`SegmentName`]) are available.
 
+#[cfg(feature = "std")]
 mod module_core;
+#[cfg(feature = "std")]
 mod module_handle;
+#[cfg(feature = "std")]
 mod runtime;
 mod segment;
 
+#[cfg(feature = "std")]
 pub use self::module_core::{Module, ModuleInitError};
+#[cfg(feature = "std")]
 pub use self::module_handle::{ModuleHandle, ModuleHandleError};
-pub use self::runtime::Runtime;
-pub use self::segment::{Segment, SegmentName};
+#[cfg(feature = "std")]
+pub use self::runtime::{Runtime, RuntimeParseError};
+pub use self::segment::{Segment, SegmentIndexError, SegmentName};
 
+#[cfg(feature = "std")]
+use crate::rel::version::Version;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
 use std::sync::{LazyLock, RwLock};
 
+#[cfg(feature = "std")]
 static MODULE: LazyLock<RwLock<ModuleState>> = LazyLock::new(|| RwLock::new(ModuleState::init()));
 
+/// Incremented on every [`ModuleState::reset`], [`ModuleState::rebase`] and [`IdDatabase::reload`](crate::rel::id::IdDatabase::reload), so that values derived from the module(e.g. [`crate::rel::Cached`]) can detect it.
+#[cfg(feature = "std")]
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of [`ModuleState::init`] calls, to test that concurrent initialization is not duplicated.
+#[cfg(all(test, feature = "std"))]
+static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
 /// Represents the state of the module.
 ///
 /// This enum implements an API to manage a single global variable of internally managed module (e.g. `SkyrimSE.exe`) information.
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ModuleState {
     /// The module is successfully initialized and active.
@@ -38,9 +60,13 @@ pub enum ModuleState {
     FailedInit(ModuleInitError),
 }
 
+#[cfg(feature = "std")]
 impl ModuleState {
     /// Initialize the module.
     fn init() -> Self {
+        #[cfg(test)]
+        INIT_COUNT.fetch_add(1, Ordering::Relaxed);
+
         match Module::init() {
             Ok(module) => Self::Active(module),
             Err(err) => Self::FailedInit(err),
@@ -124,28 +150,113 @@ impl ModuleState {
             }
         }
 
-        // The fact that it was not `Active` means that it absolutely needs to be initialized.
-        let (ret, module_state) = match Module::init() {
-            Ok(module) => (Ok(f(&module)), Self::Active(module)),
-            Err(err) => {
-                let ret_err = ModuleStateError::FailedInit {
-                    source: err.clone(),
-                };
-                (Err(ret_err), Self::FailedInit(err))
+        // Initialize while holding the write lock, so that only the first of the concurrent callers
+        // parses the module and the others wait for it and reuse the result.
+        let module_state = {
+            let mut guard = MODULE
+                .write()
+                .map_err(|_| ModuleStateError::ModuleLockIsPoisoned)?;
+            if !matches!(*guard, Self::Active(_)) {
+                *guard = Self::init();
             }
+            guard.clone()
         };
 
-        // Delaying lock acquisition to avoid prolonged lock acquisition.
-        MODULE
-            .write()
-            .map(|mut guard| *guard = module_state)
-            .map_err(|_| ModuleStateError::ModuleLockIsPoisoned)?;
+        // Call `f` outside the lock, so that it may use `ModuleState` itself.
+        match module_state {
+            Self::Active(module) => Ok(f(&module)),
+            Self::Cleared => Err(ModuleStateError::ModuleHasBeenCleared),
+            Self::FailedInit(err) => Err(ModuleStateError::FailedInit { source: err }),
+        }
+    }
+
+    /// Returns the version of the module, initializing it if necessary.
+    ///
+    /// This is a shorthand for `ModuleState::map_or_init(|module| module.version.clone())`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::ModuleState;
+    ///
+    /// let version = ModuleState::version().unwrap();
+    /// println!("Running on {version}");
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`Self::map_or_init`].
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn version() -> Result<Version, ModuleStateError> {
+        Self::map_or_init(|module| module.version.clone())
+    }
 
-        ret
+    /// Returns the runtime of the module, initializing it if necessary.
+    ///
+    /// # Errors
+    /// Same as [`Self::map_or_init`].
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn runtime() -> Result<Runtime, ModuleStateError> {
+        Self::map_or_init(|module| module.runtime)
+    }
+
+    /// Returns the base address of the module, initializing it if necessary.
+    ///
+    /// # Errors
+    /// Same as [`Self::map_or_init`].
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn base_address() -> Result<usize, ModuleStateError> {
+        Self::map_or_init(|module| module.base.as_raw())
+    }
+
+    /// Is the current Skyrim runtime the Anniversary Edition (AE)?
+    ///
+    /// # Errors
+    /// Same as [`Self::map_or_init`].
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn is_ae() -> Result<bool, ModuleStateError> {
+        Self::runtime().map(|runtime| runtime.is_ae())
+    }
+
+    /// Is the current Skyrim runtime the Special Edition (SE)?
+    ///
+    /// # Errors
+    /// Same as [`Self::map_or_init`].
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn is_se() -> Result<bool, ModuleStateError> {
+        Self::runtime().map(|runtime| runtime.is_se())
+    }
+
+    /// Is the current Skyrim runtime the VR version?
+    ///
+    /// # Errors
+    /// Same as [`Self::map_or_init`].
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    #[inline]
+    pub fn is_vr() -> Result<bool, ModuleStateError> {
+        Self::runtime().map(|runtime| runtime.is_vr())
     }
 
     /// Clears the module, transitioning it to the `Cleared` state.
     ///
+    /// The address library is not reloaded by this. If the module may change, also call
+    /// [`IdDatabase::reload`](crate::rel::id::IdDatabase::reload) after the reset.
+    ///
     /// # Example
     /// ```
     /// use commonlibsse_ng::rel::module::ModuleState;
@@ -163,13 +274,75 @@ impl ModuleState {
             .write()
             .map_or(Err(ModuleStateError::ModuleLockIsPoisoned), |mut guard| {
                 *guard = Self::Cleared;
+                Self::bump_generation();
                 Ok(())
             })
     }
+
+    /// Re-queries the current base address of the module, and updates the cached one if it has moved.
+    ///
+    /// This is cheaper than [`Self::reset`], which drops the version and segments and parses the module again.
+    /// The segments and the entry point are moved along with the base.
+    ///
+    /// Returns the new base address, so that the addresses derived from the old base can be recomputed.
+    /// (e.g. [`crate::rel::Cached`] detects it by itself.)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::ModuleState;
+    ///
+    /// let base = ModuleState::rebase().unwrap();
+    /// println!("The module is at {base:#x}");
+    /// ```
+    ///
+    /// # Errors
+    /// - If the module state is not [`ModuleState::Active`].
+    /// - If the module is no longer loaded.
+    /// - If the internal lock is poisoned.
+    ///
+    /// # Panics
+    /// This function might panic when called if the lock is already held by the current thread.
+    pub fn rebase() -> Result<usize, ModuleStateError> {
+        let mut guard = MODULE
+            .write()
+            .map_err(|_| ModuleStateError::ModuleLockIsPoisoned)?;
+        let module = match &mut *guard {
+            Self::Active(module) => module,
+            Self::Cleared => return Err(ModuleStateError::ModuleHasBeenCleared),
+            Self::FailedInit(err) => {
+                return Err(ModuleStateError::FailedInit {
+                    source: err.clone(),
+                })
+            }
+        };
+
+        let handle = ModuleHandle::new(&module.filename)
+            .map_err(|source| ModuleStateError::ModuleNotLoaded { source })?;
+        let base = handle.as_raw();
+        if handle != module.base {
+            module.rebase(handle);
+            Self::bump_generation();
+        }
+        Ok(base)
+    }
+
+    /// Returns the number of times the module has been [`reset`](Self::reset) or [`rebase`](Self::rebase)d,
+    /// or the address library has been reloaded.
+    #[inline]
+    pub(crate) fn generation() -> usize {
+        GENERATION.load(Ordering::Acquire)
+    }
+
+    /// Invalidates the values derived from the module(e.g. [`crate::rel::Cached`]), so that they are resolved again.
+    #[inline]
+    pub(crate) fn bump_generation() {
+        GENERATION.fetch_add(1, Ordering::Release);
+    }
 }
 
 /// Type definition for treating an instance of information management as an error when it is in
 /// a state where information cannot be obtained.
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, snafu::Snafu)]
 pub enum ModuleStateError {
     /// The thread that was getting Module's lock panicked.
@@ -178,6 +351,14 @@ pub enum ModuleStateError {
     /// Module has been cleared
     ModuleHasBeenCleared,
 
+    /// Failed to re-query the module handle: {source}
+    ModuleNotLoaded {
+        source: crate::rel::module::ModuleHandleError,
+    },
+
+    /// Expected an address at or above the module base {base:#x}, but got {address:#x}
+    AddressBelowBase { address: usize, base: usize },
+
     /// Module initialization error
     #[snafu(display("Module initialization error: {source}"))]
     FailedInit {
@@ -185,7 +366,7 @@ pub enum ModuleStateError {
     },
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
@@ -193,4 +374,31 @@ mod tests {
     fn test_module_reset() {
         assert!(ModuleState::reset().is_ok());
     }
+
+    #[test]
+    fn test_map_or_init_cold_concurrent() {
+        const THREADS: usize = 32;
+
+        ModuleState::reset().unwrap();
+        let generation = ModuleState::generation();
+        let inits = INIT_COUNT.load(Ordering::Relaxed);
+
+        let barrier = std::sync::Barrier::new(THREADS);
+        let bases: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    s.spawn(|| {
+                        barrier.wait();
+                        ModuleState::map_or_init(|module| module.base).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(bases.windows(2).all(|pair| pair[0] == pair[1]));
+
+        // Resets by other tests running in parallel may legitimately cause re-initialization.
+        let resets = ModuleState::generation() - generation;
+        assert!(INIT_COUNT.load(Ordering::Relaxed) - inits <= 1 + resets);
+    }
 }
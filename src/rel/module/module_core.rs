@@ -10,13 +10,17 @@
 
 use super::module_handle::{ModuleHandle, ModuleHandleError};
 use super::runtime::Runtime;
-use super::segment::{Segment, SegmentName};
+use super::segment::{Segment, SegmentIndexError, SegmentName};
 use crate::rel::version::{get_file_version, FileVersionError, Version};
 use snafu::ResultExt as _;
 use windows::Win32::System::Diagnostics::Debug::{
-    IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_WRITE, IMAGE_SECTION_CHARACTERISTICS,
+    IMAGE_NT_HEADERS64, IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_WRITE, IMAGE_SECTION_CHARACTERISTICS,
 };
 
+/// Runtime version reported by SKSE. (See [`Module::set_skse_query_interface`])
+#[cfg(not(feature = "no_sys"))]
+static SKSE_RUNTIME_VERSION: std::sync::Mutex<Option<Version>> = std::sync::Mutex::new(None);
+
 /// Represents a loaded module in memory.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Module {
@@ -24,14 +28,18 @@ pub struct Module {
     pub filename: windows::core::HSTRING,
     /// File path of the module. (e.g. `"SkyrimSE.exe"`)
     pub file_path: String,
-    /// Memory segments of the module.
-    segments: [Segment; 8],
+    /// Memory segments of the module. (`None` if the module has no such section)
+    segments: [Option<Segment>; 8],
     /// Version information of the module.
     pub version: Version,
     /// Base module handle if available.
     pub base: ModuleHandle,
     /// Runtime type of the module.
     pub runtime: Runtime,
+    /// Absolute address of the entry point. (Cached from NT header)
+    entry_point: usize,
+    /// Size of the loaded image in bytes. (Cached from NT header)
+    image_size: u32,
 }
 
 impl Module {
@@ -56,16 +64,26 @@ impl Module {
         Self::init_inner(filename.clone(), module_handle)
     }
 
+    /// Known names of the Skyrim runtime executables, tried in order by [`Self::init`].
+    ///
+    /// The Steam and GOG builds of SE/AE both ship as `SkyrimSE.exe`, so this lists only the real game executables.
+    /// Launchers(e.g. `SkyrimSELauncher.exe`, `skse64_loader.exe`) are intentionally not listed: they are not the game runtime,
+    /// and the address library does not apply to them. A renamed executable is found by the `SKSE_RUNTIME` environment variable
+    /// or the main executable fallback of [`Self::from_skyrim_with`].
+    pub const RUNTIMES: [&'static windows::core::HSTRING; 2] = [
+        windows::core::h!("SkyrimSE.exe"),
+        windows::core::h!("SkyrimVR.exe"),
+    ];
+
     /// Initializes a new `Module` instance by detecting the currently loaded module.
     ///
-    /// This method attempts to retrieve the module information from the `SKSE_RUNTIME`
-    /// or fallback to a predefined list of runtime binaries(e.g. `SkyrimSE.exe`).
+    /// Same as [`Self::from_skyrim_with`] with [`Self::RUNTIMES`].
     ///
     /// # Examples
     /// ```no_run
     /// use commonlibsse_ng::rel::module::{Module, Runtime};
     ///
-    /// let module = Module::from_skyrim();
+    /// let module = Module::from_skyrim_with(&Module::RUNTIMES);
     /// match module {
     ///     Ok(module) => {
     ///         assert!(!module.filename.is_empty());
@@ -77,57 +95,184 @@ impl Module {
     /// ```
     ///
     /// # Errors
+    /// Same as [`Self::from_skyrim_with`].
+    #[cfg(not(feature = "debug"))]
+    pub fn init() -> Result<Self, ModuleInitError> {
+        Self::from_skyrim_with(&Self::RUNTIMES)
+    }
+
+    /// Initializes a new `Module` instance by detecting the currently loaded module.
+    ///
+    /// The module is searched in the following order, and the first one found is used.
+    /// 1. The module named by the `SKSE_RUNTIME` environment variable.
+    /// 2. Each of `candidates`(e.g. [`Self::RUNTIMES`]), in order.
+    /// 3. The main executable of the current process.
+    ///    (For launch setups where the game executable has a nonstandard name.)
+    ///
+    /// With the `tracing` feature, each attempt is logged.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::Module;
+    /// use windows::core::h;
+    ///
+    /// let module = Module::from_skyrim_with(&[h!("SkyrimSE.exe"), h!("SkyrimSE_custom.exe")]);
+    /// ```
+    ///
+    /// # Errors
     /// An error occurs in the following cases
     /// - If the module handle could not be obtained.
     /// - Module version could not be obtained.
-    #[cfg(not(feature = "debug"))]
-    pub fn init() -> Result<Self, ModuleInitError> {
+    pub fn from_skyrim_with(
+        candidates: &[&windows::core::HSTRING],
+    ) -> Result<Self, ModuleInitError> {
+        let (filename, module_handle) = Self::module_from_skse_runtime()
+            .or_else(|| Self::module_from_candidates(candidates))
+            .or_else(Self::module_from_process_exe)
+            .ok_or(ModuleInitError::ModuleNameAndHandleNotFound)?;
+
+        Self::init_inner(filename, module_handle)
+    }
+
+    /// Initializes a new `Module` instance from a raw module base address and the file path of that module.
+    ///
+    /// This is for the case where only the address is known(e.g. handed by an external injector),
+    /// and the module cannot be looked up by name.
+    /// The NT header and segments are parsed from `base`, and the version is read from `file_path`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::Module;
+    ///
+    /// let base = 0x1_4000_0000; // Handed by another tool.
+    /// let module = unsafe { Module::from_base(base, r"D:\Games\Skyrim Special Edition\SkyrimSE.exe") };
+    /// ```
+    ///
+    /// # Errors
+    /// - If `base` is `0`.
+    /// - If the NT header could not be parsed from `base`.
+    /// - Module version could not be obtained from `file_path`.
+    ///
+    /// # Safety
+    /// `base` must be the base address of a module mapped into the current process, and it must stay mapped
+    /// as long as the returned `Module` is used. (See [`ModuleHandle::from_raw`])
+    pub unsafe fn from_base(base: usize, file_path: &str) -> Result<Self, ModuleInitError> {
+        // SAFETY: The caller guarantees that `base` is a mapped module.
+        let module_handle = unsafe { ModuleHandle::from_raw(base) }
+            .map_err(|_| ModuleInitError::InvalidBaseAddress { base })?;
+
+        Self::init_inner(windows::core::HSTRING::from(file_path), module_handle)
+    }
+
+    /// Gets the handle of the module(i.e. the SKSE plugin dll) this crate is linked into.
+    ///
+    /// Unlike [`Self::base`], which is the game module used for address resolution,
+    /// this is the plugin's own module. (e.g. to load bundled resources, or read its own version info)
+    /// The handle is non-owning. (The reference count is unchanged.)
+    ///
+    /// # Errors
+    /// If the module containing this function could not be found.
+    pub fn current_plugin() -> Result<ModuleHandle, ModuleHandleError> {
+        // Any address inside this crate belongs to the plugin since it is statically linked.
+        ModuleHandle::from_address(Self::current_plugin as usize)
+    }
+
+    /// Records the runtime version reported by the SKSE query interface as a fallback of the version detection.
+    ///
+    /// The version is normally read from the version resource of the executable, but it is absent in some
+    /// stripped or repacked executables, and then the module initialization fails even though SKSE itself has loaded fine.
+    /// Call this at the beginning of `SKSEPlugin_Load`(or `SKSEPlugin_Query`), before any address is resolved.
+    ///
+    /// If the module has already failed to initialize, it is retried by [`ModuleState::map_or_init`](super::ModuleState::map_or_init).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::Module;
+    /// use commonlibsse_ng::sys::root::SKSE;
+    ///
+    /// #[no_mangle]
+    /// pub extern "C" fn SKSEPlugin_Load(skse: *const SKSE::LoadInterface) -> bool {
+    ///     // `LoadInterface` derives from `QueryInterface`.
+    ///     if let Some(query) = unsafe { skse.cast::<SKSE::QueryInterface>().as_ref() } {
+    ///         Module::set_skse_query_interface(query);
+    ///     }
+    ///     true
+    /// }
+    /// ```
+    #[cfg(not(feature = "no_sys"))]
+    pub fn set_skse_query_interface(query: &crate::sys::root::SKSE::QueryInterface) {
+        // SAFETY: `query` is the interface passed by SKSE, which is valid during the call.
+        let version = unsafe { query.RuntimeVersion() };
+        *SKSE_RUNTIME_VERSION
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(version);
+    }
+
+    /// Returns the runtime version recorded by [`Self::set_skse_query_interface`].
+    #[cfg(not(feature = "no_sys"))]
+    fn skse_runtime_version() -> Option<Version> {
+        SKSE_RUNTIME_VERSION
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    #[cfg(feature = "no_sys")]
+    const fn skse_runtime_version() -> Option<Version> {
+        None
+    }
+
+    fn module_from_skse_runtime() -> Option<(windows::core::HSTRING, ModuleHandle)> {
         use windows::core::{h, HSTRING};
         use windows::Win32::System::Environment::GetEnvironmentVariableW;
 
-        #[inline]
-        fn get_module_name_from_skse() -> Option<(HSTRING, ModuleHandle)> {
-            let mut filename = vec![0; windows::Win32::Foundation::MAX_PATH as usize];
-            let filename_len =
-                unsafe { GetEnvironmentVariableW(h!("SKSE_RUNTIME"), Some(&mut filename)) }
-                    as usize;
+        let mut filename = vec![0; windows::Win32::Foundation::MAX_PATH as usize];
+        let filename_len =
+            unsafe { GetEnvironmentVariableW(h!("SKSE_RUNTIME"), Some(&mut filename)) } as usize;
 
-            let is_failed = filename_len != filename.len() - 1 || filename_len == 0;
-            if is_failed {
-                return None;
-            }
-
-            let filename = HSTRING::from_wide(&filename);
-            let new_handle = ModuleHandle::new(&filename).ok()?;
-            Some((filename, new_handle))
+        // 0: not set, >= buffer length: truncated(returns the required size)
+        if filename_len == 0 || filename_len >= filename.len() {
+            #[cfg(feature = "tracing")]
+            tracing::info!("Failed to read the `SKSE_RUNTIME` environment variable. Trying to get it from Runtime exe (e.g. `SkyrimSE.exe`) instead...");
+            return None;
         }
 
-        #[inline]
-        fn get_module_handle_from_runtime() -> Option<(HSTRING, ModuleHandle)> {
+        let filename = HSTRING::from_wide(&filename[..filename_len]);
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Trying `SKSE_RUNTIME` module: {filename}");
+        let new_handle = ModuleHandle::new(&filename).ok()?;
+        Some((filename, new_handle))
+    }
+
+    fn module_from_candidates(
+        candidates: &[&windows::core::HSTRING],
+    ) -> Option<(windows::core::HSTRING, ModuleHandle)> {
+        candidates.iter().find_map(|&runtime_name| {
             #[cfg(feature = "tracing")]
-            tracing::info!("Failed to read the `SKSE_RUNTIME` environment variable. Trying to get it from Runtime exe (e.g. `SkyrimSE.exe`) instead...");
+            tracing::debug!("Trying runtime module: {runtime_name}");
+            let new_handle = ModuleHandle::new(runtime_name).ok()?;
+            Some((runtime_name.clone(), new_handle))
+        })
+    }
 
-            const RUNTIMES: [&'static windows::core::HSTRING; 2] = [
-                windows::core::h!("SkyrimSE.exe"),
-                windows::core::h!("SkyrimVR.exe"),
-            ];
-
-            let mut ret = None;
-            for runtime_name in RUNTIMES {
-                if let Ok(new_handle) = ModuleHandle::new(runtime_name) {
-                    ret = Some((runtime_name.clone(), new_handle));
-                    break;
-                }
-            }
+    fn module_from_process_exe() -> Option<(windows::core::HSTRING, ModuleHandle)> {
+        use windows::core::HSTRING;
+        use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
 
-            ret
+        let mut path = vec![0; windows::Win32::Foundation::MAX_PATH as usize];
+        let path_len = unsafe { GetModuleFileNameW(None, &mut path) } as usize;
+        // 0: failed, buffer length: truncated
+        if path_len == 0 || path_len >= path.len() {
+            return None;
         }
 
-        let (filename, module_handle) = get_module_name_from_skse()
-            .or_else(get_module_handle_from_runtime)
-            .ok_or(ModuleInitError::ModuleNameAndHandleNotFound)?;
-
-        Self::init_inner(filename, module_handle)
+        let path = HSTRING::from_wide(&path[..path_len]);
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            "No known runtime module is loaded. Falling back to the main executable: {path}"
+        );
+        let new_handle = ModuleHandle::new(&path).ok()?;
+        Some((path, new_handle))
     }
 
     #[inline]
@@ -135,7 +280,18 @@ impl Module {
         filename: windows::core::HSTRING,
         module_handle: ModuleHandle,
     ) -> Result<Self, ModuleInitError> {
-        let segments = Self::load_segments(&module_handle).context(SegmentLoadFailedSnafu)?;
+        // Walk the NT header only once here and cache what we need.
+        let (segments, entry_point, image_size) = {
+            let nt_header = module_handle
+                .try_as_nt_header()
+                .context(SegmentLoadFailedSnafu)?;
+            let optional_header = &nt_header.OptionalHeader;
+            (
+                Self::load_segments(&module_handle, nt_header),
+                module_handle.as_raw() + optional_header.AddressOfEntryPoint as usize,
+                optional_header.SizeOfImage,
+            )
+        };
         let (version, runtime) = Self::load_version(&filename).context(VersionLoadFailedSnafu)?;
         let file_path = filename.to_string();
 
@@ -146,9 +302,39 @@ impl Module {
             version,
             base: module_handle,
             runtime,
+            entry_point,
+            image_size,
         })
     }
 
+    /// Moves the cached addresses(base, segments and entry point) to `base`, keeping the parsed information.
+    pub(crate) fn rebase(&mut self, base: ModuleHandle) {
+        let (old_base, new_base) = (self.base.as_raw(), base.as_raw());
+        for segment in self.segments.iter_mut().flatten() {
+            if segment.proxy_base == old_base {
+                segment.proxy_base = new_base;
+            }
+        }
+        self.entry_point = self.entry_point - old_base + new_base;
+        self.base = base;
+    }
+
+    /// Returns the absolute address of the module's entry point.
+    ///
+    /// This is cached at initialization, so the module memory is not touched again.
+    #[inline]
+    pub const fn entry_point(&self) -> usize {
+        self.entry_point
+    }
+
+    /// Returns the size of the loaded module image in bytes. (`SizeOfImage` of the optional header)
+    ///
+    /// This is cached at initialization, so the module memory is not touched again.
+    #[inline]
+    pub const fn image_size(&self) -> u32 {
+        self.image_size
+    }
+
     /// Gets a specific memory segment by [`SegmentName`].
     ///
     /// # Example
@@ -156,23 +342,86 @@ impl Module {
     /// ```no_run
     /// use commonlibsse_ng::rel::module::{Module, SegmentName};
     ///
-    /// match Module::from_skyrim() {
+    /// match Module::from_skyrim_with(&Module::RUNTIMES) {
     ///     Ok(module) => println!("{:?}", module.segment(SegmentName::Textx)),
     ///     Err(err) => tracing::error!("Failed to initialize module: {err}"),
     /// }
     /// ```
+    ///
+    /// [`SegmentName::Total`] is not a segment, and returns an empty segment. (`Segment::default()`)
+    /// A section absent from the module also returns an empty segment. Use [`Self::try_segment`] to tell them apart.
     #[inline]
     pub const fn segment(&self, name: SegmentName) -> Segment {
-        self.segments[name as usize]
+        match self.try_segment(name) {
+            Some(segment) => segment,
+            None => Segment::const_default(),
+        }
     }
 
+    /// Gets a specific memory segment by [`SegmentName`], or `None` if the module has no such section.
+    ///
+    /// Unlike [`Self::segment`], this distinguishes an absent section from a present but empty one.
+    /// (e.g. `.gfids` is missing in some modules)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::{Module, SegmentName};
+    ///
+    /// let module = Module::from_skyrim_with(&Module::RUNTIMES).unwrap();
+    /// if let Some(gfids) = module.try_segment(SegmentName::Gfids) {
+    ///     println!("{:#x} ({:#x} bytes)", gfids.address, gfids.size);
+    /// }
+    /// assert_eq!(module.try_segment(SegmentName::Total), None);
+    /// ```
     #[inline]
-    fn load_segments(module_handle: &ModuleHandle) -> Result<[Segment; 8], ModuleHandleError> {
-        use windows::Win32::System::Diagnostics::Debug::{
-            IMAGE_NT_HEADERS64, IMAGE_SECTION_HEADER,
-        };
+    pub const fn try_segment(&self, name: SegmentName) -> Option<Segment> {
+        match name {
+            SegmentName::Total => None,
+            name => self.segments[name as usize],
+        }
+    }
+
+    /// Gets a specific memory segment by a numeric index. (e.g. read from a config)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::{Module, SegmentName};
+    ///
+    /// let module = Module::from_skyrim_with(&Module::RUNTIMES).unwrap();
+    /// assert_eq!(module.segment_at(3), Ok(module.segment(SegmentName::Data)));
+    /// assert!(module.segment_at(8).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// If `index` is not less than [`SegmentName::Total`].
+    #[inline]
+    pub fn segment_at(&self, index: usize) -> Result<Segment, SegmentIndexError> {
+        SegmentName::try_from(index).map(|name| self.segment(name))
+    }
+
+    /// Gets all memory segments paired with their names, in order of [`SegmentName::ALL`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::Module;
+    ///
+    /// let module = Module::from_skyrim_with(&Module::RUNTIMES).unwrap();
+    /// for (name, segment) in module.segments() {
+    ///     println!("{name:?}: {:#x} ({:#x} bytes)", segment.address, segment.size);
+    /// }
+    /// ```
+    #[inline]
+    pub fn segments(&self) -> [(SegmentName, Segment); SegmentName::Total as usize] {
+        SegmentName::ALL.map(|name| (name, self.segment(name)))
+    }
+
+    #[inline]
+    fn load_segments(
+        module_handle: &ModuleHandle,
+        nt_header: &IMAGE_NT_HEADERS64,
+    ) -> [Option<Segment>; 8] {
+        use windows::Win32::System::Diagnostics::Debug::IMAGE_SECTION_HEADER;
 
-        let nt_header = module_handle.try_as_nt_header()?;
         let section_header_offset = {
             let optional_header_offset = core::mem::offset_of!(IMAGE_NT_HEADERS64, OptionalHeader);
             optional_header_offset + nt_header.FileHeader.SizeOfOptionalHeader as usize
@@ -180,40 +429,51 @@ impl Module {
 
         let section = ((nt_header as *const _ as usize) + section_header_offset)
             as *const IMAGE_SECTION_HEADER;
-        let section_len = core::cmp::min(
-            nt_header.FileHeader.NumberOfSections,
-            Self::SEGMENTS.len() as u16,
-        );
+        // SAFETY: The section headers follow the optional header of the loaded image.
+        let sections = unsafe {
+            core::slice::from_raw_parts(section, nt_header.FileHeader.NumberOfSections as usize)
+        };
 
-        let mut segments = [Segment::const_default(); 8];
-        for i in 0..section_len {
-            let current_section = unsafe { &*section.add(i as usize) };
+        let mut segments = [None; 8];
+        for current_section in sections {
+            // The name is NUL-padded to 8 bytes.
+            let name_len = current_section
+                .Name
+                .iter()
+                .position(|&byte| byte == 0)
+                .unwrap_or(current_section.Name.len());
+            let section_name = &current_section.Name[..name_len];
 
+            // A zero mask has no characteristic requirement.
             let maybe_found = Self::SEGMENTS.iter().enumerate().find(|(_, elem)| {
-                let maybe_ascii = core::str::from_utf8(&current_section.Name);
-                maybe_ascii.is_ok_and(|section_name| {
-                    elem.0 != section_name
-                        && ((current_section.Characteristics & elem.1)
-                            != IMAGE_SECTION_CHARACTERISTICS(0))
-                })
+                elem.0.as_bytes() == section_name
+                    && (current_section.Characteristics & elem.1) == elem.1
             });
 
             if let Some((idx, _)) = maybe_found {
-                segments[idx] = Segment::new(
+                segments[idx] = Some(Segment::new(
                     module_handle.as_raw(),
                     current_section.VirtualAddress,
                     current_section.SizeOfRawData,
-                );
+                ));
             }
         }
-        Ok(segments)
+        segments
     }
 
     #[inline]
     fn load_version(
         file_path: &windows::core::HSTRING,
     ) -> Result<(Version, Runtime), FileVersionError> {
-        let version = get_file_version(file_path)?;
+        let version = match get_file_version(file_path) {
+            Ok(version) => version,
+            Err(err) => {
+                let version = Self::skse_runtime_version().ok_or(err)?;
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to read the file version of {file_path}. Using the runtime version {version} reported by SKSE instead.");
+                version
+            }
+        };
         let runtime = Runtime::from_version(&version);
         Ok((version, runtime))
     }
@@ -224,6 +484,8 @@ impl Module {
 pub enum ModuleInitError {
     /// SKSE or Skyrim exe does not exist or is not loaded into the current process.
     ModuleNameAndHandleNotFound,
+    /// The module base address {base:#x} is invalid.
+    InvalidBaseAddress { base: usize },
     /// Module handle operation failed during segment search -> {source}
     SegmentLoadFailed {
         source: crate::rel::module::ModuleHandleError,
@@ -250,8 +512,104 @@ mod tests {
                 assert!(!module.file_path.is_empty());
                 assert!(!module.filename.is_empty());
                 assert_eq!(module.runtime, Runtime::Se);
+                assert!(module.image_size() > 0);
+                assert!(module.entry_point() > module.base.as_raw());
             }
             Err(err) => panic!("Failed to initialize module: {err}"),
         }
     }
+
+    #[test]
+    fn test_current_plugin() {
+        // In tests, this crate is linked into the test executable itself.
+        let exe = ModuleHandle::new(windows::core::PCWSTR::null()).unwrap();
+        assert_eq!(Module::current_plugin(), Ok(exe));
+    }
+
+    #[cfg(not(feature = "no_sys"))]
+    #[test]
+    fn test_load_version_skse_fallback() {
+        let missing = windows::core::h!("NotExistRuntime.exe");
+        assert!(Module::load_version(missing).is_err());
+
+        *SKSE_RUNTIME_VERSION.lock().unwrap() = Some(Version::new(1, 6, 1170, 0));
+        let loaded = Module::load_version(missing);
+        *SKSE_RUNTIME_VERSION.lock().unwrap() = None;
+        assert_eq!(loaded, Ok((Version::new(1, 6, 1170, 0), Runtime::Ae)));
+    }
+
+    #[test]
+    fn test_rebase() {
+        let handle = ModuleHandle::new(windows::core::h!("msvcrt.dll")).unwrap();
+        let mut module = unsafe { Module::from_base(handle.as_raw(), "msvcrt.dll") }.unwrap();
+        let entry_point = module.entry_point();
+        let text = module.try_segment(SegmentName::Textx).unwrap();
+        assert_eq!(text.proxy_base, handle.as_raw());
+
+        // Another module that is always loaded, as the destination of the rebase.
+        let new_base = ModuleHandle::new(windows::core::h!("kernel32.dll")).unwrap();
+        assert_ne!(new_base, handle);
+        module.rebase(new_base.clone());
+
+        assert_eq!(module.base, new_base);
+        assert_eq!(
+            module.entry_point(),
+            entry_point - handle.as_raw() + new_base.as_raw()
+        );
+        let rebased = module.try_segment(SegmentName::Textx).unwrap();
+        assert_eq!(rebased.proxy_base, new_base.as_raw());
+        // Only the base moves. The module relative address and size are kept.
+        assert_eq!((rebased.address, rebased.size), (text.address, text.size));
+    }
+
+    #[test]
+    fn test_segments() {
+        let handle = ModuleHandle::new(windows::core::h!("msvcrt.dll")).unwrap();
+        let module = unsafe { Module::from_base(handle.as_raw(), "msvcrt.dll") }.unwrap();
+
+        let segments = module.segments();
+        assert_eq!(segments.map(|(name, _)| name), SegmentName::ALL);
+        for (name, segment) in segments {
+            assert_eq!(segment, module.segment(name));
+        }
+    }
+
+    #[test]
+    fn test_try_segment() {
+        let handle = ModuleHandle::new(windows::core::h!("msvcrt.dll")).unwrap();
+        let module = unsafe { Module::from_base(handle.as_raw(), "msvcrt.dll") }.unwrap();
+
+        for name in SegmentName::ALL {
+            assert_eq!(
+                module.try_segment(name).unwrap_or_default(),
+                module.segment(name)
+            );
+        }
+        for name in [
+            SegmentName::Textx,
+            SegmentName::Rdata,
+            SegmentName::Data,
+            SegmentName::Pdata,
+        ] {
+            let segment = module.try_segment(name);
+            assert!(segment.is_some_and(|segment| segment.size > 0), "{name:?}");
+        }
+        // `.text` is not writable.
+        assert_eq!(module.try_segment(SegmentName::Textw), None);
+        assert_eq!(module.try_segment(SegmentName::Total), None);
+    }
+
+    #[test]
+    fn test_module_from_base() {
+        let handle = ModuleHandle::new(windows::core::h!("msvcrt.dll")).unwrap();
+        let module = unsafe { Module::from_base(handle.as_raw(), "msvcrt.dll") }
+            .unwrap_or_else(|err| panic!("{err}"));
+        assert_eq!(module.base, handle);
+        assert!(module.image_size() > 0);
+
+        assert_eq!(
+            unsafe { Module::from_base(0, "msvcrt.dll") },
+            Err(ModuleInitError::InvalidBaseAddress { base: 0 })
+        );
+    }
 }
@@ -25,6 +25,11 @@
 /// It is not thread-safe as it is because it holds raw_pointer.
 ///
 /// Therefore, we can keep it safe by creating another type that is valid as long as it holds the pointer, with the restriction that it is only invalidated on drop.
+///
+/// # Equality
+/// `PartialEq`/`Hash` compare the module identity, i.e. the base address only.
+/// Handles to the same module obtained through different APIs(`GetModuleHandleW`, `GetModuleHandleExW`, [`Self::from_raw`])
+/// compare equal. Any state added in the future(e.g. ownership) must be excluded from them.
 #[repr(transparent)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ModuleHandle(core::num::NonZeroUsize);
@@ -46,19 +51,25 @@ impl ModuleHandle {
     /// ```
     ///
     /// # Errors
-    /// - Errors if a module is specified that is not loaded by the calling process.
-    /// - If the specified module handle could not be obtained.
+    /// - [`ModuleHandleError::NotLoaded`] if the module is not loaded by the calling process(yet).
+    ///   This is an expected condition during startup, so the caller may retry later.
+    /// - [`ModuleHandleError::HandleNotFound`] if the handle could not be obtained for any other reason.
     pub fn new<H>(module_name: H) -> Result<Self, ModuleHandleError>
     where
         H: windows::core::Param<windows::core::PCWSTR>,
     {
         use core::num::NonZeroUsize;
-        use snafu::ResultExt as _;
+        use windows::Win32::Foundation::ERROR_MOD_NOT_FOUND;
         use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 
         // GetModuleHandleW: https://learn.microsoft.com/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulehandlew
-        let handle =
-            unsafe { GetModuleHandleW(module_name) }.with_context(|_| HandleNotFoundSnafu)?;
+        let handle = unsafe { GetModuleHandleW(module_name) }.map_err(|source| {
+            if source.code() == ERROR_MOD_NOT_FOUND.to_hresult() {
+                ModuleHandleError::NotLoaded
+            } else {
+                ModuleHandleError::HandleNotFound { source }
+            }
+        })?;
 
         // TODO: size of module(However, it incurs the overhead of a function call.
         //       If the assumption is that the search exe is not faked, it may not be necessary to calculate size.)
@@ -86,6 +97,57 @@ impl ModuleHandle {
         Ok(Self(handle))
     }
 
+    /// Wraps a module base address obtained elsewhere(e.g. from an external injector).
+    ///
+    /// The handle is non-owning, as are all `ModuleHandle`s. (It never calls `FreeLibrary`.)
+    ///
+    /// # Errors
+    /// [`ModuleHandleError::NullHandle`] if `base` is `0`.
+    ///
+    /// # Safety
+    /// `base` must be the base address of a module(exe, dll) mapped into the current process,
+    /// and it must stay mapped as long as the handle is used. Header parsing reads memory from it.
+    #[inline]
+    pub const unsafe fn from_raw(base: usize) -> Result<Self, ModuleHandleError> {
+        match core::num::NonZeroUsize::new(base) {
+            Some(base) => Ok(Self(base)),
+            None => Err(ModuleHandleError::NullHandle),
+        }
+    }
+
+    /// Gets the handle of the loaded module that contains `address`.(e.g. a function pointer)
+    ///
+    /// The handle is non-owning. (The reference count is unchanged.)
+    ///
+    /// # Errors
+    /// If `address` is not inside any module loaded by the calling process.
+    pub fn from_address(address: usize) -> Result<Self, ModuleHandleError> {
+        use core::num::NonZeroUsize;
+        use snafu::ResultExt as _;
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::HMODULE;
+        use windows::Win32::System::LibraryLoader::{
+            GetModuleHandleExW, GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+            GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+        };
+
+        let mut handle = HMODULE::default();
+        // GetModuleHandleExW: https://learn.microsoft.com/windows/win32/api/libloaderapi/nf-libloaderapi-getmodulehandleexw
+        // With `FROM_ADDRESS`, `lpModuleName` is treated as an address in the module.
+        unsafe {
+            GetModuleHandleExW(
+                GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS
+                    | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+                PCWSTR(address as *const u16),
+                &mut handle,
+            )
+        }
+        .with_context(|_| HandleNotFoundSnafu)?;
+
+        let handle = NonZeroUsize::new(handle.0 as usize).ok_or(ModuleHandleError::NullHandle)?;
+        Ok(Self(handle))
+    }
+
     /// Returns the raw HMODULE handle.
     #[inline]
     pub const fn to_hmodule(&self) -> windows::Win32::Foundation::HMODULE {
@@ -154,6 +216,9 @@ pub enum ModuleHandleError {
     /// Invalid module handle.
     NullHandle,
 
+    /// The module is not loaded by the calling process.
+    NotLoaded,
+
     /// Failed to get module handle for '{source}'
     HandleNotFound { source: windows::core::Error },
     /// Invalid dos header of this exe/dll. Expected `0x5a4d`, but got `{actual}`
@@ -173,6 +238,53 @@ mod tests {
         assert!(handle.as_raw() > 0);
     }
 
+    #[test]
+    fn test_module_handle_not_loaded() {
+        assert_eq!(
+            ModuleHandle::new(h!("commonlibsse_ng_not_loaded.dll")),
+            Err(ModuleHandleError::NotLoaded)
+        );
+    }
+
+    #[test]
+    fn test_module_handle_identity() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash as _, Hasher as _};
+        use windows::Win32::Foundation::HMODULE;
+        use windows::Win32::System::LibraryLoader::{
+            GetModuleHandleExW, GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+        };
+
+        let by_name = ModuleHandle::new(h!("msvcrt.dll")).unwrap_or_else(|err| panic!("{err}"));
+
+        let mut raw = HMODULE::default();
+        unsafe {
+            GetModuleHandleExW(
+                GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+                h!("msvcrt.dll"),
+                &mut raw,
+            )
+        }
+        .unwrap_or_else(|err| panic!("{err}"));
+        let by_ex = unsafe { ModuleHandle::from_raw(raw.0 as usize) }.unwrap();
+
+        let hash = |handle: &ModuleHandle| {
+            let mut hasher = DefaultHasher::new();
+            handle.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(by_name, by_ex);
+        assert_eq!(hash(&by_name), hash(&by_ex));
+    }
+
+    #[test]
+    fn test_module_handle_from_address() {
+        let by_name = ModuleHandle::new(h!("msvcrt.dll")).unwrap_or_else(|err| panic!("{err}"));
+        let address = by_name.as_raw() + 0x10;
+        assert_eq!(ModuleHandle::from_address(address), Ok(by_name));
+        assert!(ModuleHandle::from_address(1).is_err());
+    }
+
     #[test]
     fn test_module_handle_nt_header() {
         let handle = ModuleHandle::new(h!("msvcrt.dll")).unwrap_or_else(|err| panic!("{err}"));
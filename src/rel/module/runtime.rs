@@ -12,7 +12,10 @@
 use crate::rel::version::Version;
 
 /// Defines Skyrim runtime versions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// # Ordering
+/// Runtimes are ordered by their first release, i.e. `Se < Vr < Ae`, not by their discriminant bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Runtime {
     /// The Skyrim runtime is a post-Anniversary Edition Skyrim SE release (version 1.6.x and later).
     Ae = 1,
@@ -113,6 +116,16 @@ impl Runtime {
         })
     }
 
+    /// Returns the position of this runtime in release order. (SE: 2016, VR: 2017, AE: 2021)
+    #[inline]
+    const fn release_order(self) -> u8 {
+        match self {
+            Self::Se => 0,
+            Self::Vr => 1,
+            Self::Ae => 2,
+        }
+    }
+
     /// Is the current Skyrim runtime the Anniversary Edition (AE)?
     #[inline]
     pub fn is_ae(&self) -> bool {
@@ -132,6 +145,81 @@ impl Runtime {
     }
 }
 
+impl PartialOrd for Runtime {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Runtime {
+    /// Compares by first release. (`Se < Vr < Ae`)
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::module::Runtime;
+    ///
+    /// let mut runtimes = [Runtime::Ae, Runtime::Vr, Runtime::Se];
+    /// runtimes.sort();
+    /// assert_eq!(runtimes, [Runtime::Se, Runtime::Vr, Runtime::Ae]);
+    /// ```
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.release_order().cmp(&other.release_order())
+    }
+}
+
+impl core::fmt::Display for Runtime {
+    /// Writes the abbreviated name of the runtime. (`"AE"`, `"SE"` or `"VR"`)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Ae => "AE",
+            Self::Se => "SE",
+            Self::Vr => "VR",
+        })
+    }
+}
+
+impl core::str::FromStr for Runtime {
+    type Err = RuntimeParseError;
+
+    /// Parses a runtime name case-insensitively.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::module::Runtime;
+    ///
+    /// assert_eq!("AE".parse(), Ok(Runtime::Ae));
+    /// assert_eq!("anniversary".parse(), Ok(Runtime::Ae));
+    /// assert_eq!("Se".parse(), Ok(Runtime::Se));
+    /// assert_eq!("vr".parse(), Ok(Runtime::Vr));
+    /// assert!("LE".parse::<Runtime>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let eq = |name: &str| s.eq_ignore_ascii_case(name);
+
+        if eq("ae") || eq("anniversary") || eq("anniversary edition") {
+            Ok(Self::Ae)
+        } else if eq("se") || eq("special") || eq("special edition") {
+            Ok(Self::Se)
+        } else if eq("vr") || eq("skyrim vr") {
+            Ok(Self::Vr)
+        } else {
+            Err(RuntimeParseError::UnknownRuntime {
+                runtime: s.to_string(),
+            })
+        }
+    }
+}
+
+/// Error type for parsing a [`Runtime`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, snafu::Snafu)]
+pub enum RuntimeParseError {
+    /// Unknown runtime: `{runtime}`. Expected one of `AE`, `SE`, `VR`.
+    UnknownRuntime { runtime: String },
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,4 +242,28 @@ mod tests {
         assert_eq!(Runtime::from_version(&version_1_4_5), Runtime::Vr);
         assert_eq!(Runtime::from_version_strict(&version_1_4_5), None);
     }
+
+    #[test]
+    fn test_runtime_ord() {
+        // Ordered by release, not by the discriminant bits.
+        assert!(Runtime::Se < Runtime::Vr);
+        assert!(Runtime::Vr < Runtime::Ae);
+        assert!(Runtime::Se < Runtime::Ae);
+        assert_eq!(Runtime::Ae.max(Runtime::Se), Runtime::Ae);
+    }
+
+    #[test]
+    fn test_runtime_display_from_str() {
+        for runtime in [Runtime::Ae, Runtime::Se, Runtime::Vr] {
+            assert_eq!(runtime.to_string().parse(), Ok(runtime));
+            assert_eq!(runtime.to_string().to_lowercase().parse(), Ok(runtime));
+        }
+        assert_eq!(" Anniversary Edition ".parse(), Ok(Runtime::Ae));
+        assert_eq!(
+            "LE".parse::<Runtime>(),
+            Err(RuntimeParseError::UnknownRuntime {
+                runtime: "LE".to_string()
+            })
+        );
+    }
 }
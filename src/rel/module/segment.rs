@@ -11,6 +11,7 @@
 
 /// Represents a memory segment in a module.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
 pub struct Segment {
     /// Base address of the proxy module.
     pub proxy_base: usize,
@@ -20,6 +21,14 @@ pub struct Segment {
     pub size: u32,
 }
 
+// Same layout as `REL::Segment`(include/REL/Segment.h):
+// `{ std::uintptr_t _proxyBase; std::uint32_t _address; std::uint32_t _size; }`
+static_assertions::assert_eq_size!(Segment, [u8; 16]);
+static_assertions::assert_eq_align!(Segment, usize);
+static_assertions::const_assert_eq!(core::mem::offset_of!(Segment, proxy_base), 0x0);
+static_assertions::const_assert_eq!(core::mem::offset_of!(Segment, address), 0x8);
+static_assertions::const_assert_eq!(core::mem::offset_of!(Segment, size), 0xc);
+
 impl Segment {
     pub const fn const_default() -> Self {
         Self {
@@ -58,6 +67,25 @@ impl Segment {
     pub const fn offset(&self) -> usize {
         (self.address as usize).wrapping_sub(self.proxy_base)
     }
+
+    /// Checked version of [`Self::offset`].
+    ///
+    /// Returns `None` if the segment is unpopulated(`address == 0`) or `address` is below `proxy_base`,
+    /// where [`Self::offset`] would wrap around to a huge value.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::module::Segment;
+    /// assert_eq!(Segment::new(0x1000, 0x2000, 0x500).try_offset(), Some(0x1000));
+    /// assert_eq!(Segment::default().try_offset(), None);
+    /// ```
+    #[inline]
+    pub const fn try_offset(&self) -> Option<usize> {
+        if self.address == 0 {
+            return None;
+        }
+        (self.address as usize).checked_sub(self.proxy_base)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -93,6 +121,48 @@ pub enum SegmentName {
     Total,
 }
 
+impl SegmentName {
+    /// All the valid segment names, in order of their index. ([`Self::Total`] is excluded.)
+    pub const ALL: [Self; Self::Total as usize] = [
+        Self::Textx,
+        Self::Idata,
+        Self::Rdata,
+        Self::Data,
+        Self::Pdata,
+        Self::Tls,
+        Self::Textw,
+        Self::Gfids,
+    ];
+}
+
+impl TryFrom<usize> for SegmentName {
+    type Error = SegmentIndexError;
+
+    /// Converts a segment index(e.g. read from a config) back to the name.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::module::{SegmentIndexError, SegmentName};
+    ///
+    /// assert_eq!(SegmentName::try_from(3), Ok(SegmentName::Data));
+    /// assert_eq!(SegmentName::try_from(8), Err(SegmentIndexError::OutOfRange { index: 8 }));
+    /// ```
+    #[inline]
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        Self::ALL
+            .get(index)
+            .copied()
+            .ok_or(SegmentIndexError::OutOfRange { index })
+    }
+}
+
+/// Error type for converting an index into a [`SegmentName`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, snafu::Snafu)]
+pub enum SegmentIndexError {
+    /// Expected a segment index less than 8, but got {index}
+    OutOfRange { index: usize },
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,5 +171,27 @@ mod tests {
     fn test_segment_offset() {
         let segment = Segment::new(0x1000, 0x2000, 0x500);
         assert_eq!(segment.offset(), 0x1000);
+        assert_eq!(segment.try_offset(), Some(0x1000));
+    }
+
+    #[test]
+    fn test_segment_try_offset_invalid() {
+        assert_eq!(Segment::default().try_offset(), None);
+        // Unpopulated segment of a loaded module.
+        assert_eq!(Segment::new(0x1_4000_0000, 0, 0).try_offset(), None);
+        assert_eq!(Segment::new(0x3000, 0x2000, 0x500).try_offset(), None);
+    }
+
+    #[test]
+    fn test_segment_name_try_from() {
+        for (index, name) in SegmentName::ALL.into_iter().enumerate() {
+            assert_eq!(name as usize, index);
+            assert_eq!(SegmentName::try_from(index), Ok(name));
+        }
+        assert_eq!(
+            SegmentName::try_from(SegmentName::Total as usize),
+            Err(SegmentIndexError::OutOfRange { index: 8 })
+        );
+        assert!(SegmentName::try_from(usize::MAX).is_err());
     }
 }
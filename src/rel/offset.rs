@@ -28,6 +28,19 @@ impl Offset {
     pub const fn new(offset: usize) -> Self {
         Self(offset)
     }
+
+    /// Returns the raw offset value.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::offset::Offset;
+    ///
+    /// assert_eq!(Offset::new(0x1000).get(), 0x1000);
+    /// ```
+    #[inline]
+    pub const fn get(&self) -> usize {
+        self.0
+    }
 }
 
 impl ResolvableAddress for Offset {
@@ -73,6 +86,33 @@ impl VariantOffset {
             vr_offset,
         }
     }
+
+    /// Returns the offset for Skyrim Special Edition.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::offset::VariantOffset;
+    ///
+    /// const OFFSET: VariantOffset = VariantOffset::new(0x1000, 0x2000, 0x3000);
+    /// const SE: u64 = OFFSET.se();
+    /// assert_eq!((SE, OFFSET.ae(), OFFSET.vr()), (0x1000, 0x2000, 0x3000));
+    /// ```
+    #[inline]
+    pub const fn se(&self) -> u64 {
+        self.se_offset
+    }
+
+    /// Returns the offset for Skyrim Anniversary Edition.
+    #[inline]
+    pub const fn ae(&self) -> u64 {
+        self.ae_offset
+    }
+
+    /// Returns the offset for Skyrim VR.
+    #[inline]
+    pub const fn vr(&self) -> u64 {
+        self.vr_offset
+    }
 }
 
 impl ResolvableAddress for VariantOffset {
@@ -84,7 +124,7 @@ impl ResolvableAddress for VariantOffset {
     fn offset(&self) -> Result<usize, DataBaseError> {
         use crate::rel::module::Runtime;
 
-        let runtime = ModuleState::map_or_init(|module| module.runtime)?; // Derived Copy
+        let runtime = ModuleState::runtime()?;
 
         Ok(match runtime {
             Runtime::Ae => self.ae_offset,
@@ -0,0 +1,241 @@
+//! Persistent cache of pattern scan results.
+
+use super::Pattern;
+use crate::rel::version::Version;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Caches the offsets found by pattern scans in a small file, so that the next launch does not need to re-scan.
+///
+/// This is for addresses whose address library ID is missing on some game versions.
+///
+/// The file is keyed by the game version: a cache written for another version is discarded as a whole.
+/// Even on a hit, the bytes at the cached offset are validated against the pattern, and a full scan is done on mismatch.
+///
+/// # File format
+/// Plain text. The first line is the game version, followed by `{signature hash:016x} {offset:x}` per line.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::pattern::{Pattern, PatternCache};
+/// use commonlibsse_ng::rel::version::Version;
+///
+/// # let image: &[u8] = &[];
+/// let mut cache = PatternCache::load("Data/SKSE/Plugins/MyPlugin.patterns", Version::new(1, 6, 1170, 0));
+///
+/// let pattern: Pattern = "48 89 5C 24 ?? 57 48 83 EC 20".parse().unwrap();
+/// // `image` is the whole module image starting at its base, so that the offset is module relative.
+/// if let Some(offset) = cache.find(&pattern, image) {
+///     // e.g. `Offset::new(offset)`
+/// }
+///
+/// cache.save().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternCache {
+    path: PathBuf,
+    version: Version,
+    /// [`Pattern::signature_hash`] -> offset
+    entries: HashMap<u64, usize>,
+}
+
+impl PatternCache {
+    /// Creates an empty cache that is saved to `path`.
+    pub fn new(path: impl Into<PathBuf>, version: Version) -> Self {
+        Self {
+            path: path.into(),
+            version,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Loads the cache file at `path` written for `version`.
+    ///
+    /// If the file is missing, unreadable, malformed, or written for another version, this returns an empty cache.
+    /// (A cache is only an optimization, so it never fails.)
+    pub fn load(path: impl Into<PathBuf>, version: Version) -> Self {
+        let mut this = Self::new(path, version);
+
+        let contents = match std::fs::read_to_string(&this.path) {
+            Ok(contents) => contents,
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    "Failed to read the pattern cache {}: {_err}",
+                    this.path.display()
+                );
+                return this;
+            }
+        };
+
+        let entries = Self::parse(&contents, &this.version);
+        #[cfg(feature = "tracing")]
+        if entries.is_none() {
+            tracing::debug!(
+                "Discarding the stale or malformed pattern cache: {}",
+                this.path.display()
+            );
+        }
+        this.entries = entries.unwrap_or_default();
+        this
+    }
+
+    /// Parses the file contents, or returns `None` if it is malformed or written for another version.
+    fn parse(contents: &str, version: &Version) -> Option<HashMap<u64, usize>> {
+        let mut lines = contents.lines();
+        if lines.next()?.trim().parse::<Version>().ok()? != *version {
+            return None;
+        }
+
+        lines
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (hash, offset) = line.trim().split_once(' ')?;
+                Some((
+                    u64::from_str_radix(hash, 16).ok()?,
+                    usize::from_str_radix(offset, 16).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the offset of `pattern` in `haystack`.
+    ///
+    /// The cached offset is used if the bytes there still match the pattern; otherwise `haystack` is scanned
+    /// and the result is cached. The cache is not written to the file until [`Self::save`].
+    ///
+    /// Offsets are relative to the start of `haystack`, so always pass the same region(e.g. the whole module image).
+    pub fn find(&mut self, pattern: &Pattern, haystack: &[u8]) -> Option<usize> {
+        let hash = pattern.signature_hash();
+
+        if let Some(&offset) = self.entries.get(&hash) {
+            let cached = offset
+                .checked_add(pattern.len())
+                .and_then(|end| haystack.get(offset..end));
+            if cached.is_some_and(|bytes| pattern.matches(bytes)) {
+                return Some(offset);
+            }
+        }
+
+        let found = pattern.find(haystack);
+        if let Some(offset) = found {
+            self.entries.insert(hash, offset);
+        } else {
+            self.entries.remove(&hash);
+        }
+        found
+    }
+
+    /// Returns the cached offset of `pattern` without validating it.
+    #[inline]
+    pub fn get(&self, pattern: &Pattern) -> Option<usize> {
+        self.entries.get(&pattern.signature_hash()).copied()
+    }
+
+    /// Returns the number of cached patterns.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no pattern is cached.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the path of the cache file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes the cache to the file, creating the parent directories if needed.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> io::Result<()> {
+        use std::fmt::Write as _;
+
+        let mut contents = format!("{}\n", self.version);
+        // Sorted so that the file does not change between saves of the same entries.
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_unstable();
+        for (hash, offset) in entries {
+            let _ = writeln!(contents, "{hash:016x} {offset:x}");
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cache file path unique to this test process, removed on drop(even if the test panics).
+    struct TempCachePath(PathBuf);
+
+    impl TempCachePath {
+        fn new(name: &str) -> Self {
+            let file_name = format!("commonlibsse_ng-{name}-{}.txt", std::process::id());
+            Self(std::env::temp_dir().join(file_name))
+        }
+    }
+
+    impl Drop for TempCachePath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_pattern_cache() {
+        let temp = TempCachePath::new("pattern_cache");
+        let path = &temp.0;
+        let version = Version::new(1, 6, 1170, 0);
+        let pattern: Pattern = "E8 ?? C3".parse().unwrap();
+
+        let mut cache = PatternCache::new(path, version.clone());
+        assert_eq!(cache.find(&pattern, &[0x90, 0xE8, 0x01, 0xC3]), Some(1));
+        cache.save().unwrap();
+
+        // Hit
+        let mut cache = PatternCache::load(path, version.clone());
+        assert_eq!(cache.get(&pattern), Some(1));
+        assert_eq!(cache.find(&pattern, &[0x90, 0xE8, 0x02, 0xC3]), Some(1));
+
+        // The bytes at the cached offset changed: falls back to a full scan.
+        assert_eq!(
+            cache.find(&pattern, &[0x90, 0x90, 0xE8, 0x01, 0xC3]),
+            Some(2)
+        );
+        assert_eq!(cache.get(&pattern), Some(2));
+        // Not found anymore.
+        assert_eq!(cache.find(&pattern, &[0x90]), None);
+        assert!(cache.is_empty());
+
+        // Another game version discards the cache.
+        let cache = PatternCache::load(path, Version::new(1, 5, 97, 0));
+        assert!(cache.is_empty());
+
+        std::fs::remove_file(path).unwrap();
+        assert!(PatternCache::load(path, version).is_empty());
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let version = Version::new(1, 6, 1170, 0);
+        assert_eq!(
+            PatternCache::parse("1.6.1170.0\n00000000000000ff 10\n", &version),
+            Some(HashMap::from([(0xff, 0x10)]))
+        );
+        assert_eq!(PatternCache::parse("", &version), None);
+        assert_eq!(PatternCache::parse("1.6.1170.0\nff\n", &version), None);
+        assert_eq!(PatternCache::parse("1.6.1170.0\nff zz\n", &version), None);
+    }
+}
@@ -0,0 +1,233 @@
+// C++ Original code
+// - ref: https://github.com/SARDONYX-forks/CommonLibVR/blob/ng/include/REL/Pattern.h
+// SPDX-FileCopyrightText: (C) 2018 Ryan-rsm-McKenzie
+// SPDX-License-Identifier: MIT
+
+//! Byte patterns with wildcards, e.g. to verify the original bytes before patching.
+
+mod cache;
+
+pub use self::cache::PatternCache;
+
+/// A byte sequence in which each byte is either fixed or a wildcard(`None`).
+///
+/// Parsed from space separated hex bytes, where `?` or `??` is a wildcard. (Same notation as `REL::make_pattern`)
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::pattern::Pattern;
+///
+/// let pattern: Pattern = "48 83 EC ?? E8".parse().unwrap();
+/// assert_eq!(pattern.len(), 5);
+/// assert!(pattern.matches(&[0x48, 0x83, 0xEC, 0x28, 0xE8]));
+/// assert!(!pattern.matches(&[0x48, 0x83, 0xEC, 0x28, 0xE9]));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    bytes: Vec<Option<u8>>,
+}
+
+impl Pattern {
+    /// Creates a pattern from bytes, where `None` is a wildcard.
+    #[inline]
+    pub const fn new(bytes: Vec<Option<u8>>) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns the bytes of the pattern, where `None` is a wildcard.
+    #[inline]
+    pub fn as_slice(&self) -> &[Option<u8>] {
+        &self.bytes
+    }
+
+    /// Returns the number of bytes the pattern covers. (including wildcards)
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns `true` if the pattern has no bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns `true` if `bytes` has the same length and every non-wildcard byte is equal.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        self.bytes.len() == bytes.len()
+            && self
+                .bytes
+                .iter()
+                .zip(bytes)
+                .all(|(expected, actual)| expected.is_none() || *expected == Some(*actual))
+    }
+
+    /// Returns the index of the first occurrence of the pattern in `haystack`.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::pattern::Pattern;
+    ///
+    /// let pattern: Pattern = "E8 ?? C3".parse().unwrap();
+    /// assert_eq!(pattern.find(&[0x90, 0xE8, 0x01, 0xC3]), Some(1));
+    /// assert_eq!(pattern.find(&[0x90, 0xE8, 0x01]), None);
+    /// ```
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        haystack
+            .windows(self.len())
+            .position(|window| self.matches(window))
+    }
+
+    /// Returns a hash of the pattern that is stable across builds and launches. (64bit FNV-1a)
+    ///
+    /// Unlike [`core::hash::Hash`], this does not depend on the hasher, so it can be persisted. (See [`PatternCache`])
+    pub fn signature_hash(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        self.bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+            // Tag each byte so that a wildcard never collides with a fixed byte.
+            let (tag, byte) = byte.map_or((0, 0), |byte| (1, byte));
+            let hash = (hash ^ tag).wrapping_mul(PRIME);
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+    }
+}
+
+impl From<&[u8]> for Pattern {
+    /// Creates a pattern without wildcards.
+    #[inline]
+    fn from(bytes: &[u8]) -> Self {
+        Self::new(bytes.iter().copied().map(Some).collect())
+    }
+}
+
+impl core::str::FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s
+            .split_ascii_whitespace()
+            .enumerate()
+            .map(|(index, token)| {
+                if matches!(token, "?" | "??") {
+                    return Ok(None);
+                }
+                // NOTE: `from_str_radix` alone also accepts a sign. (e.g. `+F`)
+                let is_hex_byte = token.len() == 2 && token.bytes().all(|b| b.is_ascii_hexdigit());
+                match u8::from_str_radix(token, 16) {
+                    Ok(byte) if is_hex_byte => Ok(Some(byte)),
+                    _ => Err(PatternError::InvalidToken {
+                        index,
+                        token: token.to_string(),
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if bytes.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        Ok(Self::new(bytes))
+    }
+}
+
+impl core::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (index, byte) in self.bytes.iter().enumerate() {
+            if index != 0 {
+                f.write_str(" ")?;
+            }
+            match byte {
+                Some(byte) => write!(f, "{byte:02X}")?,
+                None => f.write_str("??")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by parsing a [`Pattern`].
+#[derive(Debug, Clone, PartialEq, Eq, snafu::Snafu)]
+pub enum PatternError {
+    /// Expected at least one byte, but got an empty pattern
+    Empty,
+
+    /// Expected a 2-digit hex byte or `??` at {index}, but got `{token}`
+    InvalidToken { index: usize, token: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern() {
+        let pattern: Pattern = "48 8b ? ?? E8".parse().unwrap();
+        assert_eq!(
+            pattern.as_slice(),
+            [Some(0x48), Some(0x8B), None, None, Some(0xE8)]
+        );
+        assert_eq!(pattern.to_string(), "48 8B ?? ?? E8");
+
+        assert!(pattern.matches(&[0x48, 0x8B, 0x00, 0xFF, 0xE8]));
+        // Length mismatch never matches, even if the prefix does.
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x00, 0xFF]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x00, 0xFF, 0xE8, 0x00]));
+
+        assert_eq!("".parse::<Pattern>(), Err(PatternError::Empty));
+        assert_eq!(
+            "48 8".parse::<Pattern>(),
+            Err(PatternError::InvalidToken {
+                index: 1,
+                token: "8".to_string()
+            })
+        );
+        assert_eq!(
+            "48 +F".parse::<Pattern>(),
+            Err(PatternError::InvalidToken {
+                index: 1,
+                token: "+F".to_string()
+            })
+        );
+        assert_eq!(
+            "48 GG".parse::<Pattern>(),
+            Err(PatternError::InvalidToken {
+                index: 1,
+                token: "GG".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let pattern: Pattern = "48 ?? C3".parse().unwrap();
+        assert_eq!(pattern.find(&[0x48, 0x00, 0xC3]), Some(0));
+        assert_eq!(pattern.find(&[0x90, 0x48, 0x48, 0xFF, 0xC3]), Some(2));
+        assert_eq!(pattern.find(&[0x48, 0x00]), None);
+        assert_eq!(Pattern::default().find(&[0x48]), None);
+    }
+
+    #[test]
+    fn test_signature_hash() {
+        let a: Pattern = "48 ?? C3".parse().unwrap();
+        let b: Pattern = "48 00 C3".parse().unwrap();
+        assert_eq!(
+            a.signature_hash(),
+            "48 ? C3".parse::<Pattern>().unwrap().signature_hash()
+        );
+        assert_ne!(a.signature_hash(), b.signature_hash());
+        // Persisted in caches, so it must never change.
+        assert_eq!(Pattern::default().signature_hash(), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn test_pattern_from_bytes() {
+        let pattern = Pattern::from(&[0x90_u8, 0xC3][..]);
+        assert!(pattern.matches(&[0x90, 0xC3]));
+        assert!(!pattern.matches(&[0x90, 0xCC]));
+    }
+}
@@ -11,8 +11,9 @@ use std::ops::{Deref, DerefMut};
 use std::ptr;
 
 use crate::rel::id::{DataBaseError, RelocationID, ID};
-use crate::rel::module::{ModuleState, ModuleStateError};
+use crate::rel::module::{Module, ModuleState, ModuleStateError, SegmentName};
 use crate::rel::offset::{Offset, VariantOffset};
+use crate::rel::pattern::Pattern;
 use crate::rel::ResolvableAddress;
 
 pub trait MeetsLengthReq {}
@@ -91,7 +92,11 @@ unsafe fn restore_memory_protection(
 }
 
 #[inline]
-unsafe fn safe_write<T>(dst: *mut T, src: *const T, len: usize) -> windows::core::Result<()> {
+pub(crate) unsafe fn safe_write<T>(
+    dst: *mut T,
+    src: *const T,
+    len: usize,
+) -> windows::core::Result<()> {
     let old_protection = enable_write_permission(dst as _, len)?;
     core::ptr::copy_nonoverlapping(src, dst, len);
     restore_memory_protection(dst as _, len, old_protection)
@@ -114,6 +119,76 @@ unsafe fn safe_fill(
     restore_memory_protection(dst, len, old_protection)
 }
 
+/// Page granularity of `VirtualProtect` on x64 Windows.
+const PAGE_SIZE: usize = 0x1000;
+
+/// Writes all `patches`(`(address, bytes)`) while flipping the page protection only once per page.
+///
+/// Compared to calling [`Relocation::write_bytes`] for each patch, this reduces `VirtualProtect` calls
+/// and shrinks the window in which the code pages are writable.
+///
+/// Each page is restored to its own original protection, even if the pages had different protections.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::relocation::{patch_batch, NOP2};
+///
+/// unsafe { patch_batch(&[(0x1_4000_1000, &NOP2), (0x1_4000_1010, &[0xEB])]) }.unwrap();
+/// ```
+///
+/// # Errors
+/// Returns an error if the protection of any page cannot be changed or restored.
+/// If changing fails, nothing is written and the pages changed so far are restored.
+///
+/// # Safety
+/// Every `(address, bytes)` must point to committed memory of `bytes.len()` that no other thread executes or accesses while writing.
+pub unsafe fn patch_batch(patches: &[(usize, &[u8])]) -> windows::core::Result<()> {
+    let pages = pages_of(patches);
+
+    let mut old_protections = Vec::with_capacity(pages.len());
+    for &page in &pages {
+        match enable_write_permission(page as _, PAGE_SIZE) {
+            Ok(old) => old_protections.push((page, old)),
+            Err(err) => {
+                for (page, old) in old_protections {
+                    let _ = restore_memory_protection(page as _, PAGE_SIZE, old);
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    for &(address, bytes) in patches {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), address as *mut u8, bytes.len());
+    }
+
+    // Restore all pages even if one fails, and report the first error.
+    let mut result = Ok(());
+    for (page, old) in old_protections {
+        let restored = restore_memory_protection(page as _, PAGE_SIZE, old);
+        if result.is_ok() {
+            result = restored;
+        }
+    }
+    result
+}
+
+/// Returns the sorted, deduplicated start addresses of the pages touched by `patches`.
+fn pages_of(patches: &[(usize, &[u8])]) -> Vec<usize> {
+    let mut pages: Vec<usize> = patches
+        .iter()
+        .filter(|(_, bytes)| !bytes.is_empty())
+        .flat_map(|&(address, bytes)| {
+            let first = address & !(PAGE_SIZE - 1);
+            let last = (address + bytes.len() - 1) & !(PAGE_SIZE - 1);
+            (first..=last).step_by(PAGE_SIZE)
+        })
+        .collect();
+    pages.sort_unstable();
+    pages.dedup();
+    pages
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Relocation<T = usize> {
     _impl: usize,
@@ -129,41 +204,360 @@ impl<T> Relocation<T> {
         }
     }
 
-    /// Creates an instance from two resolvable addresses.
+    /// Creates an instance from a resolved address and a relative offset from it.
+    ///
+    /// The two arguments are **asymmetric**.
+    /// - `base`: Resolved as an absolute address(`base.address()`), i.e. **including** the module base.
+    /// - `relative`: Only its raw offset(`relative.offset()`) is used, i.e. **excluding** the module base.
+    ///
+    /// This corresponds to the common C++ pattern `REL::Relocation(RELOCATION_ID(...), OFFSET)`,
+    /// e.g. hooking a call instruction located at `+0x1A` in the function of an ID.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::ID;
+    /// use commonlibsse_ng::rel::offset::Offset;
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// // The address of `+0x1A` inside the function of ID 42.
+    /// let reloc = Relocation::<usize>::from_addresses(ID::new(42), Offset::new(0x1A));
+    /// ```
     ///
     /// # Errors
-    /// Returns an error if either of the addresses cannot be resolved.
+    /// - Returns an error if either of the addresses cannot be resolved.
+    /// - Returns [`DataBaseError::AddressOutOfModule`] if the computed address is not within the module image.
+    ///   This catches e.g. passing `0`(unavailable) as `base`, or adding the module base twice.
     #[inline]
-    pub fn from_addresses<A1, A2>(id: A1, offset: A2) -> Result<Self, DataBaseError>
+    pub fn from_addresses<A1, A2>(base: A1, relative: A2) -> Result<Self, DataBaseError>
     where
         A1: ResolvableAddress,
         A2: ResolvableAddress,
     {
+        let address = base.address()?.wrapping_add(relative.offset()?);
+
+        let (module_base, image_size) =
+            ModuleState::map_or_init(|module| (module.base.as_raw(), module.image_size()))?;
+        let is_in_module = address
+            .checked_sub(module_base)
+            .is_some_and(|offset| offset < image_size as usize);
+        if !is_in_module {
+            return Err(DataBaseError::AddressOutOfModule {
+                address,
+                module_base,
+                image_size,
+            });
+        }
+
         Ok(Self {
-            _impl: id.address()? + offset.offset()?,
+            _impl: address,
             _marker: PhantomData,
         })
     }
 
+    /// Creates an instance from an offset within a segment of `module`. (e.g. a pattern found by scanning `.text`)
+    ///
+    /// The address is `proxy_base + address + seg_offset` of the segment, i.e. the absolute address of that byte.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::module::{ModuleState, SegmentName};
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let module = ModuleState::map_or_init(Clone::clone).unwrap();
+    /// let reloc = Relocation::<u8>::from_segment(&module, SegmentName::Textx, 0x1234).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`DataBaseError::SegmentOffsetOutOfRange`] if `seg_offset` is not less than the size of the segment.
+    /// (Including an unpopulated segment and [`SegmentName::Total`], whose size is regarded as `0`.)
+    pub fn from_segment(
+        module: &Module,
+        name: SegmentName,
+        seg_offset: usize,
+    ) -> Result<Self, DataBaseError> {
+        let segment = module.segment(name);
+        if seg_offset >= segment.size as usize {
+            return Err(DataBaseError::SegmentOffsetOutOfRange {
+                name,
+                seg_offset,
+                size: segment.size,
+            });
+        }
+
+        Ok(Self::new(
+            segment.proxy_base + segment.address as usize + seg_offset,
+        ))
+    }
+
     #[inline]
     pub const fn address(&self) -> usize {
         self._impl
     }
 
+    /// Returns the address as a typed pointer, e.g. to pass it to FFI.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::id::ID;
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    /// use commonlibsse_ng::rel::ResolvableAddress as _;
+    ///
+    /// let reloc = Relocation::<f32>::new(ID::new(523660).address().unwrap());
+    /// let ptr: *const f32 = reloc.as_ptr();
+    /// let value = unsafe { ptr.read() };
+    /// ```
+    #[inline]
+    pub const fn as_ptr(&self) -> *const T {
+        self._impl as *const T
+    }
+
+    /// Returns the address as a typed mutable pointer. (See [`Self::as_ptr`])
+    ///
+    /// The memory protection is not changed, so writing through it requires a writable address(e.g. `.data`).
+    #[inline]
+    pub const fn as_mut_ptr(&self) -> *mut T {
+        self._impl as *mut T
+    }
+
+    /// Returns the offset of the address from the module base.
+    ///
     /// # Errors
+    /// - If the module base cannot be retrieved.
+    /// - [`ModuleStateError::AddressBelowBase`] if the address is below the module base.
+    ///   (e.g. built from a raw address outside the module, or the module base has changed)
     #[inline]
     pub fn offset(&self) -> Result<usize, ModuleStateError> {
-        Ok(self._impl - Self::base()?)
+        let base = Self::base()?;
+        self._impl
+            .checked_sub(base)
+            .ok_or(ModuleStateError::AddressBelowBase {
+                address: self._impl,
+                base,
+            })
     }
 
+    /// Reads the value at the address.
+    ///
     /// # Panics
+    /// If the address is null(e.g. the ID is missing on the current runtime), or not aligned for `T`.
+    ///
+    /// A plugin is usually built with `panic = "abort"` or panics across FFI, so this takes down the whole game.
+    /// Prefer [`Self::try_get`] to fall back gracefully.
     #[inline]
     pub fn get(&self) -> T
     where
         T: Copy,
     {
-        assert!(self._impl != 0);
-        unsafe { ptr::read(self._impl as *const T) }
+        self.try_get()
+            .expect("Relocation::get: The address is null or misaligned.")
+    }
+
+    /// Reads the value at the address, or returns `None` if the address is null or not aligned for `T`.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// assert_eq!(Relocation::<u32>::new(0).try_get(), None);
+    ///
+    /// let value = 42_u32;
+    /// let reloc = Relocation::<u32>::new(&value as *const u32 as usize);
+    /// assert_eq!(reloc.try_get(), Some(42));
+    ///
+    /// // e.g. a wrong ID or a pattern scan result.
+    /// let misaligned = Relocation::<u32>::new(&value as *const u32 as usize + 1);
+    /// assert_eq!(misaligned.try_get(), None);
+    /// ```
+    #[inline]
+    pub fn try_get(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        self.try_deref().copied()
+    }
+
+    /// Returns a reference to the value at the address, or `None` if the address is null or not aligned for `T`.
+    ///
+    /// Unlike [`Deref`], this never creates a null or misaligned reference.
+    #[inline]
+    pub fn try_deref(&self) -> Option<&T> {
+        if !self.is_aligned() {
+            return None;
+        }
+        unsafe { (self._impl as *const T).as_ref() }
+    }
+
+    /// Returns `true` if the address is aligned for `T`.
+    ///
+    /// Address library offsets are aligned in practice, but a wrong ID or a pattern scan result may not be,
+    /// and a reference to a misaligned address is undefined behavior.
+    #[inline]
+    pub const fn is_aligned(&self) -> bool {
+        self._impl % mem::align_of::<T>() == 0
+    }
+
+    /// Reads the value at the address with [`ptr::read_volatile`].
+    ///
+    /// Use this instead of [`Self::get`] for globals that the engine mutates concurrently(e.g. timers, flags polled in a loop).
+    /// Otherwise the compiler may merge or hoist repeated reads and keep observing a stale value.
+    /// [`Self::get`] is fine for values that do not change while being observed.
+    ///
+    /// NOTE: Volatile is not atomic. It does not synchronize with the writer thread nor prevent torn reads of a large `T`.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let mut value = 42_u32;
+    /// let reloc = Relocation::<u32>::new(&mut value as *mut u32 as usize);
+    /// reloc.write_volatile(7);
+    /// assert_eq!(reloc.read_volatile(), 7);
+    /// ```
+    ///
+    /// # Panics
+    /// If the address is null.
+    #[inline]
+    pub fn read_volatile(&self) -> T
+    where
+        T: Copy,
+    {
+        assert!(
+            self._impl != 0,
+            "Relocation::read_volatile: The address is null."
+        );
+        unsafe { ptr::read_volatile(self._impl as *const T) }
+    }
+
+    /// Writes `value` to the address with [`ptr::write_volatile`]. (See [`Self::read_volatile`] for when it is needed)
+    ///
+    /// The memory protection is not changed, so the address must be writable(e.g. `.data`, not `.rdata`).
+    ///
+    /// # Panics
+    /// If the address is null.
+    #[inline]
+    pub fn write_volatile(&self, value: T)
+    where
+        T: Copy,
+    {
+        assert!(
+            self._impl != 0,
+            "Relocation::write_volatile: The address is null."
+        );
+        unsafe { ptr::write_volatile(self._impl as *mut T, value) };
+    }
+
+    /// Treats the address as an array of `len` elements. (e.g. a global table of `N` pointers)
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let table = [1_u32, 2, 3];
+    /// let reloc = Relocation::<u32>::new(table.as_ptr() as usize);
+    /// assert_eq!(unsafe { reloc.as_slice(3) }, &[1, 2, 3]);
+    /// ```
+    ///
+    /// # Panics
+    /// If the address is null.
+    ///
+    /// # Safety
+    /// The caller guarantees that `len` consecutive valid `T` exist at the address,
+    /// and that they are not mutated while the slice is alive.
+    #[inline]
+    pub unsafe fn as_slice(&self, len: usize) -> &[T] {
+        assert!(
+            self._impl != 0,
+            "Relocation::as_slice: The address is null."
+        );
+        unsafe { core::slice::from_raw_parts(self._impl as *const T, len) }
+    }
+
+    /// Mutable version of [`Self::as_slice`].
+    ///
+    /// # Panics
+    /// If the address is null.
+    ///
+    /// # Safety
+    /// The caller guarantees that `len` consecutive valid `T` exist at the address,
+    /// that the memory is writable, and that nothing else accesses them while the slice is alive.
+    #[inline]
+    pub unsafe fn as_mut_slice(&mut self, len: usize) -> &mut [T] {
+        assert!(
+            self._impl != 0,
+            "Relocation::as_mut_slice: The address is null."
+        );
+        unsafe { core::slice::from_raw_parts_mut(self._impl as *mut T, len) }
+    }
+
+    /// Reads `count` consecutive elements starting at the address, one by one with [`ptr::read`].
+    ///
+    /// Unlike [`Self::as_slice`], no reference to the memory is created, so the elements may be changed by the engine
+    /// between the reads.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let table = [1_u32, 2, 3];
+    /// let reloc = Relocation::<u32>::new(table.as_ptr() as usize);
+    /// assert_eq!(unsafe { reloc.iter(2) }.collect::<Vec<_>>(), [1, 2]);
+    /// ```
+    ///
+    /// # Panics
+    /// If the address is null.
+    ///
+    /// # Safety
+    /// The caller guarantees that the memory is valid for `count` consecutive, properly aligned `T`
+    /// as long as the iterator is used.
+    #[inline]
+    pub unsafe fn iter(&self, count: usize) -> impl Iterator<Item = T>
+    where
+        T: Copy,
+    {
+        assert!(self._impl != 0, "Relocation::iter: The address is null.");
+        let base = self._impl as *const T;
+        (0..count).map(move |index| unsafe { ptr::read(base.add(index)) })
+    }
+
+    /// Reads consecutive pointers starting at the address until a null one. (e.g. a null-terminated table of pointers)
+    ///
+    /// The null element is not yielded.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let (a, b) = (1_u32, 2_u32);
+    /// let table = [&raw const a, &raw const b, core::ptr::null()];
+    /// let reloc = Relocation::<*const u32>::new(table.as_ptr() as usize);
+    /// let values: Vec<u32> = unsafe { reloc.iter_until_null() }.map(|ptr| unsafe { *ptr }).collect();
+    /// assert_eq!(values, [1, 2]);
+    /// ```
+    ///
+    /// # Panics
+    /// If the address is null.
+    ///
+    /// # Safety
+    /// - `T` must be a pointer-sized type whose null value is all zero bits. (e.g. `*const U`, `usize`)
+    ///   Other pointer-sized types are not rejected at compile time.
+    /// - The caller guarantees that the memory is valid for properly aligned `T` up to and including the null one
+    ///   as long as the iterator is used.
+    #[inline]
+    pub unsafe fn iter_until_null(&self) -> impl Iterator<Item = T>
+    where
+        T: Copy,
+    {
+        const { assert!(mem::size_of::<T>() == mem::size_of::<usize>()) };
+
+        assert!(
+            self._impl != 0,
+            "Relocation::iter_until_null: The address is null."
+        );
+        let base = self._impl as *const T;
+        (0..)
+            .map(move |index| unsafe { ptr::read(base.add(index)) })
+            // SAFETY: The size is checked above.
+            .take_while(|element| unsafe { mem::transmute_copy::<T, usize>(element) } != 0)
     }
 
     #[inline]
@@ -174,6 +568,63 @@ impl<T> Relocation<T> {
         let _ = unsafe { safe_write_value(self._impl as *mut U, data) };
     }
 
+    /// Returns `true` if the bytes at the address equal `expected`.
+    ///
+    /// Use this before patching to make sure that the address(e.g. resolved by an ID on an unanticipated game build)
+    /// still has the original code, instead of clobbering something else.
+    ///
+    /// Returns `false` if the address is null.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let code = [0x48_u8, 0x83, 0xEC, 0x28];
+    /// let reloc = Relocation::<u8>::new(code.as_ptr() as usize);
+    /// assert!(unsafe { reloc.verify_bytes(&[0x48, 0x83, 0xEC, 0x28]) });
+    /// assert!(!unsafe { reloc.verify_bytes(&[0x48, 0x83, 0xEC, 0x38]) });
+    /// ```
+    ///
+    /// # Safety
+    /// Unless the address is null, `expected.len()` bytes from it must be readable, e.g. within a section of the loaded module.
+    /// Reading unmapped memory is not caught, and crashes the process.
+    #[inline]
+    pub unsafe fn verify_bytes(&self, expected: &[u8]) -> bool {
+        unsafe { self.read_bytes(expected.len()) }.is_some_and(|actual| actual == expected)
+    }
+
+    /// Same as [`Self::verify_bytes`], but `?` wildcards of `expected` match any byte.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::pattern::Pattern;
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// let code = [0x48_u8, 0x83, 0xEC, 0x28];
+    /// let reloc = Relocation::<u8>::new(code.as_ptr() as usize);
+    /// assert!(unsafe { reloc.verify_pattern(&"48 83 EC ??".parse::<Pattern>().unwrap()) });
+    /// ```
+    ///
+    /// # Safety
+    /// Unless the address is null, `expected.len()` bytes from it must be readable. (Same as [`Self::verify_bytes`])
+    #[inline]
+    pub unsafe fn verify_pattern(&self, expected: &Pattern) -> bool {
+        unsafe { self.read_bytes(expected.len()) }.is_some_and(|actual| expected.matches(actual))
+    }
+
+    /// Returns `len` bytes at the address, or `None` if the address is null.
+    ///
+    /// # Safety
+    /// Unless the address is null, `len` bytes from it must be readable for the returned lifetime.
+    #[inline]
+    unsafe fn read_bytes(&self, len: usize) -> Option<&[u8]> {
+        if self._impl == 0 {
+            return None;
+        }
+        // SAFETY: The caller guarantees that `len` bytes from the address are readable.
+        Some(unsafe { core::slice::from_raw_parts(self._impl as *const u8, len) })
+    }
+
     #[inline]
     pub fn write_bytes(&self, data: &[u8])
     where
@@ -193,6 +644,47 @@ impl<T> Relocation<T> {
         old_func
     }
 
+    /// Typed version of [`Self::write_vfunc`].
+    ///
+    /// Replaces the `idx`-th entry of the vtable with `new_func` and returns the previous entry as `F`,
+    /// so that the original can be called from the hook without a manual `transmute`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use commonlibsse_ng::rel::relocation::Relocation;
+    ///
+    /// type Update = unsafe extern "C" fn(this: *mut core::ffi::c_void, delta: f32);
+    /// static mut ORIGINAL: Option<Update> = None;
+    ///
+    /// unsafe extern "C" fn hook(this: *mut core::ffi::c_void, delta: f32) {
+    ///     if let Some(original) = unsafe { ORIGINAL } {
+    ///         unsafe { original(this, delta) };
+    ///     }
+    /// }
+    ///
+    /// let vtable = Relocation::<usize>::new(0x1234_5678);
+    /// unsafe { ORIGINAL = Some(vtable.replace_vfunc(0x0AD, hook as Update)) };
+    /// ```
+    ///
+    /// # Safety
+    /// - `F` must be a function pointer type with the same signature and ABI as the vtable entry.
+    ///   Pointer-sized non-function types are not rejected at compile time, so this is the caller's responsibility.
+    /// - The entry must not be null. (A null function pointer is undefined behavior.)
+    #[inline]
+    pub unsafe fn replace_vfunc<F>(&self, idx: usize, new_func: F) -> F
+    where
+        T: Into<usize>,
+        F: Copy,
+    {
+        const { assert!(mem::size_of::<F>() == mem::size_of::<usize>()) };
+
+        // SAFETY: The size is checked above, and the caller guarantees that `F` is a function pointer type.
+        let new_func = unsafe { mem::transmute_copy::<F, usize>(&new_func) };
+        let old_func = self.write_vfunc(idx, new_func);
+        // SAFETY: Same as above, and the caller guarantees that the entry is not null.
+        unsafe { mem::transmute_copy::<usize, F>(&old_func) }
+    }
+
     #[inline]
     pub fn write_fill(&self, value: u8, count: usize)
     where
@@ -210,6 +702,7 @@ impl<T> Relocation<T> {
     }
 }
 
+/// NOTE: Dereferencing a null address is undefined behavior. Use [`Relocation::try_deref`] if it may be null.
 impl<T> Deref for Relocation<T>
 where
     T: Copy,
@@ -218,6 +711,10 @@ where
 
     #[inline]
     fn deref(&self) -> &Self::Target {
+        debug_assert!(
+            self.is_aligned(),
+            "Relocation::deref: The address is misaligned."
+        );
         unsafe { &*(self._impl as *const T) }
     }
 }
@@ -228,6 +725,10 @@ where
 {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
+        debug_assert!(
+            self.is_aligned(),
+            "Relocation::deref_mut: The address is misaligned."
+        );
         unsafe { &mut *(self._impl as *mut T) }
     }
 }
@@ -291,3 +792,121 @@ impl<T> TryFrom<RelocationID> for Relocation<T> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_of() {
+        let patches: [(usize, &[u8]); 4] = [
+            (0x2010, &[0x90]),
+            (0x1ffe, &[0x90; 4]), // crosses the page boundary
+            (0x2020, &[0xEB]),
+            (0x5000, &[]), // empty patch touches no page
+        ];
+        assert_eq!(pages_of(&patches), [0x1000, 0x2000]);
+    }
+
+    #[test]
+    fn test_from_segment() {
+        let module = ModuleState::map_or_init(Clone::clone).unwrap();
+
+        for name in [SegmentName::Textx, SegmentName::Data] {
+            let segment = module.try_segment(name).unwrap();
+            assert!(segment.size > 0, "{name:?}");
+
+            let last = segment.size as usize - 1;
+            let reloc = Relocation::<u8>::from_segment(&module, name, last).unwrap();
+            assert_eq!(
+                reloc.address(),
+                segment.proxy_base + segment.address as usize + last
+            );
+            let end = segment.size as usize;
+            assert!(matches!(
+                Relocation::<u8>::from_segment(&module, name, end),
+                Err(DataBaseError::SegmentOffsetOutOfRange { name: n, seg_offset, size })
+                    if n == name && seg_offset == end && size == segment.size
+            ));
+        }
+        assert!(matches!(
+            Relocation::<u8>::from_segment(&module, SegmentName::Total, 0),
+            Err(DataBaseError::SegmentOffsetOutOfRange { size: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_offset_below_base() {
+        let base = Relocation::<u8>::base().unwrap();
+
+        assert_eq!(Relocation::<u8>::new(base).offset().unwrap(), 0);
+        assert_eq!(Relocation::<u8>::new(base + 0x10).offset().unwrap(), 0x10);
+        assert!(matches!(
+            Relocation::<u8>::new(base - 1).offset(),
+            Err(ModuleStateError::AddressBelowBase { address, base: b }) if address == base - 1 && b == base
+        ));
+    }
+
+    #[test]
+    fn test_iter() {
+        let table = [1_usize, 2, 3, 0, 5];
+        let reloc = Relocation::<usize>::new(table.as_ptr() as usize);
+        assert_eq!(unsafe { reloc.iter(5) }.collect::<Vec<_>>(), table);
+        assert_eq!(unsafe { reloc.iter(0) }.count(), 0);
+        assert_eq!(
+            unsafe { reloc.iter_until_null() }.collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_misaligned() {
+        let value = 1_u64;
+        let reloc = Relocation::<u64>::new(&value as *const u64 as usize);
+        assert!(reloc.is_aligned());
+        assert_eq!(reloc.try_get(), Some(1));
+
+        let misaligned = Relocation::<u64>::new(reloc.address() + 1);
+        assert!(!misaligned.is_aligned());
+        assert_eq!(misaligned.try_deref(), None);
+        // `u8` is always aligned.
+        assert!(Relocation::<u8>::new(reloc.address() + 1).is_aligned());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "The address is misaligned")]
+    fn test_deref_misaligned() {
+        let value = [0_u64; 2];
+        let misaligned = Relocation::<u64>::new(value.as_ptr() as usize + 1);
+        let _value: u64 = *misaligned;
+    }
+
+    #[test]
+    fn test_volatile() {
+        let mut value = 1_u64;
+        let reloc = Relocation::<u64>::new(&mut value as *mut u64 as usize);
+        assert_eq!(reloc.read_volatile(), 1);
+        reloc.write_volatile(u64::MAX);
+        assert_eq!(reloc.read_volatile(), u64::MAX);
+        assert_eq!(value, u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "The address is null")]
+    fn test_read_volatile_null() {
+        let _ = Relocation::<u32>::new(0).read_volatile();
+    }
+
+    #[test]
+    fn test_patch_batch() {
+        let mut buf = vec![0_u8; PAGE_SIZE * 3];
+        let base = buf.as_mut_ptr() as usize;
+
+        let patches: [(usize, &[u8]); 2] = [(base + 1, &[1, 2]), (base + PAGE_SIZE * 2, &[3])];
+        unsafe { patch_batch(&patches) }.unwrap();
+
+        assert_eq!(buf[..4], [0, 1, 2, 0]);
+        assert_eq!(buf[PAGE_SIZE * 2], 3);
+    }
+}
@@ -0,0 +1,109 @@
+//! [`Version`] wrapper that ignores the build component.
+
+use super::Version;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+/// A [`Version`] whose equality, ordering and hash only consider `major.minor.patch`.
+///
+/// Useful as a `HashMap`/`BTreeMap` key of a compatibility table, since Bethesda bumps the build
+/// for trivial reasons.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::version::{Version, VersionCore};
+/// use std::collections::HashMap;
+///
+/// let mut table = HashMap::new();
+/// table.insert(VersionCore(Version::new(1, 6, 1170, 0)), "supported");
+///
+/// assert_eq!(table.get(&VersionCore(Version::new(1, 6, 1170, 1))), Some(&"supported"));
+/// assert_eq!(table.get(&VersionCore(Version::new(1, 6, 640, 0))), None);
+/// ```
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub struct VersionCore(pub Version);
+
+impl VersionCore {
+    /// Returns the wrapped version. (including the build component)
+    #[inline]
+    pub const fn get(&self) -> &Version {
+        &self.0
+    }
+
+    /// Unwraps the version.
+    #[inline]
+    pub const fn into_inner(self) -> Version {
+        self.0
+    }
+}
+
+impl From<Version> for VersionCore {
+    #[inline]
+    fn from(version: Version) -> Self {
+        Self(version)
+    }
+}
+
+impl PartialEq for VersionCore {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_build(&other.0)
+    }
+}
+
+impl Eq for VersionCore {}
+
+impl PartialOrd for VersionCore {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VersionCore {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_ignore_build(&other.0)
+    }
+}
+
+impl Hash for VersionCore {
+    // Must be consistent with `PartialEq`, so the build is not hashed.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0._impl[..3].hash(state);
+    }
+}
+
+impl core::fmt::Display for VersionCore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let [major, minor, patch, _] = self.0._impl;
+        write!(f, "{major}.{minor}.{patch}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_core_ignores_build() {
+        let a = VersionCore(Version::new(1, 6, 1170, 0));
+        let b = VersionCore(Version::new(1, 6, 1170, 9));
+
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert!(VersionCore(Version::new(1, 5, 97, 9)) < a);
+
+        let hash = |v: &VersionCore| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+
+        // The derived `Ord` of `Version` still compares the build.
+        assert!(a.get() < b.get());
+        assert_eq!(a.to_string(), "1.6.1170");
+    }
+}
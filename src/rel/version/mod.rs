@@ -6,16 +6,23 @@
 // SPDX-FileCopyrightText: (C) 2025 SARDONYX
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+mod ignore_build;
+mod readable;
+mod req;
 #[cfg(feature = "win_api")]
 mod win_api;
 
+pub use self::ignore_build::VersionCore;
+pub use self::readable::ReadableVersion;
+pub use self::req::{VersionReq, VersionReqParseError};
+
 #[cfg(feature = "win_api")]
 pub use win_api::{get_file_version, FileVersionError};
 
-#[cfg(not(feature = "no_sys"))]
+#[cfg(all(feature = "std", not(feature = "no_sys")))]
 pub use crate::sys::REL::Version;
 
-#[cfg(feature = "no_sys")]
+#[cfg(any(feature = "no_sys", not(feature = "std")))]
 /// Represents a 4-part version number.
 ///
 /// In binding, [`Copy`] is inherited, but it is omitted to avoid implicit copying in for loops, etc.
@@ -28,6 +35,7 @@ pub use crate::sys::REL::Version;
 /// assert_eq!(ver.major(), 1);
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
 pub struct Version {
     /// Internal representation of the version as a 4-element array.
     ///
@@ -40,6 +48,13 @@ pub struct Version {
     _impl: [u16; 4],
 }
 
+// Same layout as `REL::Version`(include/REL/Version.h): `{ std::array<std::uint16_t, 4> _impl; }`
+// (Without `no_sys`, the bindgen type is checked by its generated layout test instead.)
+#[cfg(any(feature = "no_sys", not(feature = "std")))]
+static_assertions::assert_eq_size!(Version, [u8; 8]);
+#[cfg(any(feature = "no_sys", not(feature = "std")))]
+static_assertions::assert_eq_align!(Version, u16);
+
 impl Version {
     /// Create a empty version.
     ///
@@ -75,8 +90,10 @@ impl Version {
     /// # Errors
     /// Errors are made under the following conditions.
     ///
-    /// - When there is no number after a point.
+    /// - When the input is empty.
+    /// - When there is no number before or after a point.
     /// - If there are more than 4 numbers.
+    /// - If a number exceeds `u16::MAX`.
     /// - When there is a non-numeric character (other than a dot).
     ///
     /// # Examples
@@ -104,9 +121,45 @@ impl Version {
     ///     Version::const_from_str("1.2."),
     ///     Err(VersionParseError::MissingNumber { part: 2 })
     /// );
+    /// assert_eq!(Version::const_from_str(""), Err(VersionParseError::Empty));
+    /// assert_eq!(
+    ///     Version::const_from_str("1.65536"),
+    ///     Err(VersionParseError::NumberTooLarge { part: 1 })
+    /// );
     /// ```
     #[inline]
     pub const fn const_from_str(version: &str) -> Result<Self, VersionParseError> {
+        match Self::parse_detailed(version) {
+            Ok(version) => Ok(version),
+            Err(err) => Err(err.reason),
+        }
+    }
+
+    /// Same as [`Self::const_from_str`], but the error also carries the byte offset where the problem occurred,
+    /// e.g. to underline the offending character of a user-entered version string.
+    ///
+    /// # Errors
+    /// Same as [`Self::const_from_str`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use commonlibsse_ng::rel::version::{Version, VersionParseError, VersionParseErrorAt};
+    ///
+    /// assert_eq!(Version::parse_detailed("1.6.1170"), Ok(Version::new(1, 6, 1170, 0)));
+    /// assert_eq!(
+    ///     Version::parse_detailed("1.6.x"),
+    ///     Err(VersionParseErrorAt {
+    ///         reason: VersionParseError::InvalidCharacter { character: 'x' },
+    ///         offset: 4,
+    ///     })
+    /// );
+    /// ```
+    pub const fn parse_detailed(version: &str) -> Result<Self, VersionParseErrorAt> {
+        /// Shorthand to attach the offset to the reason.
+        const fn at(reason: VersionParseError, offset: usize) -> VersionParseErrorAt {
+            VersionParseErrorAt { reason, offset }
+        }
+
         let mut parts = [0_u16; 4];
         let mut idx = 0;
         let mut num = 0;
@@ -114,12 +167,20 @@ impl Version {
 
         let bytes = version.as_bytes();
         let len = bytes.len();
+        if len == 0 {
+            return Err(at(VersionParseError::Empty, 0));
+        }
+
         let mut i = 0;
         while i < len {
             let b = bytes[i];
             if b == b'.' {
                 if idx >= 4 {
-                    return Err(VersionParseError::TooManyParts { parts: idx });
+                    return Err(at(VersionParseError::TooManyParts { parts: idx }, i));
+                }
+                // e.g. `.1`, `1..2`: A missing number is not treated as `0`.
+                if !has_digit {
+                    return Err(at(VersionParseError::MissingNumber { part: idx }, i));
                 }
                 parts[idx] = num;
 
@@ -127,23 +188,33 @@ impl Version {
                 idx += 1;
                 has_digit = false;
             } else if b.is_ascii_digit() {
-                num = num * 10 + (b - b'0') as u16;
+                if idx >= 4 {
+                    return Err(at(VersionParseError::TooManyParts { parts: idx }, i));
+                }
+                let digit = (b - b'0') as u16;
+                if num > (u16::MAX - digit) / 10 {
+                    return Err(at(VersionParseError::NumberTooLarge { part: idx }, i));
+                }
+                num = num * 10 + digit;
                 has_digit = true;
             } else {
-                return Err(VersionParseError::InvalidCharacter {
-                    character: b as char,
-                });
+                return Err(at(
+                    VersionParseError::InvalidCharacter {
+                        character: b as char,
+                    },
+                    i,
+                ));
             }
             i += 1;
         }
 
         if has_digit {
             if idx >= 4 {
-                return Err(VersionParseError::TooManyParts { parts: idx });
+                return Err(at(VersionParseError::TooManyParts { parts: idx }, len));
             }
             parts[idx] = num;
         } else {
-            return Err(VersionParseError::MissingNumber { part: idx });
+            return Err(at(VersionParseError::MissingNumber { part: idx }, len));
         }
 
         Ok(Self { _impl: parts })
@@ -205,7 +276,53 @@ impl Version {
         self._impl[3]
     }
 
+    /// Returns the component at `idx`(0: major, 1: minor, 2: patch, 3: build), or `None` if out of range.
+    ///
+    /// Non-panicking counterpart of [`Index`](core::ops::Index).
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let v = Version::new(1, 2, 3, 4);
+    /// assert_eq!(v.get(2), Some(3));
+    /// assert_eq!(v.get(4), None);
+    /// ```
+    #[inline]
+    pub const fn get(&self, idx: usize) -> Option<u16> {
+        if idx < self._impl.len() {
+            Some(self._impl[idx])
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the component at `idx`, or `None` if out of range.
+    ///
+    /// Non-panicking counterpart of [`IndexMut`](core::ops::IndexMut).
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let mut v = Version::new(1, 2, 3, 4);
+    /// if let Some(build) = v.get_mut(3) {
+    ///     *build = 5;
+    /// }
+    /// assert_eq!(v, Version::new(1, 2, 3, 5));
+    /// assert_eq!(v.get_mut(4), None);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut u16> {
+        self._impl.get_mut(idx)
+    }
+
     /// Packs the version into a 32-bit integer.
+    ///
+    /// This is lossy: bits that do not fit are silently masked out. (major: 8bits, minor: 8bits,
+    /// patch: 12bits, build: 4bits)
+    /// Use [`Self::pack_checked`] to detect it.
+    ///
     /// # Examples
     /// ```
     /// use commonlibsse_ng::rel::version::Version;
@@ -221,6 +338,111 @@ impl Version {
             | (self._impl[3] as u32 & 0xF)
     }
 
+    /// Packs the version into a 32-bit integer, failing if any component does not fit.
+    ///
+    /// # Errors
+    /// Returns the first component that exceeds its bit width.
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::{PackOverflow, Version, VersionComponent};
+    ///
+    /// assert_eq!(Version::new(1, 6, 1170, 0).pack_checked(), Ok(Version::new(1, 6, 1170, 0).pack()));
+    /// assert_eq!(
+    ///     Version::new(1, 6, 5000, 0).pack_checked(),
+    ///     Err(PackOverflow { component: VersionComponent::Patch, value: 5000 })
+    /// );
+    /// ```
+    pub const fn pack_checked(&self) -> Result<u32, PackOverflow> {
+        const COMPONENTS: [VersionComponent; 4] = [
+            VersionComponent::Major,
+            VersionComponent::Minor,
+            VersionComponent::Patch,
+            VersionComponent::Build,
+        ];
+
+        let mut idx = 0;
+        while idx < COMPONENTS.len() {
+            let component = COMPONENTS[idx];
+            let value = self._impl[idx];
+            if value > component.max_value() {
+                return Err(PackOverflow { component, value });
+            }
+            idx += 1;
+        }
+
+        Ok(self.pack())
+    }
+
+    /// Compares `major.minor.patch`, ignoring the build component.
+    ///
+    /// The derived [`Ord`] compares all four components. Use this for compatibility gating,
+    /// where builds often differ for trivial reasons.
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    /// use core::cmp::Ordering;
+    ///
+    /// let v = Version::new(1, 6, 1170, 0);
+    /// assert_eq!(v.cmp_ignore_build(&Version::new(1, 6, 1170, 1)), Ordering::Equal);
+    /// assert_eq!(v.cmp_ignore_build(&Version::new(1, 6, 640, 9)), Ordering::Greater);
+    /// ```
+    #[inline]
+    pub fn cmp_ignore_build(&self, other: &Self) -> core::cmp::Ordering {
+        self._impl[..3].cmp(&other._impl[..3])
+    }
+
+    /// Returns `true` if `major.minor.patch` are equal, ignoring the build component.
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// assert!(Version::new(1, 6, 1170, 0).eq_ignore_build(&Version::new(1, 6, 1170, 1)));
+    /// assert!(!Version::new(1, 6, 1170, 0).eq_ignore_build(&Version::new(1, 6, 1179, 0)));
+    /// ```
+    #[inline]
+    pub const fn eq_ignore_build(&self, other: &Self) -> bool {
+        self._impl[0] == other._impl[0]
+            && self._impl[1] == other._impl[1]
+            && self._impl[2] == other._impl[2]
+    }
+
+    /// Returns `true` if `self >= min`, e.g. to gate a feature on "AE 1.6.640 or later".
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let min = Version::new(1, 6, 640, 0);
+    /// assert!(Version::new(1, 6, 1170, 0).at_least(&min));
+    /// assert!(Version::new(1, 6, 640, 0).at_least(&min));
+    /// assert!(!Version::new(1, 5, 97, 0).at_least(&min));
+    /// ```
+    #[inline]
+    pub fn at_least(&self, min: &Self) -> bool {
+        self._impl >= min._impl
+    }
+
+    /// Returns `true` if `min <= self <= max`. (Both inclusive)
+    ///
+    /// For an exclusive or open-ended range, use [`VersionReq`].
+    ///
+    /// # Examples
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    ///
+    /// let (min, max) = (Version::new(1, 6, 317, 0), Version::new(1, 6, 1170, 0));
+    /// assert!(Version::new(1, 6, 640, 0).in_range(&min, &max));
+    /// assert!(Version::new(1, 6, 1170, 0).in_range(&min, &max));
+    /// assert!(!Version::new(1, 6, 1179, 0).in_range(&min, &max));
+    /// ```
+    #[inline]
+    pub fn in_range(&self, min: &Self, max: &Self) -> bool {
+        min._impl <= self._impl && self._impl <= max._impl
+    }
+
     /// Unpacks a 32-bit integer into a `Version`.
     #[inline]
     pub const fn unpack(packed: u32) -> Self {
@@ -278,6 +500,9 @@ impl core::str::FromStr for Version {
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, snafu::Snafu)]
 pub enum VersionParseError {
+    /// Expected a version string, but got an empty string
+    Empty,
+
     /// Expected at most 4 parts, but got {parts} parts
     TooManyParts { parts: usize },
 
@@ -286,6 +511,70 @@ pub enum VersionParseError {
 
     /// Expected numbers after the dots, but got none in part {part}
     MissingNumber { part: usize },
+
+    /// Expected a number up to 65535, but part {part} is too large
+    NumberTooLarge { part: usize },
+}
+
+/// [`VersionParseError`] with the byte offset in the input where it occurred. (See [`Version::parse_detailed`])
+///
+/// The offset is that of the offending byte, or the length of the input if the input ended unexpectedly.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, snafu::Snafu)]
+#[snafu(display("{reason} (at byte {offset})"))]
+pub struct VersionParseErrorAt {
+    /// Why the parsing failed.
+    pub reason: VersionParseError,
+    /// Byte offset in the input.
+    pub offset: usize,
+}
+
+/// A component of [`Version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VersionComponent {
+    /// 1st part. (8bits when packed)
+    Major,
+    /// 2nd part. (8bits when packed)
+    Minor,
+    /// 3rd part. (12bits when packed)
+    Patch,
+    /// 4th part. (4bits when packed)
+    Build,
+}
+
+impl VersionComponent {
+    /// Returns the maximum value that fits into the packed representation.
+    #[inline]
+    pub const fn max_value(self) -> u16 {
+        match self {
+            Self::Major | Self::Minor => 0xFF,
+            Self::Patch => 0xFFF,
+            Self::Build => 0xF,
+        }
+    }
+}
+
+impl core::fmt::Display for VersionComponent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+            Self::Build => "build",
+        })
+    }
+}
+
+/// Error returned by [`Version::pack_checked`] when a component does not fit into its bit width.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, snafu::Snafu)]
+#[snafu(display(
+    "Version component {component}({value}) does not fit into the packed version. (max: {})",
+    component.max_value()
+))]
+pub struct PackOverflow {
+    /// The offending component.
+    pub component: VersionComponent,
+    /// The value of the component.
+    pub value: u16,
 }
 
 #[cfg(test)]
@@ -304,4 +593,214 @@ mod tests {
         assert!(v3 > v1);
         assert!(v1 == v4);
     }
+
+    #[test]
+    fn test_const_from_str_malformed() {
+        assert_eq!(Version::const_from_str(""), Err(VersionParseError::Empty));
+        assert_eq!(
+            Version::const_from_str("."),
+            Err(VersionParseError::MissingNumber { part: 0 })
+        );
+        assert_eq!(
+            Version::const_from_str(".5"),
+            Err(VersionParseError::MissingNumber { part: 0 })
+        );
+        assert_eq!(
+            Version::const_from_str("1..2"),
+            Err(VersionParseError::MissingNumber { part: 1 })
+        );
+        assert_eq!(
+            Version::const_from_str("..."),
+            Err(VersionParseError::MissingNumber { part: 0 })
+        );
+    }
+
+    #[test]
+    fn test_const_from_str_overflow() {
+        assert_eq!(
+            Version::const_from_str("1.65535"),
+            Ok(Version::new(1, 65535, 0, 0))
+        );
+        assert_eq!(
+            Version::const_from_str("1.65536"),
+            Err(VersionParseError::NumberTooLarge { part: 1 })
+        );
+        assert_eq!(
+            Version::const_from_str("1.6.99999.1"),
+            Err(VersionParseError::NumberTooLarge { part: 2 })
+        );
+    }
+
+    #[test]
+    fn test_parse_detailed_offset() {
+        let err_at = |s| Version::parse_detailed(s).unwrap_err();
+
+        assert_eq!(err_at("").offset, 0);
+        assert_eq!(err_at("1.2.f").offset, 4);
+        assert_eq!(err_at("1..2").offset, 2);
+        assert_eq!(err_at("1.2.").offset, 4);
+        assert_eq!(err_at("1.65536").offset, 6);
+        assert_eq!(
+            err_at("1.2.3.4.5"),
+            VersionParseErrorAt {
+                reason: VersionParseError::TooManyParts { parts: 4 },
+                offset: 8,
+            }
+        );
+        assert_eq!(
+            err_at("1.x").to_string(),
+            "Expected a number but got invalid character: `x` (at byte 2)"
+        );
+    }
+
+    #[test]
+    fn test_display_from_str_roundtrip() {
+        use core::str::FromStr as _;
+
+        const VALUES: [u16; 6] = [0, 1, 9, 10, 1170, u16::MAX];
+        for major in VALUES {
+            for minor in VALUES {
+                for patch in VALUES {
+                    for build in VALUES {
+                        let v = Version::new(major, minor, patch, build);
+                        assert_eq!(Version::from_str(&v.to_string()), Ok(v));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_version_req() {
+        let parse = |s: &str| s.parse::<VersionReq>();
+        let v = |patch| Version::new(1, 6, patch, 0);
+
+        let req = parse(" >= 1.6.640 ,<1.6.1170").unwrap();
+        assert!(!req.matches(&v(639)));
+        assert!(req.matches(&v(640)));
+        assert!(!req.matches(&v(1170)));
+        assert_eq!(req.to_string(), ">=1.6.640.0, <1.6.1170.0");
+
+        // The stricter bound wins.
+        let req = parse(">1.6.640, >=1.6.640, <=1.6.1170, <1.6.1170").unwrap();
+        assert_eq!(req, parse(">1.6.640, <1.6.1170").unwrap());
+        assert!(!req.matches(&v(640)));
+
+        let req = parse("1.6.640").unwrap();
+        assert_eq!(req, parse("=1.6.640.0").unwrap());
+        assert!(req.matches(&v(640)));
+        assert!(!req.matches(&Version::new(1, 6, 640, 1)));
+        assert_eq!(req.to_string(), "=1.6.640.0");
+
+        assert_eq!(parse("*"), Ok(VersionReq::ANY));
+        assert_eq!(parse(&VersionReq::ANY.to_string()), Ok(VersionReq::ANY));
+    }
+
+    #[test]
+    fn test_version_req_malformed() {
+        let parse = |s: &str| s.parse::<VersionReq>();
+
+        assert_eq!(
+            parse(""),
+            Err(VersionReqParseError::EmptyComparator { index: 0 })
+        );
+        assert_eq!(
+            parse(">=1.6,"),
+            Err(VersionReqParseError::EmptyComparator { index: 1 })
+        );
+        assert_eq!(
+            parse(">=1.6, ~1.7"),
+            Err(VersionReqParseError::InvalidVersion {
+                index: 1,
+                source: VersionParseError::InvalidCharacter { character: '~' }
+            })
+        );
+        assert_eq!(
+            parse(">="),
+            Err(VersionReqParseError::InvalidVersion {
+                index: 0,
+                source: VersionParseError::Empty
+            })
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let mut v = Version::new(1, 6, 1170, 0);
+        assert_eq!(
+            (0..4).map(|idx| v.get(idx)).collect::<Vec<_>>(),
+            [Some(1), Some(6), Some(1170), Some(0)]
+        );
+        assert_eq!(v.get(4), None);
+        assert_eq!(v.get(usize::MAX), None);
+
+        *v.get_mut(3).unwrap() = 1;
+        assert_eq!(v, Version::new(1, 6, 1170, 1));
+        assert_eq!(v.get_mut(4), None);
+    }
+
+    /// Pins the packed layout to SKSE's `MAKE_EXE_VERSION_EX`(same as C++ `REL::Version::pack`),
+    /// since packed versions are exchanged with SKSE. (e.g. `SKSEPlugin_Version`, `QueryInterface::RuntimeVersion`)
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_pack_matches_skse() {
+        use crate::skse::version::{
+            RUNTIME_SSE_1_1_47, RUNTIME_SSE_1_5_97, RUNTIME_SSE_1_6_1170, RUNTIME_SSE_1_6_317,
+            RUNTIME_SSE_1_6_640, RUNTIME_VR_1_4_15,
+        };
+
+        let expected = [
+            (Version::new(1, 2, 3, 4), 0x0102_0034), // 16908340
+            (RUNTIME_SSE_1_1_47, 0x0101_02F0),
+            (RUNTIME_SSE_1_5_97, 0x0105_0610),
+            (RUNTIME_SSE_1_6_317, 0x0106_13D0),
+            (RUNTIME_SSE_1_6_640, 0x0106_2800),
+            (RUNTIME_SSE_1_6_1170, 0x0106_4920),
+            (RUNTIME_VR_1_4_15, 0x0104_00F0),
+        ];
+        for (version, packed) in expected {
+            assert_eq!(version.pack(), packed, "{version}");
+            assert_eq!(version.pack_checked(), Ok(packed), "{version}");
+            assert_eq!(Version::unpack(packed), version);
+        }
+    }
+
+    #[test]
+    fn test_pack_checked() {
+        let v = Version::new(1, 6, 1170, 0);
+        assert_eq!(v.pack_checked(), Ok(v.pack()));
+        assert_eq!(Version::unpack(v.pack()), v);
+
+        let max = Version::new(0xFF, 0xFF, 0xFFF, 0xF);
+        assert_eq!(max.pack_checked(), Ok(u32::MAX));
+    }
+
+    #[test]
+    fn test_pack_checked_overflow() {
+        let v = Version::new(1, 6, 5000, 0);
+        assert_eq!(
+            v.pack_checked(),
+            Err(PackOverflow {
+                component: VersionComponent::Patch,
+                value: 5000
+            })
+        );
+        // The lossy form collides with another version.
+        assert_eq!(v.pack(), Version::new(1, 6, 5000 & 0xFFF, 0).pack());
+
+        assert_eq!(
+            Version::new(1, 6, 1170, 16).pack_checked(),
+            Err(PackOverflow {
+                component: VersionComponent::Build,
+                value: 16
+            })
+        );
+        assert_eq!(
+            Version::new(256, 0, 0, 0).pack_checked(),
+            Err(PackOverflow {
+                component: VersionComponent::Major,
+                value: 256
+            })
+        );
+    }
 }
@@ -0,0 +1,115 @@
+//! Common read-only interface of [`Version`].
+//!
+//! Depending on the `no_sys` feature, [`Version`] is either the hand-written struct or the
+//! bindgen-generated `sys::REL::Version`, whose method sets differ.
+//! Generic code written against [`ReadableVersion`] works with whichever one is selected.
+
+use super::Version;
+
+/// Read-only accessors shared by all representations of a 4-part version.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::version::ReadableVersion;
+///
+/// fn is_ae<V: ReadableVersion>(version: &V) -> bool {
+///     version.major() == 1 && version.minor() >= 6 && version.patch() >= 317
+/// }
+/// ```
+pub trait ReadableVersion {
+    /// Returns the major version component.
+    fn major(&self) -> u16;
+
+    /// Returns the minor version component.
+    fn minor(&self) -> u16;
+
+    /// Returns the patch version component.
+    fn patch(&self) -> u16;
+
+    /// Returns the build version component.
+    fn build(&self) -> u16;
+
+    /// Packs the version into a 32-bit integer. (major: 8bits, minor: 8bits, patch: 12bits, build: 4bits)
+    ///
+    /// This is lossy: bits that do not fit are silently masked out.
+    #[inline]
+    fn pack(&self) -> u32 {
+        ((self.major() as u32 & 0xFF) << 24)
+            | ((self.minor() as u32 & 0xFF) << 16)
+            | ((self.patch() as u32 & 0xFFF) << 4)
+            | (self.build() as u32 & 0xF)
+    }
+}
+
+#[cfg(any(feature = "no_sys", not(feature = "std")))]
+impl ReadableVersion for Version {
+    #[inline]
+    fn major(&self) -> u16 {
+        Self::major(self)
+    }
+
+    #[inline]
+    fn minor(&self) -> u16 {
+        Self::minor(self)
+    }
+
+    #[inline]
+    fn patch(&self) -> u16 {
+        Self::patch(self)
+    }
+
+    #[inline]
+    fn build(&self) -> u16 {
+        Self::build(self)
+    }
+
+    #[inline]
+    fn pack(&self) -> u32 {
+        Self::pack(self)
+    }
+}
+
+// `sys::REL::Version` only has the raw `_impl` field.
+#[cfg(all(feature = "std", not(feature = "no_sys")))]
+impl ReadableVersion for Version {
+    #[inline]
+    fn major(&self) -> u16 {
+        self._impl[0]
+    }
+
+    #[inline]
+    fn minor(&self) -> u16 {
+        self._impl[1]
+    }
+
+    #[inline]
+    fn patch(&self) -> u16 {
+        self._impl[2]
+    }
+
+    #[inline]
+    fn build(&self) -> u16 {
+        self._impl[3]
+    }
+}
+
+#[cfg(all(test, any(feature = "no_sys", not(feature = "std"))))]
+mod tests {
+    use super::*;
+
+    fn components<V: ReadableVersion>(version: &V) -> [u16; 4] {
+        [
+            version.major(),
+            version.minor(),
+            version.patch(),
+            version.build(),
+        ]
+    }
+
+    #[test]
+    fn test_readable_version() {
+        let version = Version::new(1, 6, 1170, 0);
+        assert_eq!(components(&version), [1, 6, 1170, 0]);
+        assert_eq!(ReadableVersion::pack(&version), version.pack());
+    }
+}
@@ -0,0 +1,174 @@
+//! Version requirement parsed from a string like `">=1.6.640, <1.7"`.
+
+use super::{Version, VersionParseError};
+use core::ops::{Bound, RangeBounds};
+
+/// A range of [`Version`]s that a feature requires, e.g. "AE 1.6.640 or later, but before 1.7".
+///
+/// The requirement is a comma separated list of comparators, all of which must be satisfied.
+/// Each comparator is an operator(`>=`, `>`, `<=`, `<`, `=`) followed by a version.
+/// A version without an operator means `=`, and `*` matches any version.
+///
+/// A partial version is filled with `0`, so `<1.7` means `< 1.7.0.0`.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::version::{Version, VersionReq};
+///
+/// let req: VersionReq = ">=1.6.640, <1.7".parse().unwrap();
+///
+/// assert!(req.matches(&Version::new(1, 6, 640, 0)));
+/// assert!(req.matches(&Version::new(1, 6, 1170, 0)));
+/// assert!(!req.matches(&Version::new(1, 5, 97, 0)));
+/// assert!(!req.matches(&Version::new(1, 7, 0, 0)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionReq {
+    lower: Bound<Version>,
+    upper: Bound<Version>,
+}
+
+impl VersionReq {
+    /// A requirement that matches any version.
+    pub const ANY: Self = Self {
+        lower: Bound::Unbounded,
+        upper: Bound::Unbounded,
+    };
+
+    /// Returns `true` if `version` satisfies all comparators.
+    #[inline]
+    pub fn matches(&self, version: &Version) -> bool {
+        self.contains(version)
+    }
+
+    /// Narrows the lower bound if `bound` is stricter than the current one.
+    fn restrict_lower(&mut self, bound: Bound<Version>) {
+        let is_stricter = match (&self.lower, &bound) {
+            (_, Bound::Unbounded) => false,
+            (Bound::Unbounded, _) => true,
+            // An exclusive bound at the same version is stricter than an inclusive one.
+            (Bound::Included(cur), Bound::Excluded(new)) => new >= cur,
+            (
+                Bound::Included(cur) | Bound::Excluded(cur),
+                Bound::Included(new) | Bound::Excluded(new),
+            ) => new > cur,
+        };
+        if is_stricter {
+            self.lower = bound;
+        }
+    }
+
+    /// Narrows the upper bound if `bound` is stricter than the current one.
+    fn restrict_upper(&mut self, bound: Bound<Version>) {
+        let is_stricter = match (&self.upper, &bound) {
+            (_, Bound::Unbounded) => false,
+            (Bound::Unbounded, _) => true,
+            // An exclusive bound at the same version is stricter than an inclusive one.
+            (Bound::Included(cur), Bound::Excluded(new)) => new <= cur,
+            (
+                Bound::Included(cur) | Bound::Excluded(cur),
+                Bound::Included(new) | Bound::Excluded(new),
+            ) => new < cur,
+        };
+        if is_stricter {
+            self.upper = bound;
+        }
+    }
+}
+
+impl Default for VersionReq {
+    #[inline]
+    fn default() -> Self {
+        Self::ANY
+    }
+}
+
+impl RangeBounds<Version> for VersionReq {
+    #[inline]
+    fn start_bound(&self) -> Bound<&Version> {
+        self.lower.as_ref()
+    }
+
+    #[inline]
+    fn end_bound(&self) -> Bound<&Version> {
+        self.upper.as_ref()
+    }
+}
+
+impl core::str::FromStr for VersionReq {
+    type Err = VersionReqParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut req = Self::ANY;
+
+        for (index, comparator) in s.split(',').enumerate() {
+            let comparator = comparator.trim();
+            if comparator.is_empty() {
+                return Err(VersionReqParseError::EmptyComparator { index });
+            }
+            if comparator == "*" {
+                continue;
+            }
+
+            // NOTE: The 2 chars operators must be tried first.
+            let (op, version) = [">=", "<=", ">", "<", "="]
+                .into_iter()
+                .find_map(|op| comparator.strip_prefix(op).map(|rest| (op, rest)))
+                .unwrap_or(("=", comparator));
+            let version = Version::const_from_str(version.trim_start())
+                .map_err(|source| VersionReqParseError::InvalidVersion { index, source })?;
+
+            match op {
+                ">=" => req.restrict_lower(Bound::Included(version)),
+                ">" => req.restrict_lower(Bound::Excluded(version)),
+                "<=" => req.restrict_upper(Bound::Included(version)),
+                "<" => req.restrict_upper(Bound::Excluded(version)),
+                _ => {
+                    req.restrict_lower(Bound::Included(version.clone()));
+                    req.restrict_upper(Bound::Included(version));
+                }
+            }
+        }
+
+        Ok(req)
+    }
+}
+
+impl core::fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (&self.lower, &self.upper) {
+            (Bound::Unbounded, Bound::Unbounded) => return f.write_str("*"),
+            (Bound::Included(lower), Bound::Included(upper)) if lower == upper => {
+                return write!(f, "={lower}");
+            }
+            _ => {}
+        }
+
+        match &self.lower {
+            Bound::Included(lower) => write!(f, ">={lower}")?,
+            Bound::Excluded(lower) => write!(f, ">{lower}")?,
+            Bound::Unbounded => {}
+        }
+        if !matches!(self.lower, Bound::Unbounded) && !matches!(self.upper, Bound::Unbounded) {
+            f.write_str(", ")?;
+        }
+        match &self.upper {
+            Bound::Included(upper) => write!(f, "<={upper}"),
+            Bound::Excluded(upper) => write!(f, "<{upper}"),
+            Bound::Unbounded => Ok(()),
+        }
+    }
+}
+
+/// Error returned by parsing a [`VersionReq`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, snafu::Snafu)]
+pub enum VersionReqParseError {
+    /// Expected a comparator, but comparator {index} is empty
+    EmptyComparator { index: usize },
+
+    /// Invalid version in comparator {index}: {source}
+    InvalidVersion {
+        index: usize,
+        source: VersionParseError,
+    },
+}
@@ -72,8 +72,8 @@ pub fn get_file_version(filename: &windows::core::HSTRING) -> Result<Version, Fi
 
     let mut version = Version::const_default();
     for (i, token) in ver_str.split('.').take(4).enumerate() {
-        if let Ok(num) = token.parse::<u16>() {
-            version[i] = num;
+        if let (Some(part), Ok(num)) = (version.get_mut(i), token.parse::<u16>()) {
+            *part = num;
         }
     }
 
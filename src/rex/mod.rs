@@ -1,2 +1,3 @@
 //! Module related to Win32 API
+pub mod time;
 pub mod ularge_integer;
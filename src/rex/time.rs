@@ -0,0 +1,109 @@
+//! Helpers for Windows time representations.
+
+use super::ularge_integer::ULargeInteger;
+
+/// A `FILETIME` value, i.e. the number of 100-nanosecond ticks since 1601-01-01 (UTC).
+///
+/// `FILETIME` is split into `dwHighDateTime`/`dwLowDateTime`, and is combined through `ULARGE_INTEGER` in Windows API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct FileTime(ULargeInteger);
+
+impl FileTime {
+    /// Number of 100-nanosecond ticks per second.
+    pub const TICKS_PER_SECOND: u64 = 10_000_000;
+
+    /// Seconds from 1601-01-01 to the Unix epoch(1970-01-01).
+    pub const UNIX_EPOCH_SECONDS: i64 = 11_644_473_600;
+
+    /// Creates a new `FileTime` from `dwHighDateTime` and `dwLowDateTime` of `FILETIME`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use commonlibsse_ng::rex::time::FileTime;
+    ///
+    /// let time = FileTime::from_filetime(0x01D9_1D5E, 0x4A5A_B000);
+    /// assert_eq!(time.as_u64_ticks(), 0x01D9_1D5E_4A5A_B000);
+    /// ```
+    #[inline]
+    pub const fn from_filetime(high: u32, low: u32) -> Self {
+        Self(ULargeInteger::from_split(high, low))
+    }
+
+    /// Creates a new `FileTime` from 100-nanosecond ticks since 1601-01-01.
+    #[inline]
+    pub const fn from_ticks(ticks: u64) -> Self {
+        Self(ULargeInteger::new(ticks))
+    }
+
+    /// Returns the 100-nanosecond ticks since 1601-01-01.
+    #[inline]
+    pub const fn as_u64_ticks(&self) -> u64 {
+        self.0.to_u64()
+    }
+
+    /// Returns `(dwHighDateTime, dwLowDateTime)` to write back into `FILETIME`.
+    #[inline]
+    pub const fn to_filetime(&self) -> (u32, u32) {
+        self.0.split()
+    }
+
+    /// Returns the seconds since the Unix epoch. (Sub-second ticks are truncated.)
+    ///
+    /// Times before 1970-01-01 are negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use commonlibsse_ng::rex::time::FileTime;
+    ///
+    /// let epoch = FileTime::from_ticks(116_444_736_000_000_000);
+    /// assert_eq!(epoch.to_unix_seconds(), 0);
+    /// ```
+    #[inline]
+    pub const fn to_unix_seconds(&self) -> i64 {
+        // `u64::MAX / TICKS_PER_SECOND` always fits in `i64`.
+        (self.as_u64_ticks() / Self::TICKS_PER_SECOND) as i64 - Self::UNIX_EPOCH_SECONDS
+    }
+}
+
+impl From<ULargeInteger> for FileTime {
+    #[inline]
+    fn from(value: ULargeInteger) -> Self {
+        Self(value)
+    }
+}
+
+impl From<FileTime> for ULargeInteger {
+    #[inline]
+    fn from(value: FileTime) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileTime;
+
+    #[test]
+    fn test_from_filetime() {
+        let time = FileTime::from_filetime(0x1234_5678, 0x9ABC_DEF0);
+        assert_eq!(time.as_u64_ticks(), 0x1234_5678_9ABC_DEF0);
+        assert_eq!(time.to_filetime(), (0x1234_5678, 0x9ABC_DEF0));
+    }
+
+    #[test]
+    fn test_to_unix_seconds() {
+        assert_eq!(FileTime::from_ticks(0).to_unix_seconds(), -11_644_473_600);
+
+        // 2023-01-01T00:00:00Z
+        let ticks = (1_672_531_200 + 11_644_473_600) * FileTime::TICKS_PER_SECOND;
+        assert_eq!(FileTime::from_ticks(ticks).to_unix_seconds(), 1_672_531_200);
+        // Sub-second ticks are truncated.
+        assert_eq!(
+            FileTime::from_ticks(ticks + 9_999_999).to_unix_seconds(),
+            1_672_531_200
+        );
+    }
+}
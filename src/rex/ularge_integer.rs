@@ -20,6 +20,21 @@ impl ULargeInteger {
         Self(value)
     }
 
+    /// Creates a new `ULargeInteger` from the `high` and `low` 32-bit parts. (The inverse of [`Self::split`])
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use commonlibsse_ng::rex::ularge_integer::ULargeInteger;
+    ///
+    /// let uli = ULargeInteger::from_split(0x1234_5678, 0x9ABC_DEF0);
+    /// assert_eq!(uli.to_u64(), 0x1234_5678_9ABC_DEF0);
+    /// ```
+    #[inline]
+    pub const fn from_split(high: u32, low: u32) -> Self {
+        Self(((high as u64) << 32) | low as u64)
+    }
+
     /// Returns the higher 32 bits as a [`u32`].
     ///
     /// # Example
@@ -86,6 +101,27 @@ impl ULargeInteger {
     }
 }
 
+// Conversions for Win32 interop.
+//
+// NOTE: The `windows` crate has no `ULARGE_INTEGER` union type. Its APIs take `ULARGE_INTEGER` as `u64`
+//       (the union's `QuadPart`), so these conversions are all that is needed, and stay OS independent.
+
+impl From<u64> for ULargeInteger {
+    /// Same as [`ULargeInteger::new`]. (e.g. a value returned by a Win32 API through `*mut u64`)
+    #[inline]
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<ULargeInteger> for u64 {
+    /// Same as [`ULargeInteger::to_u64`]. (i.e. the `QuadPart` to pass to a Win32 API)
+    #[inline]
+    fn from(value: ULargeInteger) -> Self {
+        value.to_u64()
+    }
+}
+
 // impl  arithmetic operations for `ULargeInteger`.
 
 impl core::ops::Add for ULargeInteger {
@@ -213,6 +249,19 @@ mod tests {
         assert_eq!(uli.split(), (0x1234_5678, 0x9ABC_DEF0));
     }
 
+    #[test]
+    fn test_from_split() {
+        let uli = ULargeInteger::from_split(0x1234_5678, 0x9ABC_DEF0);
+        assert_eq!(uli.split(), (0x1234_5678, 0x9ABC_DEF0));
+    }
+
+    #[test]
+    fn test_u64_conversions() {
+        let uli = ULargeInteger::from(0x1234_5678_9ABC_DEF0_u64);
+        assert_eq!(uli.split(), (0x1234_5678, 0x9ABC_DEF0));
+        assert_eq!(u64::from(uli), 0x1234_5678_9ABC_DEF0);
+    }
+
     #[test]
     fn test_add() {
         let a = ULargeInteger::new(10);
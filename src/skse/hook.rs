@@ -0,0 +1,337 @@
+//! Function detours built on the SKSE trampoline.
+
+use crate::rel::pattern::Pattern;
+use crate::rel::relocation::{patch_batch, safe_write, Relocation, JMP32, NOP};
+use crate::sys::root::SKSE;
+
+/// Size of `E9 rel32` written at the hooked address.
+const REL32_BRANCH_SIZE: usize = 5;
+
+/// Serializes the allocation from `SKSE::GetTrampoline()`, which is a plain global without any lock.
+static TRAMPOLINE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Redirects the function at `reloc` to `dst` and returns a callable to the original function.
+///
+/// The first `stolen_len` bytes at `reloc` are copied into the SKSE trampoline(allocated by `SKSE::AllocTrampoline`)
+/// followed by a jump back, and `reloc` is overwritten through the protection-aware write path with a jump to `dst`.
+///
+/// To refuse hooking an unexpected function(e.g. on an unanticipated game build), check the prologue by
+/// [`Relocation::verify_pattern`] first.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::RelocationID;
+/// use commonlibsse_ng::rel::relocation::Relocation;
+/// use commonlibsse_ng::skse::hook::install_detour;
+///
+/// type Update = unsafe extern "C" fn(this: *mut core::ffi::c_void, delta: f32);
+/// static mut ORIGINAL: Option<Update> = None;
+///
+/// unsafe extern "C" fn hook(this: *mut core::ffi::c_void, delta: f32) {
+///     if let Some(original) = unsafe { ORIGINAL } {
+///         unsafe { original(this, delta) };
+///     }
+/// }
+///
+/// let reloc = Relocation::<usize>::try_from(RelocationID::new(35565, 36564, 35565)).unwrap();
+/// // `stolen_len` is the length of the whole instructions covering the first 5 bytes. (e.g. `sub rsp, 0x28` + `mov rax, rcx`)
+/// unsafe { ORIGINAL = Some(install_detour::<_, Update>(&reloc, hook as usize, 7).unwrap()) };
+/// ```
+///
+/// # Errors
+/// - If the SKSE trampoline has not been allocated or is out of capacity.
+/// - If `stolen_len` is less than 5 or the address of `reloc` is null.
+/// - If the trampoline is out of `rel32` range from `reloc`.
+/// - If the protection of `reloc` could not be changed.
+///
+/// # Safety
+/// - `stolen_len` must end on an instruction boundary, and the stolen instructions must be position independent.
+///   (No `rip`-relative operands nor relative branches, since they are executed at the trampoline.)
+///   Instructions are not decoded here, so this is the caller's responsibility.
+/// - No other thread may execute the stolen bytes while writing.
+/// - `F` must be a function pointer type with the same signature and ABI as the hooked function.
+/// - Concurrent calls of this are serialized, but nothing else may use `SKSE::GetTrampoline()` at the same time.
+///   (e.g. allocating from it on another thread through the C++ API)
+pub unsafe fn install_detour<T, F>(
+    reloc: &Relocation<T>,
+    dst: usize,
+    stolen_len: usize,
+) -> Result<F, HookError>
+where
+    F: Copy,
+{
+    const { assert!(core::mem::size_of::<F>() == core::mem::size_of::<usize>()) };
+
+    let _lock = TRAMPOLINE_LOCK
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    // SAFETY: `GetTrampoline` returns the process-wide trampoline of this plugin.
+    //         The lock above and the caller guarantee that no other `&mut` to it exists.
+    let trampoline = unsafe { SKSE::GetTrampoline().as_mut() }.ok_or(HookError::NoTrampoline)?;
+    let original = unsafe { install_detour_with(trampoline, reloc.address(), dst, stolen_len) }?;
+
+    // SAFETY: The size is checked above, and the caller guarantees that `F` is a function pointer type.
+    Ok(unsafe { core::mem::transmute_copy::<usize, F>(&original) })
+}
+
+/// Writes the detour using `trampoline` and returns the address of the original function(gateway).
+///
+/// The trampoline layout is as follows.
+/// - gateway: stolen bytes + absolute jump to `src + stolen_len`
+/// - relay: absolute jump to `dst`(which may be out of `rel32` range from `src`)
+///
+/// The trampoline is consumed only if the hook is installed. (Not on a range error nor a write failure)
+///
+/// # Safety
+/// Same as [`install_detour`].
+unsafe fn install_detour_with(
+    trampoline: &mut SKSE::Trampoline,
+    src: usize,
+    dst: usize,
+    stolen_len: usize,
+) -> Result<usize, HookError> {
+    if src == 0 {
+        return Err(HookError::NullAddress);
+    }
+    if stolen_len < REL32_BRANCH_SIZE {
+        return Err(HookError::TooShort { stolen_len });
+    }
+
+    let gateway_size = stolen_len + SKSE::Trampoline::ABS_BRANCH_SIZE;
+    let size = gateway_size + SKSE::Trampoline::ABS_BRANCH_SIZE;
+    let gateway = trampoline
+        .peek_allocate(size)
+        .ok_or(HookError::OutOfCapacity { size })?;
+    let relay = gateway + gateway_size;
+
+    let displacement =
+        i32::try_from(relay as isize - (src + REL32_BRANCH_SIZE) as isize).map_err(|_| {
+            HookError::OutOfRange {
+                src,
+                trampoline: relay,
+            }
+        })?;
+
+    unsafe {
+        core::ptr::copy_nonoverlapping(src as *const u8, gateway as *mut u8, stolen_len);
+        SKSE::Trampoline::write_branch::<6>(gateway + stolen_len, src + stolen_len);
+        SKSE::Trampoline::write_branch::<6>(relay, dst);
+    }
+
+    // Pad the rest of the stolen instructions so that disassemblers do not show garbage.
+    let mut patch = vec![NOP; stolen_len];
+    patch[0] = JMP32;
+    patch[1..REL32_BRANCH_SIZE].copy_from_slice(&displacement.to_le_bytes());
+    unsafe { safe_write(src as *mut u8, patch.as_ptr(), patch.len()) }
+        .map_err(|source| HookError::Write { source })?;
+    flush_instruction_cache(src, stolen_len);
+
+    // Commit the space written above. This never fails, since `peek_allocate` succeeded with the same `&mut`.
+    let _ = trampoline.allocate(size);
+    Ok(gateway)
+}
+
+/// Writes `data` at `reloc` only if the original bytes there match `expected`.
+///
+/// This refuses to clobber the code of an unanticipated game build, e.g. when an address library ID points to
+/// a different function.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::id::RelocationID;
+/// use commonlibsse_ng::rel::relocation::{Relocation, NOP2};
+/// use commonlibsse_ng::skse::hook::patch_verified;
+///
+/// let reloc = Relocation::<usize>::try_from(RelocationID::new(35565, 36564, 35565)).unwrap();
+/// // Replace `jz short ??` with NOPs.
+/// unsafe { patch_verified(&reloc, &"74 ??".parse().unwrap(), &NOP2) }.unwrap();
+/// ```
+///
+/// # Errors
+/// Same as [`patch_batch_verified`].
+///
+/// # Safety
+/// Same as [`patch_batch_verified`].
+pub unsafe fn patch_verified<T>(
+    reloc: &Relocation<T>,
+    expected: &Pattern,
+    data: &[u8],
+) -> Result<(), HookError> {
+    unsafe { patch_batch_verified(&[(reloc.address(), expected, data)]) }
+}
+
+/// [`patch_batch`] that writes only if the original bytes of every `(address, expected, data)` match `expected`.
+///
+/// All patches are verified before writing anything, so a mismatch leaves the code untouched.
+///
+/// # Errors
+/// - If any address is null.
+/// - [`HookError::OriginalBytesMismatch`] if the original bytes of any patch do not match.
+/// - If the page protection could not be changed.
+///
+/// # Safety
+/// Every address must point to committed memory of `max(expected.len(), data.len())` bytes
+/// that no other thread executes or accesses while writing.
+pub unsafe fn patch_batch_verified(patches: &[(usize, &Pattern, &[u8])]) -> Result<(), HookError> {
+    for &(address, expected, _) in patches {
+        if address == 0 {
+            return Err(HookError::NullAddress);
+        }
+        // SAFETY: The caller guarantees that `expected.len()` bytes from the address are committed.
+        if !unsafe { Relocation::<u8>::new(address).verify_pattern(expected) } {
+            return Err(HookError::OriginalBytesMismatch {
+                address,
+                expected: expected.clone(),
+            });
+        }
+    }
+
+    let patches: Vec<_> = patches
+        .iter()
+        .map(|&(address, _, data)| (address, data))
+        .collect();
+    unsafe { patch_batch(&patches) }.map_err(|source| HookError::Write { source })?;
+    for &(address, data) in &patches {
+        flush_instruction_cache(address, data.len());
+    }
+    Ok(())
+}
+
+fn flush_instruction_cache(address: usize, len: usize) {
+    use windows::Win32::System::Diagnostics::Debug::FlushInstructionCache;
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    // FlushInstructionCache: https://learn.microsoft.com/windows/win32/api/processthreadsapi/nf-processthreadsapi-flushinstructioncache
+    // x64 has a coherent instruction cache, so this is only a formality and its failure is harmless.
+    let _ = unsafe { FlushInstructionCache(GetCurrentProcess(), Some(address as _), len) };
+}
+
+/// Error types for [`install_detour`] and [`patch_batch_verified`].
+#[derive(Debug, Clone, PartialEq, Eq, snafu::Snafu)]
+pub enum HookError {
+    /// The SKSE trampoline has not been allocated. Call `SKSE::AllocTrampoline` first.
+    NoTrampoline,
+
+    /// The address to hook is null.
+    NullAddress,
+
+    /// Expected at least 5 bytes to steal for `jmp rel32`, but got {stolen_len}
+    TooShort { stolen_len: usize },
+
+    /// The trampoline has no capacity left for {size} bytes
+    OutOfCapacity { size: usize },
+
+    /// The trampoline at {trampoline:#x} is out of `rel32` range from {src:#x}
+    OutOfRange { src: usize, trampoline: usize },
+
+    /// The original bytes at {address:#x} do not match `{expected}`
+    OriginalBytesMismatch { address: usize, expected: Pattern },
+
+    /// Failed to write the branch: {source}
+    Write { source: windows::core::Error },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_detour() {
+        const DST: usize = 0x1122_3344_5566_7788;
+        const STOLEN: [u8; 7] = [0x48, 0x83, 0xEC, 0x28, 0x48, 0x8B, 0xC1]; // sub rsp, 0x28; mov rax, rcx
+
+        // The first 16 bytes are the hooked function, and the rest is the trampoline.
+        let mut buf = vec![0xCC_u8; 16 + 64];
+        buf[..STOLEN.len()].copy_from_slice(&STOLEN);
+        let src = buf.as_mut_ptr() as usize;
+
+        // SAFETY: All fields of the bindgen struct are valid when zeroed.
+        let mut trampoline: SKSE::Trampoline = unsafe { core::mem::zeroed() };
+        trampoline._data = unsafe { buf.as_mut_ptr().add(16) }.cast();
+        trampoline._capacity = 64;
+
+        let gateway = unsafe { install_detour_with(&mut trampoline, src, DST, STOLEN.len()) }
+            .unwrap_or_else(|err| panic!("{err}"));
+        assert_eq!(gateway, src + 16);
+
+        // jmp rel32 to the relay + NOP padding
+        let relay = gateway + STOLEN.len() + SKSE::Trampoline::ABS_BRANCH_SIZE;
+        assert_eq!(buf[0], JMP32);
+        let displacement = i32::from_le_bytes(buf[1..5].try_into().unwrap());
+        assert_eq!((src + 5).wrapping_add_signed(displacement as isize), relay);
+        assert_eq!(buf[5..7], [NOP, NOP]);
+
+        // gateway: stolen bytes + jmp back
+        let gateway = &buf[16..16 + STOLEN.len() + 14];
+        assert_eq!(gateway[..STOLEN.len()], STOLEN);
+        assert_eq!(gateway[STOLEN.len()..][..2], [0xFF, 0x25]);
+        let back = usize::from_le_bytes(gateway[STOLEN.len() + 6..].try_into().unwrap());
+        assert_eq!(back, src + STOLEN.len());
+
+        // relay: jmp to the detour
+        let relay = &buf[16 + STOLEN.len() + 14..][..14];
+        assert_eq!(usize::from_le_bytes(relay[6..].try_into().unwrap()), DST);
+    }
+
+    #[test]
+    fn test_patch_batch_verified() {
+        let mut code = vec![0x74_u8, 0x05, 0x48, 0x8B];
+        let address = code.as_mut_ptr() as usize;
+        let jz: Pattern = "74 ??".parse().unwrap();
+        let mov: Pattern = "48 8B".parse().unwrap();
+
+        // One mismatch refuses all patches.
+        assert_eq!(
+            unsafe {
+                patch_batch_verified(&[(address, &jz, &[NOP, NOP]), (address + 2, &jz, &[NOP])])
+            },
+            Err(HookError::OriginalBytesMismatch {
+                address: address + 2,
+                expected: jz.clone()
+            })
+        );
+        assert_eq!(code, [0x74, 0x05, 0x48, 0x8B]);
+
+        unsafe {
+            patch_batch_verified(&[(address, &jz, &[NOP, NOP]), (address + 2, &mov, &[0x90])])
+        }
+        .unwrap();
+        assert_eq!(code, [NOP, NOP, 0x90, 0x8B]);
+
+        assert_eq!(
+            unsafe { patch_batch_verified(&[(0, &jz, &[NOP])]) },
+            Err(HookError::NullAddress)
+        );
+    }
+
+    #[test]
+    fn test_install_detour_out_of_range() {
+        let mut buf = vec![0xCC_u8; 64];
+        let mut trampoline: SKSE::Trampoline = unsafe { core::mem::zeroed() };
+        trampoline._data = buf.as_mut_ptr().cast();
+        trampoline._capacity = 64;
+
+        // Never read, since the range is checked first.
+        let src = buf.as_ptr() as usize + 0x1_0000_0000;
+        let relay = buf.as_ptr() as usize + 7 + SKSE::Trampoline::ABS_BRANCH_SIZE;
+        assert_eq!(
+            unsafe { install_detour_with(&mut trampoline, src, 0x2000, 7) },
+            Err(HookError::OutOfRange {
+                src,
+                trampoline: relay
+            })
+        );
+        // The trampoline is not consumed.
+        assert_eq!(trampoline._size, 0);
+        assert!(buf.iter().all(|&byte| byte == 0xCC));
+    }
+
+    #[test]
+    fn test_install_detour_too_short() {
+        let mut trampoline: SKSE::Trampoline = unsafe { core::mem::zeroed() };
+        assert_eq!(
+            unsafe { install_detour_with(&mut trampoline, 0x1000, 0x2000, 4) },
+            Err(HookError::TooShort { stolen_len: 4 })
+        );
+    }
+}
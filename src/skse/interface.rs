@@ -2,7 +2,32 @@
 // SPDX-License-Identifier: MIT OR CC-BY-NC-SA-4.0
 //
 // See: https://gitlab.com/metricexpansion/SkyrimOutfitSystemSE/-/issues/2#note_2332635556
+use crate::rel::module::Runtime;
 use crate::sys::root::{__BindgenBitfieldUnit, SKSE};
+use core::ffi::c_char;
+
+/// Returns whether the SKSE loader of `runtime` rejects a plugin built for another game version
+/// unless it declares address library independence. (See [`SKSE::PluginVersionData::set_address_library_independent`])
+///
+/// - AE(1.6.317+): SKSE reads `SKSEPlugin_Version`, and loads the plugin only if it is address library(or signature scanning)
+///   independent, or `compatibleVersions` contains the running version.
+/// - SE/VR: SKSE calls `SKSEPlugin_Query`, and the version check is left to the plugin.
+///
+/// This does not tell whether the plugin needs the address library itself:
+/// a plugin resolving addresses by [`ID`](crate::rel::id::ID) needs it on every runtime, including VR.
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::rel::module::Runtime;
+/// use commonlibsse_ng::skse::requires_version_independence_declaration;
+///
+/// assert!(requires_version_independence_declaration(Runtime::Ae));
+/// assert!(!requires_version_independence_declaration(Runtime::Se));
+/// ```
+#[inline]
+pub fn requires_version_independence_declaration(runtime: Runtime) -> bool {
+    runtime.is_ae()
+}
 
 impl SKSE::PluginVersionData {
     /// Create a new `SKSEPluginVersionData` at compile time.
@@ -25,6 +50,61 @@ impl SKSE::PluginVersionData {
             xseMinimum: 0,
         }
     }
+
+    /// Creates the version data of a plugin at compile time, as exported by [`declare_plugin!`](crate::declare_plugin).
+    ///
+    /// The plugin is declared to be address library independent and to use the struct layout of 1.6.629+,
+    /// which is what a plugin resolving addresses by [`crate::rel::id::ID`] needs to be loaded by the AE SKSE.
+    ///
+    /// `name` and `author` longer than 255 bytes are truncated.
+    ///
+    /// # Example
+    /// ```
+    /// use commonlibsse_ng::rel::version::Version;
+    /// use commonlibsse_ng::sys::root::SKSE;
+    ///
+    /// static VERSION: SKSE::PluginVersionData =
+    ///     SKSE::PluginVersionData::new("MyPlugin", "me", &Version::new(1, 0, 0, 0));
+    /// assert!(VERSION.is_address_library_independent());
+    /// ```
+    pub const fn new(name: &str, author: &str, version: &crate::rel::version::Version) -> Self {
+        let mut data = Self::const_default();
+        data.pluginVersion = version.pack();
+        data.pluginName = to_c_chars(name);
+        data.author = to_c_chars(author);
+        // bit 0: addressLibrary, bit 2: structsPost629 (The setters are not `const`.)
+        data._bitfield_2 = __BindgenBitfieldUnit::<[u8; 1]>::new([0b101]);
+        data
+    }
+
+    /// Declares that the plugin resolves addresses through the address library(e.g. [`crate::rel::id::ID`]),
+    /// so that it works on any game version for which the address library exists.
+    ///
+    /// A plugin with static offsets for a single game version must leave this `false`
+    /// and list its version in `compatibleVersions` instead.
+    #[inline]
+    pub fn set_address_library_independent(&mut self, value: bool) {
+        self.set_addressLibrary(value);
+    }
+
+    /// Returns whether the plugin is declared to be address library independent.
+    /// (See [`Self::set_address_library_independent`])
+    #[inline]
+    pub fn is_address_library_independent(&self) -> bool {
+        self.addressLibrary()
+    }
+}
+
+/// Copies `src` into a NUL-terminated C string buffer, truncating it to `N - 1` bytes.
+const fn to_c_chars<const N: usize>(src: &str) -> [c_char; N] {
+    let bytes = src.as_bytes();
+    let mut buf = [0; N];
+    let mut i = 0;
+    while i < bytes.len() && i < N - 1 {
+        buf[i] = bytes[i] as c_char;
+        i += 1;
+    }
+    buf
 }
 
 impl Default for SKSE::PluginVersionData {
@@ -32,3 +112,42 @@ impl Default for SKSE::PluginVersionData {
         Self::const_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_library_independent() {
+        let mut data = SKSE::PluginVersionData::default();
+        assert!(!data.is_address_library_independent());
+
+        data.set_address_library_independent(true);
+        assert!(data.is_address_library_independent());
+        // Other flags in the same bitfield are untouched.
+        assert!(!data.sigScanning());
+        assert!(!data.structsPost629());
+
+        data.set_address_library_independent(false);
+        assert!(!data.is_address_library_independent());
+    }
+
+    #[test]
+    fn test_new() {
+        use crate::rel::version::Version;
+
+        const DATA: SKSE::PluginVersionData =
+            SKSE::PluginVersionData::new("MyPlugin", "me", &Version::new(1, 2, 3, 0));
+        assert_eq!(DATA.pluginVersion, Version::new(1, 2, 3, 0).pack());
+        assert_eq!(DATA.pluginName[..9], b"MyPlugin\0".map(|b| b as c_char));
+        assert_eq!(DATA.author[..3], b"me\0".map(|b| b as c_char));
+        assert!(DATA.is_address_library_independent());
+        assert!(DATA.structsPost629());
+        assert!(!DATA.sigScanning());
+
+        let long = "a".repeat(300);
+        let data = SKSE::PluginVersionData::new(&long, "", &Version::const_default());
+        assert_eq!(data.pluginName[254], b'a' as c_char);
+        assert_eq!(data.pluginName[255], 0);
+    }
+}
@@ -0,0 +1,279 @@
+//! Safe wrapper of the SKSE messaging interface.
+//!
+//! SKSE calls a plain C function pointer without any user data, so the Rust closures are kept in a
+//! process-wide registry and a single `extern "C"` function dispatches each message to them.
+
+use crate::sys::root::SKSE;
+use core::ffi::{c_void, CStr};
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+
+/// Name of the sender of the messages sent by SKSE itself. (e.g. [`MessageType::DataLoaded`])
+pub const SKSE_SENDER: &str = "SKSE";
+
+type Listener = Arc<dyn Fn(&Message) + Send + Sync>;
+
+/// Registered listeners. `(sender, listener)`
+static LISTENERS: Mutex<Vec<(CString, Listener)>> = Mutex::new(Vec::new());
+
+/// Message types sent by SKSE itself(sender: [`SKSE_SENDER`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+pub enum MessageType {
+    /// All plugins have been loaded.
+    PostLoad = 0,
+    /// All `PostLoad` handlers have run.
+    PostPostLoad = 1,
+    /// Before a save game is loaded. (data: save name)
+    PreLoadGame = 2,
+    /// After a save game is loaded. (data: `bool`, whether it succeeded)
+    PostLoadGame = 3,
+    /// A game is being saved. (data: save name)
+    SaveGame = 4,
+    /// A save game is being deleted. (data: save name)
+    DeleteGame = 5,
+    /// The input devices have been loaded.
+    InputLoaded = 6,
+    /// A new game has started.
+    NewGame = 7,
+    /// All forms have been loaded. (i.e. the main menu is shown)
+    DataLoaded = 8,
+}
+
+impl TryFrom<u32> for MessageType {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::PostLoad,
+            1 => Self::PostPostLoad,
+            2 => Self::PreLoadGame,
+            3 => Self::PostLoadGame,
+            4 => Self::SaveGame,
+            5 => Self::DeleteGame,
+            6 => Self::InputLoaded,
+            7 => Self::NewGame,
+            8 => Self::DataLoaded,
+            unknown => return Err(unknown),
+        })
+    }
+}
+
+/// A message passed to a listener. (Borrowed only during the callback)
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Message(SKSE::MessagingInterface_Message);
+
+impl Message {
+    /// Returns the name of the sender plugin. (`"SKSE"` for the messages of SKSE itself)
+    #[inline]
+    pub fn sender(&self) -> Option<&CStr> {
+        if self.0.sender.is_null() {
+            return None;
+        }
+        // SAFETY: SKSE passes a NUL terminated plugin name that lives during the callback.
+        Some(unsafe { CStr::from_ptr(self.0.sender) })
+    }
+
+    /// Returns the raw message type. Its meaning is defined by the sender.
+    #[inline]
+    pub const fn raw_type(&self) -> u32 {
+        self.0.type_
+    }
+
+    /// Returns the message type if it is sent by SKSE itself.
+    #[inline]
+    pub fn message_type(&self) -> Option<MessageType> {
+        if self.sender()?.to_bytes() != SKSE_SENDER.as_bytes() {
+            return None;
+        }
+        MessageType::try_from(self.0.type_).ok()
+    }
+
+    /// Returns the data attached to the message.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        if self.0.data.is_null() || self.0.dataLen == 0 {
+            return &[];
+        }
+        // SAFETY: The sender guarantees that `data` is valid for `dataLen` bytes during the callback.
+        unsafe { core::slice::from_raw_parts(self.0.data.cast::<u8>(), self.0.dataLen as usize) }
+    }
+
+    /// Returns the raw pointer of the attached data, e.g. to reinterpret a struct defined by the sender.
+    #[inline]
+    pub const fn data_ptr(&self) -> *mut c_void {
+        self.0.data
+    }
+}
+
+/// Registers `callback` to receive the messages sent by `sender`. (e.g. [`SKSE_SENDER`])
+///
+/// Multiple listeners can be registered for the same sender, and they are called in order of registration.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::skse::messaging::{register_listener, MessageType, SKSE_SENDER};
+///
+/// register_listener(SKSE_SENDER, |message| {
+///     if message.message_type() == Some(MessageType::DataLoaded) {
+///         println!("Data loaded");
+///     }
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Errors
+/// - If `sender` contains a NUL character.
+/// - If the SKSE messaging interface is unavailable. (i.e. before the plugin is loaded by SKSE)
+/// - If SKSE refuses the registration. (e.g. `sender` is not loaded)
+pub fn register_listener<F>(sender: &str, callback: F) -> Result<(), MessagingError>
+where
+    F: Fn(&Message) + Send + Sync + 'static,
+{
+    let sender = CString::new(sender).map_err(|_| MessagingError::InvalidSender {
+        sender: sender.to_string(),
+    })?;
+
+    // SAFETY: `GetMessagingInterface` returns null or the interface that lives as long as the process.
+    let interface =
+        unsafe { SKSE::GetMessagingInterface().as_ref() }.ok_or(MessagingError::NoInterface)?;
+
+    // SKSE dispatches to the same function once per registration, so register it only once per sender.
+    let listener: Listener = Arc::new(callback);
+    if !add_listener(sender.clone(), Arc::clone(&listener)) {
+        return Ok(());
+    }
+    // SAFETY: `sender` is NUL terminated, and `dispatch` matches `EventCallback`.
+    if unsafe { interface.RegisterListener1(sender.as_ptr(), Some(dispatch)) } {
+        Ok(())
+    } else {
+        // Only this one, since others for the same sender may have been added by another thread meanwhile.
+        remove_listener(&listener);
+        Err(MessagingError::RegisterFailed {
+            sender: sender.to_string_lossy().into_owned(),
+        })
+    }
+}
+
+/// Adds `listener` to the registry, and returns `true` if it is the first listener for `sender`.
+fn add_listener(sender: CString, listener: Listener) -> bool {
+    let mut listeners = LISTENERS.lock().unwrap_or_else(|err| err.into_inner());
+    let is_first = listeners
+        .iter()
+        .all(|(registered, _)| *registered != sender);
+    listeners.push((sender, listener));
+    is_first
+}
+
+/// Removes `listener` added by [`add_listener`], leaving the other listeners of the same sender.
+fn remove_listener(listener: &Listener) {
+    let mut listeners = LISTENERS.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(index) = listeners
+        .iter()
+        .position(|(_, registered)| Arc::ptr_eq(registered, listener))
+    {
+        listeners.remove(index);
+    }
+}
+
+/// `EventCallback` registered with SKSE for all senders.
+unsafe extern "C" fn dispatch(message: *mut SKSE::MessagingInterface_Message) {
+    // SAFETY: `Message` is `repr(transparent)`, and SKSE passes a valid message during the callback.
+    let Some(message) = (unsafe { message.cast::<Message>().as_ref() }) else {
+        return;
+    };
+    let Some(sender) = message.sender() else {
+        return;
+    };
+
+    // Call them outside the lock so that a listener can register another listener.
+    let listeners: Vec<Listener> = {
+        let listeners = LISTENERS.lock().unwrap_or_else(|err| err.into_inner());
+        listeners
+            .iter()
+            .filter(|(registered, _)| registered.as_c_str() == sender)
+            .map(|(_, listener)| Arc::clone(listener))
+            .collect()
+    };
+    for listener in listeners {
+        listener(message);
+    }
+}
+
+/// Error types for [`register_listener`].
+#[derive(Debug, Clone, PartialEq, Eq, snafu::Snafu)]
+pub enum MessagingError {
+    /// The sender name `{sender}` contains a NUL character.
+    InvalidSender { sender: String },
+
+    /// The SKSE messaging interface is unavailable. The plugin must be loaded by SKSE.
+    NoInterface,
+
+    /// SKSE refused to register a listener for `{sender}`.
+    RegisterFailed { sender: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_dispatch_to_matching_sender() {
+        static RECEIVED: AtomicU32 = AtomicU32::new(0);
+
+        // Use senders that no other test registers.
+        let first: Listener = Arc::new(|message: &Message| {
+            assert_eq!(message.data(), b"abc");
+            RECEIVED.fetch_add(message.raw_type(), Ordering::Relaxed);
+        });
+        let second: Listener = Arc::new(|message: &Message| {
+            RECEIVED.fetch_add(message.raw_type() * 10, Ordering::Relaxed);
+        });
+        let other: Listener = Arc::new(|_: &Message| {
+            RECEIVED.fetch_add(100, Ordering::Relaxed);
+        });
+        assert!(add_listener(c"DispatchTest".into(), Arc::clone(&first)));
+        assert!(!add_listener(c"DispatchTest".into(), Arc::clone(&second)));
+        assert!(add_listener(c"OtherSender".into(), Arc::clone(&other)));
+
+        let mut data = *b"abc";
+        let mut message = SKSE::MessagingInterface_Message {
+            sender: c"DispatchTest".as_ptr(),
+            type_: 2,
+            dataLen: data.len() as u32,
+            data: data.as_mut_ptr().cast(),
+        };
+        unsafe { dispatch(&mut message) };
+        assert_eq!(RECEIVED.load(Ordering::Relaxed), 2 + 20);
+
+        // Removes only the given one, keeping the other listener of the same sender.
+        remove_listener(&second);
+        unsafe { dispatch(&mut message) };
+        assert_eq!(RECEIVED.load(Ordering::Relaxed), (2 + 20) + 2);
+
+        remove_listener(&first);
+        remove_listener(&other);
+        unsafe { dispatch(&mut message) };
+        assert_eq!(RECEIVED.load(Ordering::Relaxed), (2 + 20) + 2);
+    }
+
+    #[test]
+    fn test_message_type() {
+        let message = Message(SKSE::MessagingInterface_Message {
+            sender: c"SKSE".as_ptr(),
+            type_: MessageType::DataLoaded as u32,
+            dataLen: 0,
+            data: core::ptr::null_mut(),
+        });
+        assert_eq!(message.message_type(), Some(MessageType::DataLoaded));
+        assert!(message.data().is_empty());
+
+        let message = Message(SKSE::MessagingInterface_Message {
+            sender: c"OtherPlugin".as_ptr(),
+            ..message.0
+        });
+        assert_eq!(message.message_type(), None);
+    }
+}
@@ -1,10 +1,22 @@
 //! Module related to SKSE.exe (version information of SkyrimSE.exe, etc.)
 
+#[cfg(not(feature = "no_sys"))]
+pub mod hook;
 #[cfg(not(feature = "no_sys"))]
 mod interface;
 #[cfg(not(feature = "no_sys"))]
+pub mod messaging;
+#[cfg(not(feature = "no_sys"))]
+#[doc(hidden)]
+pub mod plugin;
+#[cfg(not(feature = "no_sys"))]
+pub mod serialization;
+#[cfg(not(feature = "no_sys"))]
 mod trampoline;
 #[cfg(not(feature = "no_sys"))]
 mod translation;
 
 pub mod version;
+
+#[cfg(not(feature = "no_sys"))]
+pub use self::interface::requires_version_independence_declaration;
@@ -0,0 +1,120 @@
+//! Implementation details of [`declare_plugin!`](crate::declare_plugin).
+//!
+//! These are public only to be called from the macro expansion in the plugin crate.
+
+use crate::rel::module::Module;
+use crate::sys::root::SKSE;
+
+/// Generates the exports that SKSE looks up in a plugin DLL.
+///
+/// | Symbol               | Used by  | Generated as                                                           |
+/// |----------------------|----------|------------------------------------------------------------------------|
+/// | `SKSEPlugin_Version` | AE       | `static` [`SKSE::PluginVersionData`] built by [`SKSE::PluginVersionData::new`] |
+/// | `SKSEPlugin_Query`   | SE/VR    | `extern "C" fn` filling [`SKSE::PluginInfo`] with the name and version |
+/// | `SKSEPlugin_Load`    | all      | `extern "C" fn` calling the given load function                        |
+///
+/// The arguments are the plugin name(string literal), author(string literal), version([`Version`](crate::rel::version::Version)
+/// in a const expression) and the load function(`fn(&SKSE::LoadInterface) -> bool`, or a non-capturing closure).
+///
+/// Before the load function is called, the interface is passed to
+/// [`Module::set_skse_query_interface`](crate::rel::module::Module::set_skse_query_interface).
+/// If the load function panics, the panic is caught and the plugin reports the load failure to SKSE
+/// instead of unwinding across the FFI boundary.
+///
+/// Invoke this once at the root of a `cdylib` crate, since the symbols must be unique.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::rel::version::Version;
+///
+/// commonlibsse_ng::declare_plugin!("MyPlugin", "me", Version::new(1, 0, 0, 0), |_skse| {
+///     // Register hooks, listeners, etc.
+///     true
+/// });
+/// ```
+#[macro_export]
+macro_rules! declare_plugin {
+    ($name:literal, $author:literal, $version:expr, $load:expr $(,)?) => {
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static SKSEPlugin_Version: $crate::sys::root::SKSE::PluginVersionData =
+            $crate::sys::root::SKSE::PluginVersionData::new($name, $author, &$version);
+
+        /// # Safety
+        /// Called by SKSE with valid pointers.
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        pub unsafe extern "C" fn SKSEPlugin_Query(
+            skse: *const $crate::sys::root::SKSE::QueryInterface,
+            info: *mut $crate::sys::root::SKSE::PluginInfo,
+        ) -> bool {
+            unsafe {
+                $crate::skse::plugin::query(
+                    skse,
+                    info,
+                    concat!($name, "\0"),
+                    SKSEPlugin_Version.pluginVersion,
+                )
+            }
+        }
+
+        /// # Safety
+        /// Called by SKSE with a valid pointer.
+        #[no_mangle]
+        #[allow(non_snake_case)]
+        pub unsafe extern "C" fn SKSEPlugin_Load(
+            skse: *const $crate::sys::root::SKSE::LoadInterface,
+        ) -> bool {
+            const LOAD: fn(&$crate::sys::root::SKSE::LoadInterface) -> bool = $load;
+            unsafe { $crate::skse::plugin::load(skse, LOAD) }
+        }
+    };
+}
+
+/// Body of the generated `SKSEPlugin_Query`.
+///
+/// # Safety
+/// `skse` and `info` must be null or valid. `name` must end with NUL.
+#[doc(hidden)]
+pub unsafe fn query(
+    skse: *const SKSE::QueryInterface,
+    info: *mut SKSE::PluginInfo,
+    name: &'static str,
+    version: u32,
+) -> bool {
+    debug_assert!(name.ends_with('\0'));
+
+    if let Some(query) = unsafe { skse.as_ref() } {
+        Module::set_skse_query_interface(query);
+    }
+    let Some(info) = (unsafe { info.as_mut() }) else {
+        return false;
+    };
+    info.infoVersion = SKSE::PluginInfo_kVersion as u32;
+    info.name = name.as_ptr().cast();
+    info.version = version;
+    true
+}
+
+/// Body of the generated `SKSEPlugin_Load`.
+///
+/// # Safety
+/// `skse` must be null or valid.
+#[doc(hidden)]
+pub unsafe fn load(
+    skse: *const SKSE::LoadInterface,
+    load: fn(&SKSE::LoadInterface) -> bool,
+) -> bool {
+    let Some(skse) = (unsafe { skse.as_ref() }) else {
+        return false;
+    };
+    // SAFETY: `LoadInterface` derives from `QueryInterface`.
+    let query = unsafe { &*core::ptr::from_ref(skse).cast::<SKSE::QueryInterface>() };
+    Module::set_skse_query_interface(query);
+
+    std::panic::catch_unwind(|| load(skse)).unwrap_or_else(|_| {
+        #[cfg(feature = "tracing")]
+        tracing::error!("The plugin load function panicked.");
+        false
+    })
+}
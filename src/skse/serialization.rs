@@ -0,0 +1,302 @@
+//! Safe wrapper of the SKSE serialization interface to store per-save data in the co-save(`.skse`).
+//!
+//! A record is identified by a `(type, version)` pair, where `type` is usually a four-character code.(See [`record_type`])
+
+use crate::sys::root::SKSE;
+use core::ffi::c_void;
+use core::mem::{size_of, MaybeUninit};
+use std::sync::Mutex;
+
+/// Callback called with the serialization interface on save, load or revert.
+pub type Callback = fn(&SKSE::SerializationInterface);
+
+static SAVE: Mutex<Option<Callback>> = Mutex::new(None);
+static LOAD: Mutex<Option<Callback>> = Mutex::new(None);
+static REVERT: Mutex<Option<Callback>> = Mutex::new(None);
+
+/// Converts a four-character code into a record type. (Same as the C++ multi-character literal `'ABCD'`)
+///
+/// # Example
+/// ```
+/// use commonlibsse_ng::skse::serialization::record_type;
+///
+/// assert_eq!(record_type(b"ABCD"), 0x4142_4344);
+/// ```
+#[inline]
+pub const fn record_type(tag: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*tag)
+}
+
+/// Header of a record being read. (See [`SKSE::SerializationInterface::next_record`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RecordInfo {
+    /// Record type. (See [`record_type`])
+    pub record_type: u32,
+    /// Version of the record written by the plugin.
+    pub version: u32,
+    /// Length of the record data in bytes.
+    pub length: u32,
+}
+
+/// Sets the unique ID of this plugin that owns the records in the co-save. Call this before registering callbacks.
+///
+/// # Errors
+/// If the SKSE serialization interface is unavailable.
+pub fn set_unique_id(uid: u32) -> Result<(), SerializationError> {
+    unsafe { interface()?.SetUniqueID(uid) };
+    Ok(())
+}
+
+/// Registers `callback` to be called when the game is saved.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::skse::serialization::{record_type, register_save, set_unique_id};
+///
+/// set_unique_id(record_type(b"MYPL")).unwrap();
+/// register_save(|intfc| {
+///     let _ = intfc.write_record(record_type(b"CNT_"), 1, &42_u32.to_le_bytes());
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Errors
+/// If the SKSE serialization interface is unavailable.
+pub fn register_save(callback: Callback) -> Result<(), SerializationError> {
+    let interface = interface()?;
+    set_callback(&SAVE, callback);
+    unsafe { interface.SetSaveCallback(Some(on_save)) };
+    Ok(())
+}
+
+/// Registers `callback` to be called when a save game is loaded.
+///
+/// # Example
+/// ```no_run
+/// use commonlibsse_ng::skse::serialization::{record_type, register_load};
+///
+/// register_load(|intfc| {
+///     while let Some((info, data)) = intfc.read_record() {
+///         if info.record_type == record_type(b"CNT_") && info.version == 1 {
+///             println!("{data:?}");
+///         }
+///     }
+/// })
+/// .unwrap();
+/// ```
+///
+/// # Errors
+/// If the SKSE serialization interface is unavailable.
+pub fn register_load(callback: Callback) -> Result<(), SerializationError> {
+    let interface = interface()?;
+    set_callback(&LOAD, callback);
+    unsafe { interface.SetLoadCallback(Some(on_load)) };
+    Ok(())
+}
+
+/// Registers `callback` to be called when the game state is reverted(e.g. before loading another save).
+///
+/// # Errors
+/// If the SKSE serialization interface is unavailable.
+pub fn register_revert(callback: Callback) -> Result<(), SerializationError> {
+    let interface = interface()?;
+    set_callback(&REVERT, callback);
+    unsafe { interface.SetRevertCallback(Some(on_revert)) };
+    Ok(())
+}
+
+fn interface() -> Result<&'static SKSE::SerializationInterface, SerializationError> {
+    // SAFETY: `GetSerializationInterface` returns null or the interface that lives as long as the process.
+    unsafe { SKSE::GetSerializationInterface().as_ref() }.ok_or(SerializationError::NoInterface)
+}
+
+fn set_callback(slot: &Mutex<Option<Callback>>, callback: Callback) {
+    *slot.lock().unwrap_or_else(|err| err.into_inner()) = Some(callback);
+}
+
+fn call(slot: &Mutex<Option<Callback>>, interface: *mut SKSE::SerializationInterface) {
+    let callback = *slot.lock().unwrap_or_else(|err| err.into_inner());
+    // SAFETY: SKSE passes a valid interface during the callback.
+    if let (Some(callback), Some(interface)) = (callback, unsafe { interface.as_ref() }) {
+        callback(interface);
+    }
+}
+
+unsafe extern "C" fn on_save(interface: *mut SKSE::SerializationInterface) {
+    call(&SAVE, interface);
+}
+
+unsafe extern "C" fn on_load(interface: *mut SKSE::SerializationInterface) {
+    call(&LOAD, interface);
+}
+
+unsafe extern "C" fn on_revert(interface: *mut SKSE::SerializationInterface) {
+    call(&REVERT, interface);
+}
+
+impl SKSE::SerializationInterface {
+    /// Writes a whole record at once.
+    ///
+    /// # Errors
+    /// - If `data` is larger than `u32::MAX` bytes.
+    /// - If SKSE fails to write it. (e.g. called outside the save callback)
+    pub fn write_record(
+        &self,
+        record_type: u32,
+        version: u32,
+        data: &[u8],
+    ) -> Result<(), SerializationError> {
+        let length = data_length(data)?;
+        if unsafe { self.WriteRecord(record_type, version, data.as_ptr().cast(), length) } {
+            Ok(())
+        } else {
+            Err(SerializationError::WriteFailed { record_type })
+        }
+    }
+
+    /// Opens a record to be written piecewise by [`Self::write_record_data`].
+    ///
+    /// # Errors
+    /// If SKSE fails to open it. (e.g. called outside the save callback)
+    pub fn open_record(&self, record_type: u32, version: u32) -> Result<(), SerializationError> {
+        if unsafe { self.OpenRecord(record_type, version) } {
+            Ok(())
+        } else {
+            Err(SerializationError::WriteFailed { record_type })
+        }
+    }
+
+    /// Appends `data` to the record opened by [`Self::open_record`].
+    ///
+    /// # Errors
+    /// - If `data` is larger than `u32::MAX` bytes.
+    /// - If SKSE fails to write it.
+    pub fn write_record_data(&self, data: &[u8]) -> Result<(), SerializationError> {
+        let length = data_length(data)?;
+        if unsafe { self.WriteRecordData(data.as_ptr().cast(), length) } {
+            Ok(())
+        } else {
+            Err(SerializationError::WriteDataFailed { length })
+        }
+    }
+
+    /// Appends the bytes of `value` to the record opened by [`Self::open_record`].
+    ///
+    /// # Errors
+    /// Same as [`Self::write_record_data`].
+    ///
+    /// # Safety
+    /// `T` must have no padding bytes, since all `size_of::<T>()` bytes are read. (e.g. integers, `#[repr(C)]` structs without padding)
+    pub unsafe fn write_value<T>(&self, value: &T) -> Result<(), SerializationError>
+    where
+        T: Copy,
+    {
+        let data =
+            unsafe { core::slice::from_raw_parts((value as *const T).cast(), size_of::<T>()) };
+        self.write_record_data(data)
+    }
+
+    /// Moves to the next record and returns its header, or `None` if there are no more records.
+    pub fn next_record(&self) -> Option<RecordInfo> {
+        let (mut record_type, mut version, mut length) = (0, 0, 0);
+        if !unsafe { self.GetNextRecordInfo(&mut record_type, &mut version, &mut length) } {
+            return None;
+        }
+        Some(RecordInfo {
+            record_type,
+            version,
+            length,
+        })
+    }
+
+    /// Reads the data of the current record into `buf`, and returns the number of bytes read.
+    pub fn read_record_data(&self, buf: &mut [u8]) -> usize {
+        let length = u32::try_from(buf.len()).unwrap_or(u32::MAX);
+        unsafe { self.ReadRecordData(buf.as_mut_ptr().cast::<c_void>(), length) as usize }
+    }
+
+    /// Reads the next whole record. Returns `None` if there are no more records.
+    pub fn read_record(&self) -> Option<(RecordInfo, Vec<u8>)> {
+        let info = self.next_record()?;
+        let mut data = vec![0; info.length as usize];
+        let read = self.read_record_data(&mut data);
+        data.truncate(read);
+        Some((info, data))
+    }
+
+    /// Reads `T` from the current record. Returns `None` if the record has fewer bytes left than `size_of::<T>()`.
+    ///
+    /// # Safety
+    /// Any bit pattern must be a valid `T`. (e.g. integers, floats, `#[repr(C)]` structs of them. Not `bool`, `char`, references)
+    pub unsafe fn read_value<T>(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let mut value = MaybeUninit::<T>::uninit();
+        let buf = unsafe {
+            core::slice::from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), size_of::<T>())
+        };
+        if self.read_record_data(buf) != size_of::<T>() {
+            return None;
+        }
+        // SAFETY: All bytes are initialized above, and the caller guarantees that any bit pattern is valid.
+        Some(unsafe { value.assume_init() })
+    }
+
+    /// Resolves a form ID saved in the previous session to the one of the current load order.
+    ///
+    /// Returns `None` if the plugin of the form is no longer loaded.
+    pub fn resolve_form_id(&self, old_form_id: u32) -> Option<u32> {
+        let mut new_form_id = 0;
+        if !unsafe { self.ResolveFormID(old_form_id, &mut new_form_id) } {
+            return None;
+        }
+        Some(new_form_id)
+    }
+}
+
+fn data_length(data: &[u8]) -> Result<u32, SerializationError> {
+    u32::try_from(data.len()).map_err(|_| SerializationError::TooLarge { length: data.len() })
+}
+
+/// Error types for [`SKSE::SerializationInterface`] helpers.
+#[derive(Debug, Clone, PartialEq, Eq, snafu::Snafu)]
+pub enum SerializationError {
+    /// The SKSE serialization interface is unavailable. The plugin must be loaded by SKSE.
+    NoInterface,
+
+    /// Expected record data up to `u32::MAX` bytes, but got {length} bytes
+    TooLarge { length: usize },
+
+    /// Failed to write the record {record_type:#x}
+    WriteFailed { record_type: u32 },
+
+    /// Failed to write {length} bytes of record data
+    WriteDataFailed { length: u32 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_record_type() {
+        assert_eq!(record_type(b"ABCD"), 0x4142_4344);
+    }
+
+    #[test]
+    fn test_callback_dispatch() {
+        static CALLED: AtomicBool = AtomicBool::new(false);
+
+        set_callback(&REVERT, |_| CALLED.store(true, Ordering::Relaxed));
+        // Null is ignored.
+        unsafe { on_revert(core::ptr::null_mut()) };
+        assert!(!CALLED.load(Ordering::Relaxed));
+
+        // The interface is opaque, and the callback does not touch it here.
+        let mut interface = SKSE::SerializationInterface { _address: 0 };
+        unsafe { on_revert(&mut interface) };
+        assert!(CALLED.load(Ordering::Relaxed));
+    }
+}
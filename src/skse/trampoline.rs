@@ -2,16 +2,67 @@ use crate::sys::root::SKSE;
 
 /// - ref: vcpkg_installed\x64-windows\commonlibsse_ng\include\SKSE\Trampoline.h
 impl SKSE::Trampoline {
+    /// Size of the instruction and inline data written by [`Self::write_branch`] `N == 6`.
+    /// (`FF 25 00000000` + 8-byte target)
+    pub const ABS_BRANCH_SIZE: usize = 6 + 8;
+
+    /// Size of the instruction and inline data written by [`Self::write_call`] `N == 6`.
+    /// (`FF 15 02000000` + `EB 08` to skip the target on return + 8-byte target)
+    pub const ABS_CALL_SIZE: usize = 6 + 2 + 8;
+
+    /// Allocates `size` bytes from the trampoline buffer and returns its address.
+    ///
+    /// Returns `None` if the buffer is not allocated yet or its remaining capacity is insufficient.
+    /// (C++ throws instead)
+    pub fn allocate(&mut self, size: usize) -> Option<usize> {
+        let address = self.peek_allocate(size)?;
+        self._size += size as u64;
+        Some(address)
+    }
+
+    /// Returns the address that [`Self::allocate`] would return for `size` bytes, without allocating it.
+    ///
+    /// This allows validating the address(e.g. `rel32` range) before consuming the buffer.
+    pub fn peek_allocate(&self, size: usize) -> Option<usize> {
+        let end = self._size.checked_add(size as u64)?;
+        if self._data.is_null() || end > self._capacity {
+            return None;
+        }
+        Some(self._data as usize + self._size as usize)
+    }
+
+    /// Writes a jump to `a_dst` at `a_src`.
+    ///
+    /// - `N == 5`: `E9 rel32`
+    /// - `N == 6`: `FF 25 [rip+0]` followed by the 8-byte absolute target.
+    ///   This writes [`Self::ABS_BRANCH_SIZE`] bytes, not 6.
+    ///
+    /// Returns the address after the written bytes.
+    ///
+    /// # Safety
+    /// `a_src` must be writable for the written size.
     pub const unsafe fn write_branch<const N: usize>(a_src: usize, a_dst: usize) -> usize {
         let data: u8 = match N {
             5 => 0xE9, // JMP rel32
-            6 => 0x25, // JMP r/m64
+            6 => 0x25, // JMP r/m64 (ModRM of `FF /4`)
             _ => panic!("invalid branch size"),
         };
 
         Self::write_branch_with_data::<N>(a_src, a_dst, data)
     }
 
+    /// Writes a branch to `a_dst` at `a_src`.
+    ///
+    /// - `N == 5`: `opcode` is the opcode of `rel32` form. (`E9`: JMP, `E8`: CALL)
+    /// - `N == 6`: `opcode` is the ModRM of `FF /r` absolute indirect form. (`25`: JMP, `15`: CALL)
+    ///   The 8-byte target is placed inline right after the instruction, addressed by `[rip+disp32]`.
+    ///   For CALL, a short jump over the target is inserted so that the callee returns to a valid instruction.
+    ///
+    /// Returns the address after the written bytes.
+    ///
+    /// # Safety
+    /// `a_src` must be writable for the written size.
+    /// (`N == 6`: [`Self::ABS_BRANCH_SIZE`] for JMP, [`Self::ABS_CALL_SIZE`] for CALL)
     pub const unsafe fn write_branch_with_data<const N: usize>(
         a_src: usize,
         a_dst: usize,
@@ -21,34 +72,130 @@ impl SKSE::Trampoline {
 
         use std::ptr::write_unaligned;
 
+        /// ModRM of `FF /2`(CALL r/m64) with `[rip+disp32]`.
+        const MODRM_CALL: u8 = 0x15;
+        /// `EB rel8`: Short jump.
+        const JMP8: u8 = 0xEB;
+
         let src_ptr = a_src as *mut u8;
-        write_unaligned(src_ptr, opcode);
 
         if N == 5 {
             // Relative 32-bit displacement
+            write_unaligned(src_ptr, opcode);
             let displacement = (a_dst as isize - (a_src as isize + 5)) as i32;
             let displacement_ptr = src_ptr.add(1) as *mut i32;
             write_unaligned(displacement_ptr, displacement);
+            a_src + 5 // Return the address after the written instruction
         } else if N == 6 {
-            // Absolute 64-bit address
-            let displacement_ptr = src_ptr.add(1) as *mut u8;
-            write_unaligned(displacement_ptr, 0); // ModRM byte for indirect addressing
-            let address_ptr = src_ptr.add(2) as *mut usize;
-            write_unaligned(address_ptr, a_dst);
+            // Absolute 64-bit address: FF <ModRM> <disp32 from the next instruction to the target>
+            let is_call = opcode == MODRM_CALL;
+            let disp: i32 = if is_call { 2 } else { 0 };
+
+            write_unaligned(src_ptr, 0xFF);
+            write_unaligned(src_ptr.add(1), opcode);
+            write_unaligned(src_ptr.add(2) as *mut i32, disp);
+
+            if is_call {
+                // The callee returns to `src + 6`, so skip the inline target from there.
+                write_unaligned(src_ptr.add(6), JMP8);
+                write_unaligned(src_ptr.add(7), size_of::<usize>() as u8);
+                write_unaligned(src_ptr.add(8) as *mut usize, a_dst);
+                a_src + Self::ABS_CALL_SIZE
+            } else {
+                write_unaligned(src_ptr.add(6) as *mut usize, a_dst);
+                a_src + Self::ABS_BRANCH_SIZE
+            }
         } else {
             panic!("Invalid branch size");
         }
-
-        a_src + N // Return the address after the written instruction
     }
 
+    /// Writes a call to `a_dst` at `a_src`.
+    ///
+    /// - `N == 5`: `E8 rel32`
+    /// - `N == 6`: `FF 15 [rip+2]`, `EB 08` and the 8-byte absolute target.
+    ///   This writes [`Self::ABS_CALL_SIZE`] bytes, not 6.
+    ///
+    /// Returns the address after the written bytes.
+    ///
+    /// # Safety
+    /// `a_src` must be writable for the written size.
     pub const unsafe fn write_call<const N: usize>(a_src: usize, a_dst: usize) -> usize {
         let data: u8 = match N {
             5 => 0xE8, // CALL rel32
-            6 => 0x15, // CALL r/m64
+            6 => 0x15, // CALL r/m64 (ModRM of `FF /2`)
             _ => panic!("invalid call size"),
         };
 
         Self::write_branch_with_data::<N>(a_src, a_dst, data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DST: usize = 0x1122_3344_5566_7788;
+
+    #[test]
+    fn test_allocate() {
+        let mut buf = [0_u8; 16];
+        // SAFETY: All fields of the bindgen struct are valid when zeroed.
+        let mut trampoline: SKSE::Trampoline = unsafe { core::mem::zeroed() };
+        assert_eq!(trampoline.allocate(1), None); // not allocated
+
+        trampoline._data = buf.as_mut_ptr().cast();
+        trampoline._capacity = buf.len() as u64;
+        let base = buf.as_ptr() as usize;
+        assert_eq!(trampoline.peek_allocate(10), Some(base));
+        assert_eq!(trampoline.allocate(10), Some(base));
+        assert_eq!(trampoline.peek_allocate(7), None);
+        assert_eq!(trampoline.allocate(6), Some(base + 10));
+        assert_eq!(trampoline.allocate(1), None);
+    }
+
+    #[test]
+    fn test_write_branch_rel32() {
+        let mut buf = [0xCC_u8; 8];
+        let src = buf.as_mut_ptr() as usize;
+        let dst = src + 0x100;
+
+        let next = unsafe { SKSE::Trampoline::write_branch::<5>(src, dst) };
+        assert_eq!(next, src + 5);
+        assert_eq!(buf[0], 0xE9);
+        // rel32 is relative to the next instruction.
+        assert_eq!(i32::from_le_bytes(buf[1..5].try_into().unwrap()), 0x100 - 5);
+        assert_eq!(buf[5], 0xCC);
+    }
+
+    #[test]
+    fn test_write_branch_abs64() {
+        let mut buf = [0xCC_u8; 16];
+        let src = buf.as_mut_ptr() as usize;
+
+        let next = unsafe { SKSE::Trampoline::write_branch::<6>(src, DST) };
+        assert_eq!(next, src + SKSE::Trampoline::ABS_BRANCH_SIZE);
+
+        // jmp qword ptr [rip+0]
+        assert_eq!(buf[..6], [0xFF, 0x25, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(usize::from_le_bytes(buf[6..14].try_into().unwrap()), DST);
+        assert_eq!(buf[14..], [0xCC, 0xCC]);
+    }
+
+    #[test]
+    fn test_write_call_abs64() {
+        let mut buf = [0xCC_u8; 17];
+        let src = buf.as_mut_ptr() as usize;
+
+        let next = unsafe { SKSE::Trampoline::write_call::<6>(src, DST) };
+        assert_eq!(next, src + SKSE::Trampoline::ABS_CALL_SIZE);
+
+        // call qword ptr [rip+2]
+        assert_eq!(buf[..6], [0xFF, 0x15, 0x02, 0x00, 0x00, 0x00]);
+        // jmp short +8 (return address lands here and skips the target)
+        assert_eq!(buf[6..8], [0xEB, 0x08]);
+        // The target is at `rip(= src + 6) + 2`.
+        assert_eq!(usize::from_le_bytes(buf[8..16].try_into().unwrap()), DST);
+        assert_eq!(buf[16], 0xCC);
+    }
+}
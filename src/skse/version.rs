@@ -47,3 +47,36 @@ pub const RUNTIME_SSE_LATEST: Version = RUNTIME_SSE_LATEST_AE;
 
 pub const RUNTIME_VR_1_4_15: Version = Version::new(1, 4, 15, 0);
 pub const RUNTIME_LATEST_VR: Version = RUNTIME_VR_1_4_15;
+
+/// All the runtime versions above, from oldest to newest. (SE, AE, then VR)
+pub const KNOWN_RUNTIMES: [Version; 29] = [
+    RUNTIME_SSE_1_1_47,
+    RUNTIME_SSE_1_1_51,
+    RUNTIME_SSE_1_2_36,
+    RUNTIME_SSE_1_2_39,
+    RUNTIME_SSE_1_3_5,
+    RUNTIME_SSE_1_3_9,
+    RUNTIME_SSE_1_4_2,
+    RUNTIME_SSE_1_5_3,
+    RUNTIME_SSE_1_5_16,
+    RUNTIME_SSE_1_5_23,
+    RUNTIME_SSE_1_5_39,
+    RUNTIME_SSE_1_5_50,
+    RUNTIME_SSE_1_5_53,
+    RUNTIME_SSE_1_5_62,
+    RUNTIME_SSE_1_5_73,
+    RUNTIME_SSE_1_5_80,
+    RUNTIME_SSE_1_5_97,
+    RUNTIME_SSE_1_6_317,
+    RUNTIME_SSE_1_6_318,
+    RUNTIME_SSE_1_6_323,
+    RUNTIME_SSE_1_6_342,
+    RUNTIME_SSE_1_6_353,
+    RUNTIME_SSE_1_6_629,
+    RUNTIME_SSE_1_6_640,
+    RUNTIME_SSE_1_6_659,
+    RUNTIME_SSE_1_6_678,
+    RUNTIME_SSE_1_6_1130,
+    RUNTIME_SSE_1_6_1170,
+    RUNTIME_VR_1_4_15,
+];